@@ -6,7 +6,11 @@ mod bindings {
     use mpc_driver::{
         meeting, MeetingOptions, PrivateKey, SessionOptions,
     };
-    use mpc_protocol::{hex, MeetingId, UserId, PATTERN};
+    use mpc_protocol::{
+        ed25519_dalek::{SigningKey, VerifyingKey},
+        hex, JoinCode, MeetingId, MeetingSlot, UserId, PATTERN,
+    };
+    use serde::Deserialize;
     use serde_json::Value;
     use wasm_bindgen::prelude::*;
     use wasm_bindgen_futures::future_to_promise;
@@ -131,37 +135,56 @@ mod bindings {
     }
 
     /// Create a meeting point used to exchange public keys.
+    ///
+    /// Each slot is a hex-encoded user identifier or an object
+    /// with the `userId` and an optional `role` and hex-encoded
+    /// `verifyingKey` that binds the slot to an identity.
+    ///
+    /// Resolves to the meeting identifier and the secrets
+    /// participants need to join the meeting.
     #[wasm_bindgen(js_name = "createMeeting")]
     pub fn create_meeting(
         options: JsValue,
-        identifiers: JsValue,
+        slots: JsValue,
         initiator: String,
+        invitations: bool,
         data: JsValue,
     ) -> Result<JsValue, JsError> {
         let options: MeetingOptions =
             serde_wasm_bindgen::from_value(options)?;
-        let identifiers = parse_user_identifiers(identifiers)?;
+        let slots = parse_meeting_slots(slots)?;
         let initiator = parse_user_id(initiator)?;
         let data: Value = serde_wasm_bindgen::from_value(data)?;
         let fut = async move {
-            let meeting_id = meeting::create(
+            let meeting = meeting::create(
                 options,
-                identifiers,
+                slots,
                 initiator,
+                invitations,
                 data,
             )
             .await?;
-            Ok(serde_wasm_bindgen::to_value(&meeting_id)?)
+            Ok(serde_wasm_bindgen::to_value(&meeting)?)
         };
         Ok(future_to_promise(fut).into())
     }
 
     /// Join a meeting point used to exchange public keys.
+    ///
+    /// The optional hex-encoded signing key is used to join
+    /// a slot bound to a verifying key.
+    ///
+    /// The slots must be the slots passed to `createMeeting`,
+    /// participants are verified against these slots rather
+    /// than the verifying keys returned by the relay.
     #[wasm_bindgen(js_name = "joinMeeting")]
     pub fn join_meeting(
         options: JsValue,
         meeting_id: String,
         user_id: JsValue,
+        join_code: String,
+        signing_key: JsValue,
+        slots: JsValue,
     ) -> Result<JsValue, JsError> {
         let options: MeetingOptions =
             serde_wasm_bindgen::from_value(options)?;
@@ -174,10 +197,26 @@ mod bindings {
         } else {
             None
         };
+        let join_code = parse_join_code(join_code)?;
+        let signing_key: Option<String> =
+            serde_wasm_bindgen::from_value(signing_key)?;
+        let signing_key = if let Some(signing_key) = signing_key {
+            Some(parse_signing_key(signing_key)?)
+        } else {
+            None
+        };
+        let slots = parse_meeting_slots(slots)?;
 
         let fut = async move {
-            let (public_keys, data) =
-                meeting::join(options, meeting_id, user_id).await?;
+            let (public_keys, data) = meeting::join(
+                options,
+                meeting_id,
+                user_id,
+                join_code,
+                signing_key,
+                slots,
+            )
+            .await?;
             let public_keys: Vec<String> = public_keys
                 .into_iter()
                 .map(|v| hex::encode(v))
@@ -187,17 +226,73 @@ mod bindings {
         Ok(future_to_promise(fut).into())
     }
 
-    /// Parse a collection of user identifiers.
-    fn parse_user_identifiers(
-        identifiers: JsValue,
-    ) -> Result<Vec<UserId>, JsError> {
-        let identifiers: Vec<String> =
-            serde_wasm_bindgen::from_value(identifiers)?;
-        let mut ids = Vec::new();
-        for id in identifiers {
-            ids.push(parse_user_id(id)?);
+    /// Slot for a meeting point passed from Javascript.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum JsMeetingSlot {
+        UserId(String),
+        #[serde(rename_all = "camelCase")]
+        Slot {
+            user_id: String,
+            role: Option<String>,
+            verifying_key: Option<String>,
+        },
+    }
+
+    /// Parse a collection of meeting slots.
+    fn parse_meeting_slots(
+        slots: JsValue,
+    ) -> Result<Vec<MeetingSlot>, JsError> {
+        let slots: Vec<JsMeetingSlot> =
+            serde_wasm_bindgen::from_value(slots)?;
+        let mut parsed = Vec::new();
+        for slot in slots {
+            parsed.push(match slot {
+                JsMeetingSlot::UserId(id) => {
+                    parse_user_id(id)?.into()
+                }
+                JsMeetingSlot::Slot {
+                    user_id,
+                    role,
+                    verifying_key,
+                } => MeetingSlot {
+                    user_id: parse_user_id(user_id)?,
+                    role,
+                    verifying_key: if let Some(key) = verifying_key {
+                        Some(parse_verifying_key(key)?)
+                    } else {
+                        None
+                    },
+                },
+            });
         }
-        Ok(ids)
+        Ok(parsed)
+    }
+
+    /// Parse a hex-encoded meeting join code.
+    fn parse_join_code(code: String) -> Result<JoinCode, JsError> {
+        let code = hex::decode(code).map_err(JsError::from)?;
+        let code: [u8; 32] =
+            code.as_slice().try_into().map_err(JsError::from)?;
+        Ok(code.into())
+    }
+
+    /// Parse a hex-encoded ed25519 signing key.
+    fn parse_signing_key(key: String) -> Result<SigningKey, JsError> {
+        let key = hex::decode(key).map_err(JsError::from)?;
+        let key: [u8; 32] =
+            key.as_slice().try_into().map_err(JsError::from)?;
+        Ok(SigningKey::from_bytes(&key))
+    }
+
+    /// Parse a hex-encoded ed25519 verifying key.
+    fn parse_verifying_key(
+        key: String,
+    ) -> Result<VerifyingKey, JsError> {
+        let key = hex::decode(key).map_err(JsError::from)?;
+        let key: [u8; 32] =
+            key.as_slice().try_into().map_err(JsError::from)?;
+        VerifyingKey::from_bytes(&key).map_err(JsError::from)
     }
 
     /// Parse a single hex-encoded user identifier (SHA256 checksum).
@@ -226,10 +226,18 @@ macro_rules! client_transport_impl {
             async fn new_meeting(
                 &mut self,
                 owner_id: UserId,
-                slots: HashSet<UserId>,
+                slots: Vec<MeetingSlot>,
+                invitations: bool,
+                join_code: Option<JoinCode>,
                 data: Value,
             ) -> Result<()> {
-                let message = ServerMessage::NewMeeting { owner_id, slots, data };
+                let message = ServerMessage::NewMeeting {
+                    owner_id,
+                    slots,
+                    invitations,
+                    join_code,
+                    data,
+                };
                 self.request(message).await
             }
 
@@ -238,8 +246,33 @@ macro_rules! client_transport_impl {
                 &mut self,
                 meeting_id: MeetingId,
                 user_id: UserId,
+                join_code: JoinCode,
+                signature: Option<Signature>,
+            ) -> Result<()> {
+                let message = ServerMessage::JoinMeeting(
+                    meeting_id,
+                    user_id,
+                    join_code,
+                    signature,
+                );
+                self.request(message).await
+            }
+
+            /// Leave a meeting point.
+            async fn leave_meeting(
+                &mut self,
+                meeting_id: MeetingId,
             ) -> Result<()> {
-                let message = ServerMessage::JoinMeeting(meeting_id, user_id);
+                let message = ServerMessage::LeaveMeeting(meeting_id);
+                self.request(message).await
+            }
+
+            /// Cancel a meeting point.
+            async fn cancel_meeting(
+                &mut self,
+                meeting_id: MeetingId,
+            ) -> Result<()> {
+                let message = ServerMessage::CancelMeeting(meeting_id);
                 self.request(message).await
             }
 
@@ -247,8 +280,14 @@ macro_rules! client_transport_impl {
             async fn new_session(
                 &mut self,
                 participant_keys: Vec<Vec<u8>>,
+                quorum: Option<SessionQuorum>,
+                limits: SessionLimits,
             ) -> Result<()> {
-                let session = SessionRequest { participant_keys };
+                let session = SessionRequest {
+                    participant_keys,
+                    quorum,
+                    limits,
+                };
                 let message = ServerMessage::NewSession(session);
                 self.request(message).await
             }
@@ -266,6 +305,15 @@ macro_rules! client_transport_impl {
                 self.request(message).await
             }
 
+            /// Join a session this client was invited to.
+            async fn join_session(
+                &mut self,
+                session_id: SessionId,
+            ) -> Result<()> {
+                let message = ServerMessage::JoinSession(session_id);
+                self.request(message).await
+            }
+
             /// Close a session.
             async fn close_session(
                 &mut self,
@@ -275,6 +323,30 @@ macro_rules! client_transport_impl {
                 self.request(message).await
             }
 
+            /// Request the status of a session.
+            async fn get_session(
+                &mut self,
+                session_id: SessionId,
+            ) -> Result<()> {
+                let message = ServerMessage::GetSession(session_id);
+                self.request(message).await
+            }
+
+            /// Request the state of a meeting.
+            async fn get_meeting(
+                &mut self,
+                meeting_id: MeetingId,
+            ) -> Result<()> {
+                let message = ServerMessage::GetMeeting(meeting_id);
+                self.request(message).await
+            }
+
+            /// List the sessions this client participates in.
+            async fn list_sessions(&mut self) -> Result<()> {
+                let message = ServerMessage::ListSessions;
+                self.request(message).await
+            }
+
             /// Broadcast a JSON message in the context of a session.
             async fn broadcast_json<S>(
                 &mut self,
@@ -318,13 +390,9 @@ macro_rules! client_transport_impl {
 
             #[cfg(target_arch="wasm32")]
             async fn close(&self) -> Result<()> {
-                // Remove event listener closures
-                self.ws.set_onopen(None);
-                self.ws.set_onmessage(None);
-                self.ws.set_onerror(None);
-
-                // Close the socket connection
-                self.ws.close()?;
+                // Remove event listener closures and close
+                // the connection
+                self.close_connection()?;
 
                 // Must also dispatch the close event for the driver
                 self.outbound_tx.send(InternalMessage::Close).await?;
@@ -1,5 +1,5 @@
 use crate::event_loop::InternalMessage;
-use mpc_protocol::{http::StatusCode, ResponseMessage};
+use mpc_protocol::{http::StatusCode, ErrorCode, ResponseMessage};
 use thiserror::Error;
 
 /// Errors generated by the relay client.
@@ -11,9 +11,47 @@ pub enum Error {
     #[error("{0} {1}")]
     ConnectError(StatusCode, String),
 
-    /// Error generated by the server.
-    #[error("{0} {1}")]
-    ServerError(StatusCode, String),
+    /// Error generated by the server that does not have
+    /// a more specific variant.
+    #[error("{1}")]
+    ServerError(ErrorCode, String),
+
+    /// Server denied permission for the request.
+    #[error("{0}")]
+    PermissionDenied(String),
+
+    /// Server rejected the request due to rate limiting.
+    #[error("{0}")]
+    RateLimited(String),
+
+    /// Target peer is not connected to the server.
+    #[error("{0}")]
+    PeerOffline(String),
+
+    /// Another connection using the same public key is
+    /// active on the server.
+    #[error("{0}")]
+    DuplicateConnection(String),
+
+    /// Session does not exist on the server.
+    #[error("{0}")]
+    SessionNotFound(String),
+
+    /// Peer is not a participant in the session.
+    #[error("{0}")]
+    NotSessionParticipant(String),
+
+    /// Meeting does not exist on the server.
+    #[error("{0}")]
+    MeetingNotFound(String),
+
+    /// Meeting does not have any available slots.
+    #[error("{0}")]
+    MeetingFull(String),
+
+    /// Peer has not joined the meeting.
+    #[error("{0}")]
+    NotMeetingParticipant(String),
 
     /// Error generated if the client expects a reply but none was received.
     #[error("server did not reply")]
@@ -50,10 +88,40 @@ pub enum Error {
     #[error("invalid peer handshake message")]
     InvalidPeerHandshakeMessage,
 
+    /// Error generated when the event loop stream ends because
+    /// the connection to the server was lost.
+    #[error("connection to the server was lost")]
+    ConnectionLost,
+
     /// Error generated when the client fails to write to the websocket.
     #[error("web socket failed to send")]
     WebSocketSend,
 
+    /// Error generated when the HTTP client fails to send
+    /// a request to the server.
+    #[error("http request failed to send")]
+    HttpSend,
+
+    /// Error generated when the event stream for the HTTP
+    /// client does not start with a connect event.
+    #[error("invalid event stream, expected connect event")]
+    InvalidEventStream,
+
+    /// Error generated when the server URL does not have
+    /// a valid host name.
+    #[error("invalid server url")]
+    InvalidServerUrl,
+
+    /// Error generated when the QUIC client fails to send
+    /// a frame to the server.
+    #[error("quic frame failed to send")]
+    QuicSend,
+
+    /// Error generated when a QUIC frame exceeds the maximum
+    /// frame size.
+    #[error("quic frame of {0} bytes is too large")]
+    FrameTooLarge(usize),
+
     /// Javascript string error message.
     #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
     #[error("{0}")]
@@ -74,6 +142,11 @@ pub enum Error {
     #[error("stream and sink reunite failed")]
     StreamReunite,
 
+    /// Error generated when the client certificate
+    /// does not have a private key.
+    #[error("no private key for the client certificate")]
+    NoPrivateKey,
+
     /// Error generated by input/output.
     #[error(transparent)]
     Io(#[from] std::io::Error),
@@ -90,6 +163,50 @@ pub enum Error {
     #[error(transparent)]
     Json(#[from] serde_json::Error),
 
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    /// Error generated by the TLS library.
+    #[error(transparent)]
+    Rustls(#[from] rustls::Error),
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    /// Error generated by the HTTP library.
+    #[error(transparent)]
+    Hyper(#[from] hyper::Error),
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    /// Error generated building an HTTP request.
+    #[error(transparent)]
+    HttpRequest(#[from] hyper::http::Error),
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    /// Error generated parsing a URI.
+    #[error(transparent)]
+    InvalidUri(#[from] hyper::http::uri::InvalidUri),
+
+    /// Error generated decoding base64.
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    /// Error generated connecting a QUIC endpoint.
+    #[error(transparent)]
+    QuicConnect(#[from] quinn::ConnectError),
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    /// Error generated by a QUIC connection.
+    #[error(transparent)]
+    QuicConnection(#[from] quinn::ConnectionError),
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    /// Error generated writing to a QUIC stream.
+    #[error(transparent)]
+    QuicWrite(#[from] quinn::WriteError),
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    /// Error generated reading from a QUIC stream.
+    #[error(transparent)]
+    QuicRead(#[from] quinn::ReadExactError),
+
     #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
     /// Error generated by the client websocket library.
     #[error(transparent)]
@@ -108,6 +225,71 @@ pub enum Error {
     ),
 }
 
+impl Error {
+    /// Error code when this error was sent by the server.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Error::ServerError(code, _) => Some(*code),
+            Error::PermissionDenied(_) => {
+                Some(ErrorCode::PermissionDenied)
+            }
+            Error::RateLimited(_) => Some(ErrorCode::RateLimited),
+            Error::PeerOffline(_) => Some(ErrorCode::PeerOffline),
+            Error::DuplicateConnection(_) => {
+                Some(ErrorCode::DuplicateConnection)
+            }
+            Error::SessionNotFound(_) => {
+                Some(ErrorCode::SessionNotFound)
+            }
+            Error::NotSessionParticipant(_) => {
+                Some(ErrorCode::NotSessionParticipant)
+            }
+            Error::MeetingNotFound(_) => {
+                Some(ErrorCode::MeetingNotFound)
+            }
+            Error::MeetingFull(_) => Some(ErrorCode::MeetingFull),
+            Error::NotMeetingParticipant(_) => {
+                Some(ErrorCode::NotMeetingParticipant)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<(ErrorCode, String)> for Error {
+    fn from(value: (ErrorCode, String)) -> Self {
+        let (code, message) = value;
+        match code {
+            ErrorCode::PermissionDenied => {
+                Error::PermissionDenied(message)
+            }
+            ErrorCode::RateLimited => Error::RateLimited(message),
+            ErrorCode::PeerOffline => Error::PeerOffline(message),
+            ErrorCode::DuplicateConnection => {
+                Error::DuplicateConnection(message)
+            }
+            ErrorCode::SessionNotFound => {
+                Error::SessionNotFound(message)
+            }
+            ErrorCode::NotSessionParticipant => {
+                Error::NotSessionParticipant(message)
+            }
+            ErrorCode::MeetingNotFound => {
+                Error::MeetingNotFound(message)
+            }
+            ErrorCode::MeetingFull => Error::MeetingFull(message),
+            ErrorCode::NotMeetingParticipant => {
+                Error::NotMeetingParticipant(message)
+            }
+            ErrorCode::Internal
+            | ErrorCode::BadRequest
+            | ErrorCode::Unknown(_) => {
+                Error::ServerError(code, message)
+            }
+        }
+    }
+}
+
 #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
 impl From<wasm_bindgen::JsValue> for Error {
     fn from(value: wasm_bindgen::JsValue) -> Self {
@@ -1,15 +1,13 @@
-use futures::{
-    sink::SinkExt,
-    stream::{BoxStream, Stream},
-};
+use futures::stream::{BoxStream, Stream};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 
 use mpc_protocol::{
     channel::decrypt_server_channel, decode, hex, snow::Builder,
-    Encoding, HandshakeMessage, MeetingState, OpaqueMessage,
-    ProtocolState, RequestMessage, ResponseMessage, SealedEnvelope,
-    ServerMessage, SessionId, SessionState, TransparentMessage,
+    Encoding, HandshakeMessage, MeetingId, MeetingSecret,
+    MeetingState, OpaqueMessage, ProtocolState, RequestMessage,
+    ResponseMessage, SealedEnvelope, ServerMessage, SessionId,
+    SessionState, SessionStatus, TransparentMessage,
 };
 
 use super::{decrypt_peer_channel, Peers, Server};
@@ -53,7 +51,9 @@ pub enum Event {
     },
 
     /// Event dispatched when a meeting has been created.
-    MeetingCreated(MeetingState),
+    ///
+    /// Includes the secrets participants need to join.
+    MeetingCreated(MeetingState, MeetingSecret),
 
     /// Event dispatched when a meeting is ready.
     ///
@@ -61,13 +61,29 @@ pub enum Event {
     /// has been reached.
     MeetingReady(MeetingState),
 
+    /// Event dispatched when the owner of a meeting
+    /// cancels the meeting.
+    MeetingCancelled(MeetingId),
+
+    /// Event dispatched when a meeting expired before
+    /// all participants joined.
+    MeetingExpired(MeetingId),
+
     /// Event dispatched when a session has been created.
     SessionCreated(SessionState),
 
+    /// Event dispatched when this client is invited to
+    /// a session.
+    ///
+    /// Join the session to take part in it.
+    SessionInvitation(SessionState),
+
     /// Event dispatched when a session is ready.
     ///
-    /// A session is ready when all participants
-    /// have completed the server handshake.
+    /// A session is ready when all participants have
+    /// joined the session, or when a quorum has joined by
+    /// the deadline in which case participants that are not
+    /// taking part also receive this event.
     ///
     /// Peers can now handshake with each other.
     SessionReady(SessionState),
@@ -88,6 +104,47 @@ pub enum Event {
     /// explicitly closes the session.
     SessionFinished(SessionId),
 
+    /// Event dispatched with the status of a session
+    /// in response to a session query.
+    SessionInfo(SessionStatus),
+
+    /// Event dispatched with the state of a meeting
+    /// in response to a meeting query.
+    MeetingInfo(MeetingState),
+
+    /// Event dispatched with the status of the sessions
+    /// this client participates in.
+    SessionList(Vec<SessionStatus>),
+
+    /// Event dispatched when a session expired.
+    SessionExpired(SessionId),
+
+    /// Event dispatched when a session is about to expire
+    /// with the number of seconds remaining.
+    ///
+    /// Sending a message in the session context resets
+    /// the session timeout but not the maximum lifetime
+    /// which expires the session regardless of activity.
+    SessionExpiring(SessionId, u64),
+
+    /// Event dispatched when a meeting is about to expire
+    /// with the number of seconds remaining.
+    MeetingExpiring(MeetingId, u64),
+
+    /// Event dispatched when the server has replaced this
+    /// connection with a newer connection that uses the
+    /// same public key.
+    ///
+    /// The server closes the socket after this event.
+    ConnectionReplaced,
+
+    /// Event dispatched when the connection to the server
+    /// was lost.
+    ///
+    /// The socket was closed by the server or no messages
+    /// were received within the idle timeout.
+    ConnectionLost,
+
     /// Event dispatched when the socket is closed.
     Close,
 }
@@ -124,7 +181,7 @@ where
     M: Send,
     E: Send,
     R: Stream<Item = std::result::Result<M, E>> + Unpin,
-    W: SinkExt<M> + Unpin,
+    W: Unpin,
 {
     pub(crate) options: Arc<ClientOptions>,
     pub(crate) ws_reader: R,
@@ -142,8 +199,36 @@ where
     M: Send,
     E: Send,
     R: Stream<Item = std::result::Result<M, E>> + Unpin,
-    W: SinkExt<M> + Unpin,
+    W: Unpin,
 {
+    /// Handle messages that were decoded but not yet read
+    /// from the inbound channel.
+    ///
+    /// Called when the connection is lost so that messages
+    /// sent before the socket closed (eg: connection replaced)
+    /// are not dropped.
+    pub(crate) async fn drain_incoming(
+        &mut self,
+    ) -> Vec<Result<Event>> {
+        let mut events = Vec::new();
+        while let Ok(incoming) = self.inbound_rx.try_recv() {
+            match Self::handle_incoming_message(
+                Arc::clone(&self.options),
+                Arc::clone(&self.server),
+                Arc::clone(&self.peers),
+                incoming,
+                self.outbound_tx.clone(),
+            )
+            .await
+            {
+                Ok(Some(event)) => events.push(Ok(event)),
+                Err(e) => events.push(Err(e)),
+                Ok(None) => {}
+            }
+        }
+        events
+    }
+
     pub(crate) async fn handle_incoming_message(
         options: Arc<ClientOptions>,
         server: Server,
@@ -154,7 +239,7 @@ where
         match incoming {
             ResponseMessage::Transparent(
                 TransparentMessage::Error(code, message),
-            ) => Err(Error::ServerError(code, message)),
+            ) => Err(Error::from((code, message))),
             ResponseMessage::Transparent(
                 TransparentMessage::ServerHandshake(
                     HandshakeMessage::Responder(len, buf),
@@ -233,17 +318,26 @@ where
     ) -> Result<Option<Event>> {
         match message {
             ServerMessage::Error(code, message) => {
-                Err(Error::ServerError(code, message))
+                Err(Error::from((code, message)))
             }
-            ServerMessage::MeetingCreated(response) => {
-                Ok(Some(Event::MeetingCreated(response)))
+            ServerMessage::MeetingCreated(response, secret) => {
+                Ok(Some(Event::MeetingCreated(response, secret)))
             }
             ServerMessage::MeetingReady(response) => {
                 Ok(Some(Event::MeetingReady(response)))
             }
+            ServerMessage::MeetingCancelled(meeting_id) => {
+                Ok(Some(Event::MeetingCancelled(meeting_id)))
+            }
+            ServerMessage::MeetingExpired(meeting_id) => {
+                Ok(Some(Event::MeetingExpired(meeting_id)))
+            }
             ServerMessage::SessionCreated(response) => {
                 Ok(Some(Event::SessionCreated(response)))
             }
+            ServerMessage::SessionInvitation(response) => {
+                Ok(Some(Event::SessionInvitation(response)))
+            }
             ServerMessage::SessionReady(response) => {
                 Ok(Some(Event::SessionReady(response)))
             }
@@ -256,6 +350,33 @@ where
             ServerMessage::SessionFinished(session_id) => {
                 Ok(Some(Event::SessionFinished(session_id)))
             }
+            ServerMessage::SessionInfo(status) => {
+                Ok(Some(Event::SessionInfo(status)))
+            }
+            ServerMessage::MeetingInfo(meeting) => {
+                Ok(Some(Event::MeetingInfo(meeting)))
+            }
+            ServerMessage::SessionList(sessions) => {
+                Ok(Some(Event::SessionList(sessions)))
+            }
+            ServerMessage::SessionExpired(session_id) => {
+                Ok(Some(Event::SessionExpired(session_id)))
+            }
+            ServerMessage::SessionExpiring(
+                session_id,
+                expires_in,
+            ) => Ok(Some(Event::SessionExpiring(
+                session_id, expires_in,
+            ))),
+            ServerMessage::MeetingExpiring(
+                meeting_id,
+                expires_in,
+            ) => Ok(Some(Event::MeetingExpiring(
+                meeting_id, expires_in,
+            ))),
+            ServerMessage::ConnectionReplaced => {
+                Ok(Some(Event::ConnectionReplaced))
+            }
             _ => Ok(None),
         }
     }
@@ -420,12 +541,21 @@ macro_rules! event_loop_run_impl {
             let peers = Arc::clone(&self.peers);
 
             let s = stream! {
+                let mut idle = Self::idle_timeout(&options);
                 loop {
                     select!(
+                        _ = (&mut idle).fuse() => {
+                            for event in self.drain_incoming().await {
+                                yield event;
+                            }
+                            yield Ok(Event::ConnectionLost);
+                            break;
+                        },
                         message_in =
                             self.ws_reader.next().fuse()
                                 => match message_in {
                             Some(message) => {
+                                idle = Self::idle_timeout(&options);
                                 match message {
                                     Ok(message) => {
                                         if let Err(e) = Self::read_message(
@@ -440,7 +570,13 @@ macro_rules! event_loop_run_impl {
                                     }
                                 }
                             }
-                            _ => {}
+                            None => {
+                                for event in self.drain_incoming().await {
+                                    yield event;
+                                }
+                                yield Ok(Event::ConnectionLost);
+                                break;
+                            }
                         },
                         message_out =
                             self.outbound_rx.recv().fuse()
@@ -0,0 +1,470 @@
+use async_stream::stream;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::{
+    channel::mpsc as futures_mpsc,
+    future::{self, BoxFuture},
+    select,
+    sink::SinkExt,
+    stream::BoxStream,
+    FutureExt, StreamExt,
+};
+use hyper::{
+    body::HttpBody,
+    client::conn::{self, SendRequest},
+    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, HOST},
+    http::request,
+    Body, Method, Request, Uri,
+};
+use rustls::ServerName;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+    sync::{mpsc, RwLock},
+};
+use tokio_rustls::TlsConnector;
+
+use mpc_protocol::{
+    channel::encrypt_server_channel, decode,
+    ed25519_dalek::Signature, encode, hex, snow::Builder, zlib,
+    Encoding, HandshakeMessage, JoinCode, MeetingId, MeetingSlot,
+    OpaqueMessage, ProtocolState, RequestMessage, ResponseMessage,
+    ServerMessage, SessionId, SessionLimits, SessionQuorum,
+    SessionRequest, TransparentMessage, UserId,
+};
+
+use super::{
+    encrypt_peer_channel,
+    event_loop::{
+        event_loop_run_impl, EventLoop, EventStream, InternalMessage,
+    },
+    native::tls_config,
+    Peers, Server,
+};
+use crate::{
+    client_impl, client_transport_impl, ClientOptions, Error, Event,
+    Result,
+};
+
+type HttpError = Error;
+type HttpReadStream = BoxStream<'static, Result<HttpFrame>>;
+type HttpWriteStream = HttpWriter;
+type RequestSender = futures_mpsc::Sender<Request<Body>>;
+
+/// Event loop for the HTTP client.
+pub type HttpEventLoop =
+    EventLoop<HttpFrame, HttpError, HttpReadStream, HttpWriteStream>;
+
+/// Frame received on the event stream.
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum HttpFrame {
+    /// Compressed response message.
+    Message(Vec<u8>),
+    /// Ping from the server.
+    Ping,
+}
+
+/// Relay service client for the HTTP transport.
+///
+/// Response messages are received on a server-sent event
+/// stream and request messages are sent in POST requests
+/// for networks that block websocket connections.
+#[derive(Clone)]
+pub struct HttpClient {
+    options: Arc<ClientOptions>,
+    outbound_tx: mpsc::Sender<InternalMessage>,
+    server: Server,
+    peers: Peers,
+}
+
+impl HttpClient {
+    /// Create a new HTTP client.
+    ///
+    /// The server URL uses the `http` or `https` scheme
+    /// and may include the path prefix for the server.
+    pub async fn new(
+        server: &str,
+        options: ClientOptions,
+    ) -> Result<(Self, HttpEventLoop)> {
+        let server = server.trim_end_matches('/');
+        let public_key = hex::encode(options.keypair.public_key());
+        let events_url =
+            format!("{}/http?public_key={}", server, public_key);
+        let events_uri: Uri = events_url.parse()?;
+
+        // The event stream occupies a connection so requests
+        // are sent on a separate connection
+        let mut events = connect(&events_uri, &options).await?;
+        let requests = connect(&events_uri, &options).await?;
+
+        let mut request = request(Method::GET, &events_uri)
+            .header(ACCEPT, "text/event-stream");
+        if let Some(token) = &options.token {
+            request = request
+                .header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = request.body(Body::empty())?;
+        let response = events.send_request(request).await?;
+        if !response.status().is_success() {
+            return Err(Error::ConnectError(
+                response.status(),
+                response.status().to_string(),
+            ));
+        }
+
+        let mut reader = EventReader {
+            body: response.into_body(),
+            buffer: String::new(),
+        };
+        let ConnectEvent { id, secret } = match reader.next().await? {
+            Some((name, data)) if name == "connect" => {
+                serde_json::from_str(&data)
+                    .map_err(|_| Error::InvalidEventStream)?
+            }
+            _ => return Err(Error::InvalidEventStream),
+        };
+        let authorization = format!("Bearer {}", secret);
+
+        let (requests_tx, requests_rx) = futures_mpsc::channel(32);
+        tokio::spawn(send_requests(requests, requests_rx));
+
+        let send_uri: Uri = format!(
+            "{}/http/{}?public_key={}",
+            server, id, public_key
+        )
+        .parse()?;
+        let ws_reader = read_frames(
+            reader,
+            requests_tx.clone(),
+            send_uri.clone(),
+            authorization.clone(),
+        );
+
+        let builder = Builder::new(options.params()?);
+        let handshake = builder
+            .local_private_key(options.keypair.private_key())
+            .remote_public_key(&options.server_public_key)
+            .build_initiator()?;
+
+        // Channel for writing outbound messages to send
+        // to the server
+        let (outbound_tx, outbound_rx) =
+            mpsc::channel::<InternalMessage>(32);
+
+        // State for the server transport
+        let server = Arc::new(RwLock::new(Some(
+            ProtocolState::Handshake(Box::new(handshake)),
+        )));
+
+        let peers = Arc::new(RwLock::new(Default::default()));
+        let options = Arc::new(options);
+        let client = Self {
+            options: Arc::clone(&options),
+            outbound_tx: outbound_tx.clone(),
+            server: Arc::clone(&server),
+            peers: Arc::clone(&peers),
+        };
+
+        // Decoded socket messages are sent over this channel
+        let (inbound_tx, inbound_rx) =
+            mpsc::channel::<ResponseMessage>(32);
+
+        let event_loop = EventLoop {
+            options,
+            ws_reader,
+            ws_writer: HttpWriter {
+                requests: requests_tx,
+                uri: send_uri,
+                authorization,
+            },
+            inbound_tx,
+            inbound_rx,
+            outbound_tx,
+            outbound_rx,
+            server,
+            peers,
+        };
+
+        Ok((client, event_loop))
+    }
+
+    client_impl!();
+}
+
+client_transport_impl!(HttpClient);
+
+impl
+    EventLoop<HttpFrame, HttpError, HttpReadStream, HttpWriteStream>
+{
+    /// Decode frames from the event stream then send to
+    /// the messages channel.
+    pub(crate) async fn read_message(
+        incoming: HttpFrame,
+        event_proxy: &mut mpsc::Sender<ResponseMessage>,
+    ) -> Result<()> {
+        if let HttpFrame::Message(buffer) = incoming {
+            let inflated = zlib::inflate(&buffer)?;
+            let response: ResponseMessage = decode(inflated).await?;
+            event_proxy.send(response).await?;
+        }
+        Ok(())
+    }
+
+    /// Send a message in a POST request.
+    pub(crate) async fn send_message(
+        &mut self,
+        message: RequestMessage,
+    ) -> Result<()> {
+        let encoded = encode(&message).await?;
+        let deflated = zlib::deflate(&encoded)?;
+        let request = request(Method::POST, &self.ws_writer.uri)
+            .header(AUTHORIZATION, &self.ws_writer.authorization)
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .body(Body::from(deflated))?;
+        self.ws_writer
+            .requests
+            .send(request)
+            .await
+            .map_err(|_| Error::HttpSend)
+    }
+
+    /// Future that completes when the connection has been
+    /// idle for the configured timeout.
+    fn idle_timeout(
+        options: &ClientOptions,
+    ) -> BoxFuture<'static, ()> {
+        if let Some(timeout) = options.idle_timeout {
+            Box::pin(tokio::time::sleep(timeout))
+        } else {
+            Box::pin(future::pending())
+        }
+    }
+
+    async fn handle_close_message(mut self) -> Result<()> {
+        let request = request(Method::DELETE, &self.ws_writer.uri)
+            .header(AUTHORIZATION, &self.ws_writer.authorization)
+            .body(Body::empty())?;
+        self.ws_writer
+            .requests
+            .send(request)
+            .await
+            .map_err(|_| Error::HttpSend)?;
+        self.ws_writer.requests.close_channel();
+        Ok(())
+    }
+
+    event_loop_run_impl!();
+}
+
+/// Connect to the server for the URI.
+async fn connect(
+    uri: &Uri,
+    options: &ClientOptions,
+) -> Result<SendRequest<Body>> {
+    let host = uri
+        .host()
+        .map(|host| {
+            host.trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_owned()
+        })
+        .ok_or(Error::InvalidServerUrl)?;
+    let socket = TcpStream::connect((
+        host.as_str(),
+        uri.port_u16().unwrap_or(match uri.scheme_str() {
+            Some("https") => 443,
+            _ => 80,
+        }),
+    ))
+    .await?;
+    match uri.scheme_str() {
+        Some("https") => {
+            let config =
+                tls_config(&options.tls.clone().unwrap_or_default())?;
+            let connector = TlsConnector::from(Arc::new(config));
+            let name = ServerName::try_from(host.as_str())
+                .map_err(|_| Error::InvalidServerUrl)?;
+            handshake(connector.connect(name, socket).await?).await
+        }
+        _ => handshake(socket).await,
+    }
+}
+
+/// Create a request builder for the URI.
+///
+/// Requests are sent directly on a connection so the path
+/// is used for the request and the host is set in a header.
+fn request(method: Method, uri: &Uri) -> request::Builder {
+    let path = uri
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or("/");
+    let builder = Request::builder().method(method).uri(path);
+    match uri.authority() {
+        Some(authority) => builder.header(HOST, authority.as_str()),
+        None => builder,
+    }
+}
+
+/// Perform the HTTP handshake and drive the connection
+/// in a background task.
+async fn handshake<T>(io: T) -> Result<SendRequest<Body>>
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (sender, connection) = conn::handshake(io).await?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            tracing::warn!(error = %e, "http connection");
+        }
+    });
+    Ok(sender)
+}
+
+/// Send requests in order and stop on the first failure.
+async fn send_requests(
+    mut sender: SendRequest<Body>,
+    mut requests: futures_mpsc::Receiver<Request<Body>>,
+) {
+    while let Some(request) = requests.next().await {
+        let method = request.method().clone();
+        match sender.send_request(request).await {
+            Ok(response) if response.status().is_success() => {}
+            Ok(response) => {
+                tracing::warn!(
+                    status = %response.status(),
+                    method = %method,
+                    "http request rejected",
+                );
+                break;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "http request failed");
+                break;
+            }
+        }
+    }
+}
+
+/// Stream of frames from the event stream.
+///
+/// Pings are answered with an empty POST request like
+/// the pong frames sent automatically for a websocket.
+fn read_frames(
+    mut reader: EventReader,
+    mut requests: RequestSender,
+    uri: Uri,
+    authorization: String,
+) -> HttpReadStream {
+    let s = stream! {
+        loop {
+            match reader.next().await {
+                Ok(Some((name, data))) => match &name[..] {
+                    "message" => match STANDARD.decode(data) {
+                        Ok(buffer) => yield Ok(HttpFrame::Message(buffer)),
+                        Err(e) => yield Err(e.into()),
+                    },
+                    "ping" => {
+                        let pong = request(Method::POST, &uri)
+                            .header(AUTHORIZATION, &authorization)
+                            .body(Body::empty())
+                            .map_err(Error::from);
+                        match pong {
+                            Ok(pong) => {
+                                if requests.send(pong).await.is_err() {
+                                    yield Err(Error::HttpSend);
+                                }
+                            }
+                            Err(e) => yield Err(e),
+                        }
+                        yield Ok(HttpFrame::Ping);
+                    }
+                    _ => {}
+                },
+                Ok(None) => break,
+                Err(e) => {
+                    yield Err(e);
+                    break;
+                }
+            }
+        }
+    };
+    Box::pin(s)
+}
+
+/// Writer for requests to the server.
+#[doc(hidden)]
+pub struct HttpWriter {
+    requests: RequestSender,
+    uri: Uri,
+    authorization: String,
+}
+
+/// Data for the first event on the stream.
+#[derive(Deserialize)]
+struct ConnectEvent {
+    /// Identifier for the connection.
+    id: String,
+    /// Secret for requests to the connection.
+    secret: String,
+}
+
+/// Reads server-sent events from a response body.
+struct EventReader {
+    body: Body,
+    buffer: String,
+}
+
+impl EventReader {
+    /// Read the next event name and data.
+    async fn next(&mut self) -> Result<Option<(String, String)>> {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let block: String =
+                    self.buffer.drain(..end + 2).collect();
+                if let Some(event) = parse_event(&block) {
+                    return Ok(Some(event));
+                }
+                continue;
+            }
+
+            match self.body.data().await {
+                Some(chunk) => {
+                    let chunk = chunk?;
+                    self.buffer.push_str(
+                        &String::from_utf8_lossy(&chunk)
+                            .replace("\r\n", "\n"),
+                    );
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Parse the name and data for an event, comments and
+/// blocks without data are ignored.
+fn parse_event(block: &str) -> Option<(String, String)> {
+    let mut name = String::from("message");
+    let mut data: Option<String> = None;
+    for line in block.lines() {
+        let (field, value) =
+            line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => name = value.to_owned(),
+            "data" => match &mut data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => data = Some(value.to_owned()),
+            },
+            _ => {}
+        }
+    }
+    data.map(|data| (name, data))
+}
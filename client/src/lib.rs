@@ -18,8 +18,17 @@ pub(crate) use client::{client_impl, client_transport_impl};
 pub use event_loop::{Event, EventStream, JsonMessage};
 pub use transport::{NetworkTransport, Transport};
 
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+mod http;
 #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 mod native;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+mod quic;
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use http::{HttpClient, HttpEventLoop};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use quic::{QuicClient, QuicEventLoop};
 
 #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 pub use native::{
@@ -32,12 +41,17 @@ mod web;
 #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
 pub use web::{WebClient as Client, WebEventLoop as EventLoop};
 
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub use web::{
+    WebHttpClient as HttpClient, WebHttpEventLoop as HttpEventLoop,
+};
+
 use mpc_protocol::{
     hex, snow::params::NoiseParams, Chunk, Encoding, Keypair,
     OpaqueMessage, ProtocolState, RequestMessage, SealedEnvelope,
     SessionId, PATTERN,
 };
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio::sync::RwLock;
 
 pub(crate) type Peers = Arc<RwLock<HashMap<Vec<u8>, ProtocolState>>>;
@@ -54,20 +68,74 @@ pub struct ClientOptions {
     /// If no pattern is specified the default noise parameters
     /// pattern is used.
     pub pattern: Option<String>,
+    /// Timeout for an idle connection.
+    ///
+    /// When no messages (including pings) are received from
+    /// the server within this duration the event loop emits
+    /// a connection lost event and stops.
+    ///
+    /// Browsers do not expose websocket ping frames so the
+    /// web client sends an empty frame at half the timeout
+    /// which the server answers with an empty frame. The web
+    /// client using the HTTP transport is kept alive by the
+    /// server ping events so the timeout must be longer than
+    /// the server heartbeat interval.
+    pub idle_timeout: Option<Duration>,
+    /// Bearer token for servers that require authentication.
+    ///
+    /// Native clients send the token in the `Authorization`
+    /// header, browsers cannot set headers for websockets or
+    /// event streams so the web client sends the token
+    /// percent-encoded in the query string.
+    pub token: Option<String>,
+    /// Options for connecting to servers over TLS.
+    ///
+    /// Only supported on native platforms, browsers use
+    /// their own root certificates and client certificates.
+    pub tls: Option<TlsOptions>,
+}
+
+/// Options for connecting to servers over TLS.
+#[derive(Debug, Default, Clone)]
+pub struct TlsOptions {
+    /// PEM-encoded root certificates used to verify the
+    /// server instead of the default web PKI roots.
+    pub roots: Option<Vec<u8>>,
+    /// Client certificate for servers that require
+    /// client certificate authentication.
+    pub identity: Option<TlsIdentity>,
+}
+
+/// Client certificate and private key.
+#[derive(Debug, Clone)]
+pub struct TlsIdentity {
+    /// PEM-encoded certificate chain.
+    pub cert: Vec<u8>,
+    /// PEM-encoded private key.
+    pub key: Vec<u8>,
 }
 
 impl ClientOptions {
     /// Build a connection URL for the given server.
     ///
     /// This method appends the public key query string
-    /// parameter necessary for connecting to the server.
+    /// parameter necessary for connecting to the server
+    /// and on the web the token when one is configured.
     pub fn url(&self, server: &str) -> String {
         let server = server.trim_end_matches('/');
-        format!(
+        let url = format!(
             "{}/?public_key={}",
             server,
             hex::encode(self.keypair.public_key())
-        )
+        );
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        let url = match &self.token {
+            Some(token) => {
+                format!("{}&token={}", url, encode_query(token))
+            }
+            None => url,
+        };
+        url
     }
 
     /// Parse noise parameters from the pattern.
@@ -81,6 +149,25 @@ impl ClientOptions {
     }
 }
 
+/// Percent-encode a value for a query string.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) fn encode_query(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
 pub use error::Error;
 
 /// Result type for the client library.
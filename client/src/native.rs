@@ -1,28 +1,45 @@
 use async_stream::stream;
 use futures::{
+    future::{self, BoxFuture},
     select,
     sink::SinkExt,
     stream::{SplitSink, SplitStream},
     FutureExt, StreamExt,
 };
+use rustls::{
+    Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey,
+    RootCertStore,
+};
 use serde::Serialize;
 use serde_json::Value;
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
 use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
     net::TcpStream,
     sync::{mpsc, RwLock},
 };
 use tokio_tungstenite::{
-    connect_async, tungstenite::protocol::Message, MaybeTlsStream,
-    WebSocketStream,
+    client_async_tls_with_config,
+    tungstenite::{
+        self, client::IntoClientRequest, error::UrlError,
+        handshake::client::Request, http::header::AUTHORIZATION,
+        protocol::Message,
+    },
+    Connector, MaybeTlsStream, WebSocketStream,
 };
 
 use mpc_protocol::{
-    channel::encrypt_server_channel, decode, encode, hex,
-    http::StatusCode, snow::Builder, zlib, Encoding,
-    HandshakeMessage, MeetingId, OpaqueMessage, ProtocolState,
+    channel::encrypt_server_channel, decode,
+    ed25519_dalek::Signature, encode, hex, http::StatusCode,
+    snow::Builder, zlib, Encoding, HandshakeMessage, JoinCode,
+    MeetingId, MeetingSlot, OpaqueMessage, ProtocolState,
     RequestMessage, ResponseMessage, ServerMessage, SessionId,
-    SessionRequest, TransparentMessage, UserId,
+    SessionLimits, SessionQuorum, SessionRequest, TransparentMessage,
+    UserId,
 };
 
 use super::{
@@ -34,15 +51,15 @@ use super::{
 };
 use crate::{
     client_impl, client_transport_impl, ClientOptions, Error, Event,
-    Result,
+    Result, TlsOptions,
 };
 
 type WsMessage = Message;
 type WsError = tokio_tungstenite::tungstenite::Error;
 type WsReadStream =
-    SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>;
+    SplitStream<WebSocketStream<MaybeTlsStream<Socket>>>;
 type WsWriteStream =
-    SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, WsMessage>;
+    SplitSink<WebSocketStream<MaybeTlsStream<Socket>>, WsMessage>;
 
 /// Event loop for the websocket client.
 pub type NativeEventLoop =
@@ -63,7 +80,85 @@ impl NativeClient {
         server: &str,
         options: ClientOptions,
     ) -> Result<(Self, NativeEventLoop)> {
-        let (stream, response) = connect_async(server).await?;
+        let request = server.into_client_request()?;
+        let host = request
+            .uri()
+            .host()
+            .map(|host| {
+                host.trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .to_owned()
+            })
+            .ok_or(tungstenite::Error::Url(UrlError::NoHostName))?;
+        let port = match request.uri().scheme_str() {
+            Some("ws") => request.uri().port_u16().unwrap_or(80),
+            Some("wss") => request.uri().port_u16().unwrap_or(443),
+            _ => {
+                return Err(tungstenite::Error::Url(
+                    UrlError::UnsupportedUrlScheme,
+                )
+                .into())
+            }
+        };
+        let socket =
+            TcpStream::connect((host.as_str(), port)).await?;
+        Self::connect_socket(request, Socket::Tcp(socket), options)
+            .await
+    }
+
+    /// Create a new native client connected to a server
+    /// listening on a Unix domain socket.
+    ///
+    /// The server URL is used for the websocket request
+    /// (eg: `ws://localhost`), the host is not resolved.
+    #[cfg(unix)]
+    pub async fn new_unix(
+        path: impl AsRef<std::path::Path>,
+        server: &str,
+        options: ClientOptions,
+    ) -> Result<(Self, NativeEventLoop)> {
+        let request = server.into_client_request()?;
+        let socket = tokio::net::UnixStream::connect(path).await?;
+        Self::connect_socket(request, Socket::Unix(socket), options)
+            .await
+    }
+
+    /// Perform the websocket handshake over a socket.
+    async fn connect_socket(
+        mut request: Request,
+        socket: Socket,
+        options: ClientOptions,
+    ) -> Result<(Self, NativeEventLoop)> {
+        if let Some(token) = &options.token {
+            let value =
+                format!("Bearer {}", token).parse().map_err(|e| {
+                    tungstenite::Error::HttpFormat(
+                        tungstenite::http::Error::from(e),
+                    )
+                })?;
+            request.headers_mut().insert(AUTHORIZATION, value);
+        }
+
+        let connector = match &options.tls {
+            Some(tls) => Some(tls_connector(tls)?),
+            None => None,
+        };
+        let (stream, response) = match client_async_tls_with_config(
+            request, socket, None, connector,
+        )
+        .await
+        {
+            Ok(result) => result,
+            // Upgrade rejected by the server, eg: access denied
+            // or a duplicate connection for the public key
+            Err(tungstenite::Error::Http(response)) => {
+                return Err(Error::ConnectError(
+                    response.status(),
+                    response.status().to_string(),
+                ));
+            }
+            Err(e) => return Err(e.into()),
+        };
 
         if response.status() != StatusCode::SWITCHING_PROTOCOLS {
             return Err(Error::ConnectError(
@@ -157,16 +252,143 @@ impl EventLoop<WsMessage, WsError, WsReadStream, WsWriteStream> {
             .map_err(|_| Error::WebSocketSend)
     }
 
+    /// Future that completes when the connection has been
+    /// idle for the configured timeout.
+    fn idle_timeout(
+        options: &ClientOptions,
+    ) -> BoxFuture<'static, ()> {
+        if let Some(timeout) = options.idle_timeout {
+            Box::pin(tokio::time::sleep(timeout))
+        } else {
+            Box::pin(future::pending())
+        }
+    }
+
     async fn handle_close_message(self) -> Result<()> {
-        let mut websocket: WebSocketStream<
-            MaybeTlsStream<TcpStream>,
-        > = self
-            .ws_reader
-            .reunite(self.ws_writer)
-            .map_err(|_| Error::StreamReunite)?;
+        let mut websocket: WebSocketStream<MaybeTlsStream<Socket>> =
+            self.ws_reader
+                .reunite(self.ws_writer)
+                .map_err(|_| Error::StreamReunite)?;
         websocket.close(None).await?;
         Ok(())
     }
 
     event_loop_run_impl!();
 }
+
+/// Create a TLS connector from the options.
+fn tls_connector(tls: &TlsOptions) -> Result<Connector> {
+    Ok(Connector::Rustls(Arc::new(tls_config(tls)?)))
+}
+
+/// Create a TLS client configuration from the options.
+pub(crate) fn tls_config(tls: &TlsOptions) -> Result<ClientConfig> {
+    let mut roots = RootCertStore::empty();
+    if let Some(pem) = &tls.roots {
+        for cert in rustls_pemfile::certs(&mut pem.as_slice())? {
+            roots.add(&Certificate(cert))?;
+        }
+    } else {
+        roots.add_trust_anchors(
+            webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+                OwnedTrustAnchor::from_subject_spki_name_constraints(
+                    ta.subject,
+                    ta.spki,
+                    ta.name_constraints,
+                )
+            }),
+        );
+    }
+
+    let builder = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+    let config = if let Some(identity) = &tls.identity {
+        let certs =
+            rustls_pemfile::certs(&mut identity.cert.as_slice())?
+                .into_iter()
+                .map(Certificate)
+                .collect();
+        let key =
+            private_key(&identity.key)?.ok_or(Error::NoPrivateKey)?;
+        builder.with_client_auth_cert(certs, key)?
+    } else {
+        builder.with_no_client_auth()
+    };
+    Ok(config)
+}
+
+/// Read the first private key from PEM-encoded data.
+fn private_key(pem: &[u8]) -> Result<Option<PrivateKey>> {
+    use rustls_pemfile::Item;
+    for item in rustls_pemfile::read_all(&mut &pem[..])? {
+        match item {
+            Item::RSAKey(key)
+            | Item::PKCS8Key(key)
+            | Item::ECKey(key) => return Ok(Some(PrivateKey(key))),
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+/// Socket for the websocket connection.
+pub enum Socket {
+    /// TCP socket.
+    Tcp(TcpStream),
+    /// Unix domain socket.
+    #[cfg(unix)]
+    Unix(tokio::net::UnixStream),
+}
+
+impl AsyncRead for Socket {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(socket) => Pin::new(socket).poll_read(cx, buf),
+            #[cfg(unix)]
+            Self::Unix(socket) => Pin::new(socket).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Socket {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(socket) => Pin::new(socket).poll_write(cx, buf),
+            #[cfg(unix)]
+            Self::Unix(socket) => {
+                Pin::new(socket).poll_write(cx, buf)
+            }
+        }
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(socket) => Pin::new(socket).poll_flush(cx),
+            #[cfg(unix)]
+            Self::Unix(socket) => Pin::new(socket).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(socket) => Pin::new(socket).poll_shutdown(cx),
+            #[cfg(unix)]
+            Self::Unix(socket) => Pin::new(socket).poll_shutdown(cx),
+        }
+    }
+}
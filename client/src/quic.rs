@@ -0,0 +1,409 @@
+use async_stream::stream;
+use futures::{
+    future::{self, BoxFuture},
+    select,
+    stream::BoxStream,
+    FutureExt, StreamExt,
+};
+use hyper::Uri;
+use quinn::{
+    ClientConfig, Connection, Endpoint, ReadExactError, RecvStream,
+    SendStream, TransportConfig,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::{mpsc, Mutex, RwLock};
+
+use mpc_protocol::{
+    channel::encrypt_server_channel, decode,
+    ed25519_dalek::Signature, encode, hex, http::StatusCode,
+    snow::Builder, zlib, Encoding, HandshakeMessage, JoinCode,
+    MeetingId, MeetingSlot, OpaqueMessage, ProtocolState,
+    RequestMessage, ResponseMessage, ServerMessage, SessionId,
+    SessionLimits, SessionQuorum, SessionRequest, TransparentMessage,
+    UserId,
+};
+
+use super::{
+    encrypt_peer_channel,
+    event_loop::{
+        event_loop_run_impl, EventLoop, EventStream, InternalMessage,
+    },
+    native::tls_config,
+    Peers, Server,
+};
+use crate::{
+    client_impl, client_transport_impl, ClientOptions, Error, Event,
+    Result,
+};
+
+type QuicError = Error;
+type QuicReadStream = BoxStream<'static, Result<QuicFrame>>;
+type QuicWriteStream = QuicWriter;
+type FrameSender = Arc<Mutex<SendStream>>;
+
+/// Protocol identifier negotiated with ALPN.
+const ALPN: &[u8] = b"mpc-relay";
+
+/// Maximum size of a frame.
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// Interval for keep alive packets so the connection is not
+/// closed by the QUIC idle timeout between server pings.
+const KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+/// Event loop for the QUIC client.
+pub type QuicEventLoop =
+    EventLoop<QuicFrame, QuicError, QuicReadStream, QuicWriteStream>;
+
+/// Frame received on the QUIC stream.
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum QuicFrame {
+    /// Compressed response message.
+    Message(Vec<u8>),
+    /// Ping from the server.
+    Ping,
+}
+
+/// Query sent in the connect frame.
+#[derive(Serialize)]
+struct ConnectQuery<'a> {
+    public_key: String,
+    token: Option<&'a str>,
+}
+
+/// Relay service client for the QUIC transport.
+///
+/// Request and response messages for the server are sent on
+/// a bidirectional stream of a QUIC connection which avoids
+/// the TCP and TLS handshakes of a websocket connection.
+///
+/// Messages relayed to each peer are sent on a separate
+/// unidirectional stream so that a lost packet for one peer
+/// does not delay the messages for other peers. Messages
+/// relayed from peers are received on the bidirectional
+/// stream so they are delivered in order with the messages
+/// from the server.
+///
+/// Head-of-line blocking remains when receiving; a lost
+/// packet delays all the messages relayed to the client.
+#[derive(Clone)]
+pub struct QuicClient {
+    options: Arc<ClientOptions>,
+    outbound_tx: mpsc::Sender<InternalMessage>,
+    server: Server,
+    peers: Peers,
+}
+
+impl QuicClient {
+    /// Create a new QUIC client.
+    ///
+    /// The server URL uses the `quic` scheme, for example
+    /// `quic://example.com:7008`.
+    pub async fn new(
+        server: &str,
+        options: ClientOptions,
+    ) -> Result<(Self, QuicEventLoop)> {
+        let uri: Uri = server.parse()?;
+        if uri.scheme_str() != Some("quic") {
+            return Err(Error::InvalidServerUrl);
+        }
+        let host = uri
+            .host()
+            .map(|host| {
+                host.trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .to_owned()
+            })
+            .ok_or(Error::InvalidServerUrl)?;
+        let addr = tokio::net::lookup_host((
+            host.as_str(),
+            uri.port_u16().unwrap_or(443),
+        ))
+        .await?
+        .next()
+        .ok_or(Error::InvalidServerUrl)?;
+
+        let bind: SocketAddr = if addr.is_ipv6() {
+            ([0u16; 8], 0).into()
+        } else {
+            ([0u8; 4], 0).into()
+        };
+        let endpoint = Endpoint::client(bind)?;
+
+        let mut crypto =
+            tls_config(&options.tls.clone().unwrap_or_default())?;
+        crypto.alpn_protocols = vec![ALPN.to_vec()];
+        let mut transport = TransportConfig::default();
+        transport.keep_alive_interval(Some(KEEP_ALIVE));
+        let mut config = ClientConfig::new(Arc::new(crypto));
+        config.transport_config(Arc::new(transport));
+
+        let connection =
+            endpoint.connect_with(config, addr, &host)?.await?;
+        let (mut send, mut recv) = connection.open_bi().await?;
+
+        let query = ConnectQuery {
+            public_key: hex::encode(options.keypair.public_key()),
+            token: options.token.as_deref(),
+        };
+        write_frame(&mut send, &serde_json::to_vec(&query)?).await?;
+
+        let status = match read_frame(&mut recv).await? {
+            Some(buffer) if buffer.len() == 2 => {
+                u16::from_be_bytes([buffer[0], buffer[1]])
+            }
+            _ => return Err(ReadExactError::FinishedEarly.into()),
+        };
+        let status = StatusCode::from_u16(status)
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        if status != StatusCode::OK {
+            return Err(Error::ConnectError(
+                status,
+                status.to_string(),
+            ));
+        }
+
+        let send = Arc::new(Mutex::new(send));
+        let ws_reader =
+            read_frames(recv, Arc::clone(&send), connection.clone());
+
+        let builder = Builder::new(options.params()?);
+        let handshake = builder
+            .local_private_key(options.keypair.private_key())
+            .remote_public_key(&options.server_public_key)
+            .build_initiator()?;
+
+        // Channel for writing outbound messages to send
+        // to the server
+        let (outbound_tx, outbound_rx) =
+            mpsc::channel::<InternalMessage>(32);
+
+        // State for the server transport
+        let server = Arc::new(RwLock::new(Some(
+            ProtocolState::Handshake(Box::new(handshake)),
+        )));
+
+        let peers = Arc::new(RwLock::new(Default::default()));
+        let options = Arc::new(options);
+        let client = Self {
+            options: Arc::clone(&options),
+            outbound_tx: outbound_tx.clone(),
+            server: Arc::clone(&server),
+            peers: Arc::clone(&peers),
+        };
+
+        // Decoded socket messages are sent over this channel
+        let (inbound_tx, inbound_rx) =
+            mpsc::channel::<ResponseMessage>(32);
+
+        let event_loop = EventLoop {
+            options,
+            ws_reader,
+            ws_writer: QuicWriter {
+                send,
+                peers: HashMap::new(),
+                connection,
+                _endpoint: endpoint,
+            },
+            inbound_tx,
+            inbound_rx,
+            outbound_tx,
+            outbound_rx,
+            server,
+            peers,
+        };
+
+        Ok((client, event_loop))
+    }
+
+    client_impl!();
+}
+
+client_transport_impl!(QuicClient);
+
+impl
+    EventLoop<QuicFrame, QuicError, QuicReadStream, QuicWriteStream>
+{
+    /// Decode frames from the stream then send to
+    /// the messages channel.
+    pub(crate) async fn read_message(
+        incoming: QuicFrame,
+        event_proxy: &mut mpsc::Sender<ResponseMessage>,
+    ) -> Result<()> {
+        if let QuicFrame::Message(buffer) = incoming {
+            let inflated = zlib::inflate(&buffer)?;
+            let response: ResponseMessage = decode(inflated).await?;
+            event_proxy.send(response).await?;
+        }
+        Ok(())
+    }
+
+    /// Send a message in a frame on the stream for the
+    /// peer or the server.
+    pub(crate) async fn send_message(
+        &mut self,
+        message: RequestMessage,
+    ) -> Result<()> {
+        let peer = match &message {
+            RequestMessage::Transparent(
+                TransparentMessage::PeerHandshake {
+                    public_key, ..
+                },
+            )
+            | RequestMessage::Opaque(OpaqueMessage::PeerMessage {
+                public_key,
+                ..
+            }) => Some(public_key.clone()),
+            _ => None,
+        };
+
+        let encoded = encode(&message).await?;
+        let deflated = zlib::deflate(&encoded)?;
+
+        if let Some(peer) = peer {
+            let writer = &mut self.ws_writer;
+            let send = match writer.peers.entry(peer) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    entry.insert(writer.connection.open_uni().await?)
+                }
+            };
+            write_frame(send, &deflated).await
+        } else {
+            let mut send = self.ws_writer.send.lock().await;
+            write_frame(&mut send, &deflated).await
+        }
+    }
+
+    /// Future that completes when the connection has been
+    /// idle for the configured timeout.
+    fn idle_timeout(
+        options: &ClientOptions,
+    ) -> BoxFuture<'static, ()> {
+        if let Some(timeout) = options.idle_timeout {
+            Box::pin(tokio::time::sleep(timeout))
+        } else {
+            Box::pin(future::pending())
+        }
+    }
+
+    async fn handle_close_message(mut self) -> Result<()> {
+        // Finishing the streams waits until the server has
+        // received all the frames
+        for send in self.ws_writer.peers.values_mut() {
+            let _ = send.finish().await;
+        }
+        let result = {
+            let mut send = self.ws_writer.send.lock().await;
+            send.finish().await
+        };
+        self.ws_writer.connection.close(0u32.into(), b"close");
+        Ok(result?)
+    }
+
+    event_loop_run_impl!();
+}
+
+/// Stream of frames from the server stream.
+///
+/// The stream is read in a separate task so that a partially
+/// read frame is never dropped.
+fn read_frames(
+    recv: RecvStream,
+    send: FrameSender,
+    connection: Connection,
+) -> QuicReadStream {
+    let (tx, mut rx) = mpsc::channel(32);
+    tokio::spawn(read_server(recv, send, connection, tx));
+
+    let s = stream! {
+        while let Some(frame) = rx.recv().await {
+            yield frame;
+        }
+    };
+    Box::pin(s)
+}
+
+/// Read frames from the server stream.
+///
+/// Pings are answered with an empty frame like the pong
+/// frames sent automatically for a websocket.
+///
+/// The connection is closed when the server stream ends.
+async fn read_server(
+    mut recv: RecvStream,
+    send: FrameSender,
+    connection: Connection,
+    tx: mpsc::Sender<Result<QuicFrame>>,
+) {
+    loop {
+        let frame = match read_frame(&mut recv).await {
+            Ok(Some(buffer)) if buffer.is_empty() => {
+                let mut send = send.lock().await;
+                if let Err(e) = write_frame(&mut send, &[]).await {
+                    let _ = tx.send(Err(e)).await;
+                }
+                Ok(QuicFrame::Ping)
+            }
+            Ok(Some(buffer)) => Ok(QuicFrame::Message(buffer)),
+            Ok(None) => break,
+            Err(e) => {
+                let _ = tx.send(Err(e)).await;
+                break;
+            }
+        };
+        if tx.send(frame).await.is_err() {
+            break;
+        }
+    }
+    connection.close(0u32.into(), b"close");
+}
+
+/// Writer for frames to the server.
+#[doc(hidden)]
+pub struct QuicWriter {
+    send: FrameSender,
+    peers: HashMap<Vec<u8>, SendStream>,
+    connection: Connection,
+    // Keep the endpoint open for the lifetime of the connection
+    _endpoint: Endpoint,
+}
+
+/// Read a length-prefixed frame.
+///
+/// Returns `None` when the stream is finished.
+async fn read_frame(
+    recv: &mut RecvStream,
+) -> Result<Option<Vec<u8>>> {
+    let mut length = [0u8; 4];
+    match recv.read_exact(&mut length).await {
+        Ok(_) => {}
+        Err(ReadExactError::FinishedEarly) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_FRAME_SIZE {
+        return Err(Error::FrameTooLarge(length));
+    }
+    let mut buffer = vec![0u8; length];
+    recv.read_exact(&mut buffer).await?;
+    Ok(Some(buffer))
+}
+
+/// Write a length-prefixed frame.
+async fn write_frame(
+    send: &mut SendStream,
+    buffer: &[u8],
+) -> Result<()> {
+    send.write_all(&(buffer.len() as u32).to_be_bytes()).await?;
+    send.write_all(buffer).await?;
+    Ok(())
+}
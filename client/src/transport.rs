@@ -1,15 +1,27 @@
 use crate::{Client, ClientOptions, EventLoop, Result};
 use async_trait::async_trait;
-use mpc_protocol::{MeetingId, SessionId, UserId};
+use mpc_protocol::{
+    ed25519_dalek::Signature, JoinCode, MeetingId, MeetingSlot,
+    SessionId, SessionLimits, SessionQuorum, UserId,
+};
 use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashSet;
+
+use crate::{HttpClient, HttpEventLoop};
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use crate::{QuicClient, QuicEventLoop};
 
 /// Enumeration of available transports.
 #[derive(Clone)]
 pub enum Transport {
     /// Relay websocket client.
     Relay(Client),
+    /// Relay client using the HTTP transport.
+    Http(HttpClient),
+    /// Relay client using the QUIC transport.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    Quic(QuicClient),
     // NOTE: later we will add a Peer variant using
     // NOTE: a WebRTC data channel for communication
 }
@@ -25,18 +37,36 @@ impl NetworkTransport for Transport {
     fn public_key(&self) -> &[u8] {
         match self {
             Transport::Relay(client) => client.public_key(),
+            Transport::Http(client) => client.public_key(),
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => client.public_key(),
         }
     }
 
     async fn connect(&mut self) -> Result<()> {
         match self {
             Transport::Relay(client) => client.connect().await,
+            Transport::Http(client) => client.connect().await,
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => client.connect().await,
         }
     }
 
     async fn is_connected(&self) -> bool {
         match self {
             Transport::Relay(client) => client.is_connected().await,
+            Transport::Http(client) => client.is_connected().await,
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => client.is_connected().await,
         }
     }
 
@@ -48,6 +78,16 @@ impl NetworkTransport for Transport {
             Transport::Relay(client) => {
                 client.connect_peer(public_key).await
             }
+            Transport::Http(client) => {
+                client.connect_peer(public_key).await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client.connect_peer(public_key).await
+            }
         }
     }
 
@@ -66,6 +106,20 @@ impl NetworkTransport for Transport {
                     .send_json(public_key, payload, session_id)
                     .await
             }
+            Transport::Http(client) => {
+                client
+                    .send_json(public_key, payload, session_id)
+                    .await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client
+                    .send_json(public_key, payload, session_id)
+                    .await
+            }
         }
     }
 
@@ -81,18 +135,68 @@ impl NetworkTransport for Transport {
                     .send_blob(public_key, payload, session_id)
                     .await
             }
+            Transport::Http(client) => {
+                client
+                    .send_blob(public_key, payload, session_id)
+                    .await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client
+                    .send_blob(public_key, payload, session_id)
+                    .await
+            }
         }
     }
 
     async fn new_meeting(
         &mut self,
         owner_id: UserId,
-        slots: HashSet<UserId>,
+        slots: Vec<MeetingSlot>,
+        invitations: bool,
+        join_code: Option<JoinCode>,
         data: Value,
     ) -> Result<()> {
         match self {
             Transport::Relay(client) => {
-                client.new_meeting(owner_id, slots, data).await
+                client
+                    .new_meeting(
+                        owner_id,
+                        slots,
+                        invitations,
+                        join_code,
+                        data,
+                    )
+                    .await
+            }
+            Transport::Http(client) => {
+                client
+                    .new_meeting(
+                        owner_id,
+                        slots,
+                        invitations,
+                        join_code,
+                        data,
+                    )
+                    .await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client
+                    .new_meeting(
+                        owner_id,
+                        slots,
+                        invitations,
+                        join_code,
+                        data,
+                    )
+                    .await
             }
         }
     }
@@ -101,10 +205,76 @@ impl NetworkTransport for Transport {
         &mut self,
         meeting_id: MeetingId,
         user_id: UserId,
+        join_code: JoinCode,
+        signature: Option<Signature>,
     ) -> Result<()> {
         match self {
             Transport::Relay(client) => {
-                client.join_meeting(meeting_id, user_id).await
+                client
+                    .join_meeting(
+                        meeting_id, user_id, join_code, signature,
+                    )
+                    .await
+            }
+            Transport::Http(client) => {
+                client
+                    .join_meeting(
+                        meeting_id, user_id, join_code, signature,
+                    )
+                    .await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client
+                    .join_meeting(
+                        meeting_id, user_id, join_code, signature,
+                    )
+                    .await
+            }
+        }
+    }
+
+    async fn leave_meeting(
+        &mut self,
+        meeting_id: MeetingId,
+    ) -> Result<()> {
+        match self {
+            Transport::Relay(client) => {
+                client.leave_meeting(meeting_id).await
+            }
+            Transport::Http(client) => {
+                client.leave_meeting(meeting_id).await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client.leave_meeting(meeting_id).await
+            }
+        }
+    }
+
+    async fn cancel_meeting(
+        &mut self,
+        meeting_id: MeetingId,
+    ) -> Result<()> {
+        match self {
+            Transport::Relay(client) => {
+                client.cancel_meeting(meeting_id).await
+            }
+            Transport::Http(client) => {
+                client.cancel_meeting(meeting_id).await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client.cancel_meeting(meeting_id).await
             }
         }
     }
@@ -112,10 +282,28 @@ impl NetworkTransport for Transport {
     async fn new_session(
         &mut self,
         participant_keys: Vec<Vec<u8>>,
+        quorum: Option<SessionQuorum>,
+        limits: SessionLimits,
     ) -> Result<()> {
         match self {
             Transport::Relay(client) => {
-                client.new_session(participant_keys).await
+                client
+                    .new_session(participant_keys, quorum, limits)
+                    .await
+            }
+            Transport::Http(client) => {
+                client
+                    .new_session(participant_keys, quorum, limits)
+                    .await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client
+                    .new_session(participant_keys, quorum, limits)
+                    .await
             }
         }
     }
@@ -129,6 +317,37 @@ impl NetworkTransport for Transport {
             Transport::Relay(client) => {
                 client.register_connection(session_id, peer_key).await
             }
+            Transport::Http(client) => {
+                client.register_connection(session_id, peer_key).await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client.register_connection(session_id, peer_key).await
+            }
+        }
+    }
+
+    async fn join_session(
+        &mut self,
+        session_id: SessionId,
+    ) -> Result<()> {
+        match self {
+            Transport::Relay(client) => {
+                client.join_session(session_id).await
+            }
+            Transport::Http(client) => {
+                client.join_session(session_id).await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client.join_session(session_id).await
+            }
         }
     }
 
@@ -140,6 +359,70 @@ impl NetworkTransport for Transport {
             Transport::Relay(client) => {
                 client.close_session(session_id).await
             }
+            Transport::Http(client) => {
+                client.close_session(session_id).await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client.close_session(session_id).await
+            }
+        }
+    }
+
+    async fn get_session(
+        &mut self,
+        session_id: SessionId,
+    ) -> Result<()> {
+        match self {
+            Transport::Relay(client) => {
+                client.get_session(session_id).await
+            }
+            Transport::Http(client) => {
+                client.get_session(session_id).await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client.get_session(session_id).await
+            }
+        }
+    }
+
+    async fn get_meeting(
+        &mut self,
+        meeting_id: MeetingId,
+    ) -> Result<()> {
+        match self {
+            Transport::Relay(client) => {
+                client.get_meeting(meeting_id).await
+            }
+            Transport::Http(client) => {
+                client.get_meeting(meeting_id).await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client.get_meeting(meeting_id).await
+            }
+        }
+    }
+
+    async fn list_sessions(&mut self) -> Result<()> {
+        match self {
+            Transport::Relay(client) => client.list_sessions().await,
+            Transport::Http(client) => client.list_sessions().await,
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => client.list_sessions().await,
         }
     }
 
@@ -162,6 +445,28 @@ impl NetworkTransport for Transport {
                     )
                     .await
             }
+            Transport::Http(client) => {
+                client
+                    .broadcast_json(
+                        session_id,
+                        recipient_public_keys,
+                        payload,
+                    )
+                    .await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client
+                    .broadcast_json(
+                        session_id,
+                        recipient_public_keys,
+                        payload,
+                    )
+                    .await
+            }
         }
     }
 
@@ -181,16 +486,57 @@ impl NetworkTransport for Transport {
                     )
                     .await
             }
+            Transport::Http(client) => {
+                client
+                    .broadcast_blob(
+                        session_id,
+                        recipient_public_keys,
+                        payload,
+                    )
+                    .await
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => {
+                client
+                    .broadcast_blob(
+                        session_id,
+                        recipient_public_keys,
+                        payload,
+                    )
+                    .await
+            }
         }
     }
 
     async fn close(&self) -> Result<()> {
         match self {
             Transport::Relay(client) => client.close().await,
+            Transport::Http(client) => client.close().await,
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                target_os = "unknown"
+            )))]
+            Transport::Quic(client) => client.close().await,
         }
     }
 }
 
+impl From<HttpClient> for Transport {
+    fn from(value: HttpClient) -> Self {
+        Self::Http(value)
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl From<QuicClient> for Transport {
+    fn from(value: QuicClient) -> Self {
+        Self::Quic(value)
+    }
+}
+
 impl Transport {
     /// Create a new relay client.
     pub async fn new_relay(
@@ -201,6 +547,27 @@ impl Transport {
             Client::new(server, options).await?;
         Ok((Self::Relay(client), event_loop))
     }
+
+    /// Create a new relay client using the HTTP transport.
+    pub async fn new_http(
+        server: &str,
+        options: ClientOptions,
+    ) -> Result<(Self, HttpEventLoop)> {
+        let (client, event_loop) =
+            HttpClient::new(server, options).await?;
+        Ok((Self::Http(client), event_loop))
+    }
+
+    /// Create a new relay client using the QUIC transport.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub async fn new_quic(
+        server: &str,
+        options: ClientOptions,
+    ) -> Result<(Self, QuicEventLoop)> {
+        let (client, event_loop) =
+            QuicClient::new(server, options).await?;
+        Ok((Self::Quic(client), event_loop))
+    }
 }
 
 /// Trait for network clients.
@@ -242,27 +609,61 @@ pub trait NetworkTransport {
     ) -> Result<()>;
 
     /// Create a new meeting point.
+    ///
+    /// When `invitations` is set a one-time invitation code
+    /// is created for each slot; when `join_code` is not given
+    /// the server generates a random join code.
     async fn new_meeting(
         &mut self,
         owner_id: UserId,
-        slots: HashSet<UserId>,
+        slots: Vec<MeetingSlot>,
+        invitations: bool,
+        join_code: Option<JoinCode>,
         data: Value,
     ) -> Result<()>;
 
-    /// Join a meeting point.
+    /// Join a meeting point using the join code for the
+    /// meeting or the invitation code for the slot.
+    ///
+    /// Slots bound to a verifying key require a signature
+    /// over the message returned by `join_meeting_message`.
     async fn join_meeting(
         &mut self,
         meeting_id: MeetingId,
         user_id: UserId,
+        join_code: JoinCode,
+        signature: Option<Signature>,
+    ) -> Result<()>;
+
+    /// Leave a meeting point.
+    async fn leave_meeting(
+        &mut self,
+        meeting_id: MeetingId,
+    ) -> Result<()>;
+
+    /// Cancel a meeting point.
+    ///
+    /// Only the owner of a meeting point may cancel it.
+    async fn cancel_meeting(
+        &mut self,
+        meeting_id: MeetingId,
     ) -> Result<()>;
 
     /// Create a new session.
     ///
     /// Do not include the public key of the initiator for the new
     /// session; it is automatically included as the session owner.
+    ///
+    /// When a quorum is given the session proceeds with the
+    /// participants that have joined by the quorum deadline.
+    ///
+    /// Requested limits are clamped by the server and the
+    /// effective limits are returned in the session state.
     async fn new_session(
         &mut self,
         participant_keys: Vec<Vec<u8>>,
+        quorum: Option<SessionQuorum>,
+        limits: SessionLimits,
     ) -> Result<()>;
 
     /// Register a peer connection in a session.
@@ -272,12 +673,48 @@ pub trait NetworkTransport {
         peer_key: &[u8],
     ) -> Result<()>;
 
+    /// Join a session this client was invited to.
+    ///
+    /// Participants must join a session created with a quorum
+    /// for it to become ready; without a quorum participants
+    /// are counted as joined once connected. When a participant
+    /// that has not yet joined joins a session that is already
+    /// ready the current state is dispatched as a session ready
+    /// or session active event.
+    async fn join_session(
+        &mut self,
+        session_id: SessionId,
+    ) -> Result<()>;
+
     /// Close a session.
     async fn close_session(
         &mut self,
         session_id: SessionId,
     ) -> Result<()>;
 
+    /// Request the status of a session.
+    ///
+    /// The status is dispatched as a session info event.
+    async fn get_session(
+        &mut self,
+        session_id: SessionId,
+    ) -> Result<()>;
+
+    /// Request the state of a meeting.
+    ///
+    /// The state is dispatched as a meeting info event.
+    async fn get_meeting(
+        &mut self,
+        meeting_id: MeetingId,
+    ) -> Result<()>;
+
+    /// Request the status of the sessions this client
+    /// participates in.
+    ///
+    /// Use this to recover sessions after a restart; the
+    /// statuses are dispatched as a session list event.
+    async fn list_sessions(&mut self) -> Result<()>;
+
     /// Broadcast a JSON message in the context of a session.
     async fn broadcast_json<S>(
         &mut self,
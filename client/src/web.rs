@@ -1,22 +1,31 @@
 use wasm_bindgen::prelude::*;
-use wasm_bindgen_futures::spawn_local;
-use web_sys::{ErrorEvent, MessageEvent, WebSocket};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    CloseEvent, ErrorEvent, EventSource, Headers, MessageEvent,
+    Request, RequestInit, Response, WebSocket, Window,
+    WorkerGlobalScope,
+};
 
 use async_stream::stream;
+use base64::{engine::general_purpose::STANDARD, Engine};
 use futures::{
-    select, stream::BoxStream, FutureExt, Sink, SinkExt, StreamExt,
+    future::{self, BoxFuture},
+    select,
+    stream::BoxStream,
+    FutureExt, Sink, SinkExt, StreamExt,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::{collections::HashSet, pin::Pin, sync::Arc};
-use tokio::sync::{mpsc, RwLock};
+use std::{pin::Pin, sync::Arc, time::Duration};
+use tokio::sync::{mpsc, oneshot, RwLock};
 
 use mpc_protocol::{
-    channel::encrypt_server_channel, decode, encode, hex,
-    snow::Builder, zlib, Encoding, HandshakeMessage, MeetingId,
+    channel::encrypt_server_channel, decode,
+    ed25519_dalek::Signature, encode, hex, snow::Builder, zlib,
+    Encoding, HandshakeMessage, JoinCode, MeetingId, MeetingSlot,
     OpaqueMessage, ProtocolState, RequestMessage, ResponseMessage,
-    ServerMessage, SessionId, SessionRequest, TransparentMessage,
-    UserId,
+    ServerMessage, SessionId, SessionLimits, SessionQuorum,
+    SessionRequest, TransparentMessage, UserId,
 };
 
 use crate::{
@@ -80,8 +89,9 @@ impl WebClient {
                                     buffer,
                                 )) = e
                                 {
-                                    let inflated = zlib::inflate(&buffer)
-                                        .unwrap();
+                                    let inflated =
+                                        zlib::inflate(&buffer)
+                                            .unwrap();
                                     let message: ResponseMessage =
                                         decode(&inflated)
                                             .await
@@ -116,6 +126,18 @@ impl WebClient {
         ));
         onerror_callback.forget();
 
+        // End the reader stream when the socket is closed
+        let (close_tx, mut close_rx) = mpsc::channel::<()>(1);
+        let onclose_callback = Closure::once(move |_: CloseEvent| {
+            spawn_local(async move {
+                let _ = close_tx.send(()).await;
+            });
+        });
+        ws.set_onclose(Some(
+            onclose_callback.as_ref().unchecked_ref(),
+        ));
+        onclose_callback.forget();
+
         let (open_tx, mut open_rx) = mpsc::channel(1);
 
         let onopen_callback = Closure::once(move || {
@@ -144,6 +166,23 @@ impl WebClient {
             ProtocolState::Handshake(Box::new(handshake)),
         )));
 
+        // Browsers do not expose websocket ping frames so an
+        // empty frame is sent for the server to answer which
+        // resets the idle timeout
+        if let Some(timeout) = options.idle_timeout {
+            let socket = ws.clone();
+            spawn_local(async move {
+                loop {
+                    sleep(timeout / 2).await;
+                    if socket.ready_state() != WebSocket::OPEN
+                        || socket.send_with_u8_array(&[]).is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+
         let peers = Arc::new(RwLock::new(Default::default()));
         let options = Arc::new(options);
 
@@ -159,8 +198,16 @@ impl WebClient {
         // Proxy stream from the websocket message event closure
         // to the event loop
         let ws_reader = Box::pin(stream! {
-            while let Some(message) = ws_msg_rx.recv().await {
-                yield message;
+            loop {
+                select! {
+                    message = ws_msg_rx.recv().fuse() => {
+                        match message {
+                            Some(message) => yield message,
+                            None => break,
+                        }
+                    }
+                    _ = close_rx.recv().fuse() => break,
+                }
             }
         });
 
@@ -187,13 +234,15 @@ impl WebClient {
 
     client_impl!();
 
-    /*
-        self.ws_writer.ws.set_onopen(None);
-        self.ws_writer.ws.set_onmessage(None);
-        self.ws_writer.ws.set_onerror(None);
-
-        self.ws_writer.ws.close()?;
-    */
+    /// Remove the event listener closures and close the socket.
+    fn close_connection(&self) -> Result<()> {
+        self.ws.set_onopen(None);
+        self.ws.set_onmessage(None);
+        self.ws.set_onerror(None);
+        self.ws.set_onclose(None);
+        self.ws.close()?;
+        Ok(())
+    }
 }
 
 client_transport_impl!(WebClient);
@@ -213,10 +262,16 @@ unsafe impl Sync for WebClient {}
 impl EventLoop<WsMessage, WsError, WsReadStream, WsWriteStream> {
     /// Receive and decode socket messages then send to
     /// the messages channel.
+    ///
+    /// Empty frames answer the keep alive frames sent by
+    /// the client and are ignored.
     pub(crate) async fn read_message(
         incoming: WsMessage,
         event_proxy: &mut mpsc::Sender<ResponseMessage>,
     ) -> Result<()> {
+        if incoming.is_empty() {
+            return Ok(());
+        }
         let inflated = zlib::inflate(&incoming)?;
         let response: ResponseMessage = decode(&inflated).await?;
         event_proxy.send(response).await?;
@@ -241,6 +296,22 @@ impl EventLoop<WsMessage, WsError, WsReadStream, WsWriteStream> {
             .map_err(|_| Error::WebSocketSend)?)
     }
 
+    /// Future that completes when no frames have been
+    /// received for the configured timeout.
+    ///
+    /// Browsers do not expose websocket ping frames so the
+    /// client sends an empty frame at half the timeout which
+    /// the server answers with an empty frame.
+    fn idle_timeout(
+        options: &ClientOptions,
+    ) -> BoxFuture<'static, ()> {
+        if let Some(timeout) = options.idle_timeout {
+            delay(timeout)
+        } else {
+            Box::pin(future::pending())
+        }
+    }
+
     async fn handle_close_message(self) -> Result<()> {
         // NOTE: for webassembly this is handled by a platform
         // NOTE: specific implementation of close() in the
@@ -303,3 +374,391 @@ impl Sink<Vec<u8>> for WebSocketSink {
 // ever run in a single threaded context so we can
 // implement `Send` to appease the compiler.
 unsafe impl Send for WebSocketSink {}
+
+type HttpMessage = Vec<u8>;
+type HttpError = Error;
+type HttpReadStream = BoxStream<'static, Result<Vec<u8>>>;
+type HttpWriteStream = HttpWriter;
+
+/// Event loop for the web client using the HTTP transport.
+pub type WebHttpEventLoop = EventLoop<
+    HttpMessage,
+    HttpError,
+    HttpReadStream,
+    HttpWriteStream,
+>;
+
+/// Client for the web platform using the HTTP transport.
+///
+/// Response messages are received from an `EventSource` and
+/// request messages are sent using `fetch` for networks that
+/// block websocket connections.
+#[derive(Clone)]
+pub struct WebHttpClient {
+    events: EventSource,
+    uri: String,
+    authorization: String,
+    options: Arc<ClientOptions>,
+    outbound_tx: mpsc::Sender<InternalMessage>,
+    server: Server,
+    peers: Peers,
+}
+
+impl WebHttpClient {
+    /// Create a new web client using the HTTP transport.
+    ///
+    /// The server URL uses the `http` or `https` scheme
+    /// and may include the path prefix for the server.
+    pub async fn new(
+        server: &str,
+        options: ClientOptions,
+    ) -> Result<(WebHttpClient, WebHttpEventLoop)> {
+        let server = server.trim_end_matches('/');
+        let public_key = hex::encode(options.keypair.public_key());
+        let mut events_url =
+            format!("{}/http?public_key={}", server, public_key);
+        if let Some(token) = &options.token {
+            events_url.push_str("&token=");
+            events_url.push_str(&crate::encode_query(token));
+        }
+
+        let events = EventSource::new(&events_url)?;
+
+        // Data for the connection or none when the event
+        // stream could not be opened
+        let (connect_tx, mut connect_rx) =
+            mpsc::unbounded_channel::<Option<String>>();
+        let (msg_tx, mut msg_rx) =
+            mpsc::unbounded_channel::<Result<Vec<u8>>>();
+        let (close_tx, mut close_rx) =
+            mpsc::unbounded_channel::<()>();
+
+        let onconnect_tx = connect_tx.clone();
+        let onconnect_callback =
+            Closure::<dyn FnMut(_)>::new(move |e: MessageEvent| {
+                let _ = onconnect_tx.send(e.data().as_string());
+            });
+        events.add_event_listener_with_callback(
+            "connect",
+            onconnect_callback.as_ref().unchecked_ref(),
+        )?;
+        onconnect_callback.forget();
+
+        let ping_tx = msg_tx.clone();
+        let onmessage_callback =
+            Closure::<dyn FnMut(_)>::new(move |e: MessageEvent| {
+                let buffer = e
+                    .data()
+                    .as_string()
+                    .ok_or(Error::InvalidEventStream)
+                    .and_then(|data| {
+                        STANDARD.decode(data).map_err(Error::from)
+                    });
+                let _ = msg_tx.send(buffer);
+            });
+        events.set_onmessage(Some(
+            onmessage_callback.as_ref().unchecked_ref(),
+        ));
+        onmessage_callback.forget();
+
+        // The event source would reconnect after an error
+        // which creates a new connection on the server so
+        // close the event source and end the reader stream
+        let source = events.clone();
+        let onerror_callback =
+            Closure::<dyn FnMut(_)>::new(move |_: web_sys::Event| {
+                source.close();
+                let _ = connect_tx.send(None);
+                let _ = close_tx.send(());
+            });
+        events.set_onerror(Some(
+            onerror_callback.as_ref().unchecked_ref(),
+        ));
+        onerror_callback.forget();
+
+        let ConnectEvent { id, secret } =
+            match connect_rx.recv().await {
+                Some(Some(data)) => serde_json::from_str(&data)
+                    .map_err(|_| Error::InvalidEventStream)?,
+                _ => return Err(Error::InvalidEventStream),
+            };
+        drop(connect_rx);
+        let authorization = format!("Bearer {}", secret);
+
+        let uri = format!(
+            "{}/http/{}?public_key={}",
+            server, id, public_key
+        );
+
+        // Pings are answered with an empty POST request like
+        // the pong frames sent automatically for a websocket
+        // and reset the idle timeout with an empty message
+        let pong_uri = uri.clone();
+        let pong_authorization = authorization.clone();
+        let onping_callback =
+            Closure::<dyn FnMut(_)>::new(move |_: MessageEvent| {
+                let _ = ping_tx.send(Ok(Vec::new()));
+                let uri = pong_uri.clone();
+                let authorization = pong_authorization.clone();
+                spawn_local(async move {
+                    if let Err(e) =
+                        fetch("POST", &uri, &authorization, None)
+                            .await
+                    {
+                        log::warn!("http pong failed: {}", e);
+                    }
+                });
+            });
+        events.add_event_listener_with_callback(
+            "ping",
+            onping_callback.as_ref().unchecked_ref(),
+        )?;
+        onping_callback.forget();
+
+        // Channel for writing outbound messages to send
+        // to the server
+        let (outbound_tx, outbound_rx) =
+            mpsc::channel::<InternalMessage>(32);
+
+        let builder = Builder::new(options.params()?);
+        let handshake = builder
+            .local_private_key(options.keypair.private_key())
+            .remote_public_key(&options.server_public_key)
+            .build_initiator()?;
+
+        // State for the server transport
+        let server = Arc::new(RwLock::new(Some(
+            ProtocolState::Handshake(Box::new(handshake)),
+        )));
+
+        let peers = Arc::new(RwLock::new(Default::default()));
+        let options = Arc::new(options);
+
+        let client = WebHttpClient {
+            events,
+            uri: uri.clone(),
+            authorization: authorization.clone(),
+            options: Arc::clone(&options),
+            outbound_tx: outbound_tx.clone(),
+            server: Arc::clone(&server),
+            peers: Arc::clone(&peers),
+        };
+
+        // Proxy stream from the event source closures
+        // to the event loop
+        let ws_reader = Box::pin(stream! {
+            loop {
+                select! {
+                    message = msg_rx.recv().fuse() => {
+                        match message {
+                            Some(message) => yield message,
+                            None => break,
+                        }
+                    }
+                    _ = close_rx.recv().fuse() => break,
+                }
+            }
+        });
+
+        // Decoded event stream messages are sent over this channel
+        let (inbound_tx, inbound_rx) =
+            mpsc::channel::<ResponseMessage>(32);
+
+        let event_loop: WebHttpEventLoop = EventLoop {
+            options,
+            ws_reader,
+            ws_writer: HttpWriter { uri, authorization },
+            inbound_tx,
+            inbound_rx,
+            outbound_tx,
+            outbound_rx,
+            server,
+            peers,
+        };
+
+        Ok((client, event_loop))
+    }
+
+    client_impl!();
+
+    /// Close the event source and the connection on the server.
+    fn close_connection(&self) -> Result<()> {
+        self.events.set_onmessage(None);
+        self.events.set_onerror(None);
+        self.events.close();
+        let uri = self.uri.clone();
+        let authorization = self.authorization.clone();
+        spawn_local(async move {
+            if let Err(e) =
+                fetch("DELETE", &uri, &authorization, None).await
+            {
+                log::warn!("http close failed: {}", e);
+            }
+        });
+        Ok(())
+    }
+}
+
+client_transport_impl!(WebHttpClient);
+
+// The `EventSource` type stores a `JsValue` which is not
+// `Send`, see the note for `WebSocketSink`.
+unsafe impl Send for WebHttpClient {}
+unsafe impl Sync for WebHttpClient {}
+
+impl
+    EventLoop<HttpMessage, HttpError, HttpReadStream, HttpWriteStream>
+{
+    /// Decode messages from the event source then send to
+    /// the messages channel.
+    ///
+    /// Empty messages are dispatched for ping events and
+    /// are ignored.
+    pub(crate) async fn read_message(
+        incoming: HttpMessage,
+        event_proxy: &mut mpsc::Sender<ResponseMessage>,
+    ) -> Result<()> {
+        if incoming.is_empty() {
+            return Ok(());
+        }
+        let inflated = zlib::inflate(&incoming)?;
+        let response: ResponseMessage = decode(&inflated).await?;
+        event_proxy.send(response).await?;
+        Ok(())
+    }
+
+    /// Send a message in a POST request.
+    ///
+    /// Waits for the response so that messages are received
+    /// by the server in order.
+    pub(crate) async fn send_message(
+        &mut self,
+        message: RequestMessage,
+    ) -> Result<()> {
+        let encoded = encode(&message).await?;
+        let deflated = zlib::deflate(&encoded)?;
+
+        // Javascript futures are not `Send` so the request
+        // is sent on the local task
+        let (result_tx, result_rx) = oneshot::channel();
+        let uri = self.ws_writer.uri.clone();
+        let authorization = self.ws_writer.authorization.clone();
+        spawn_local(async move {
+            let _ = result_tx.send(
+                fetch("POST", &uri, &authorization, Some(deflated))
+                    .await,
+            );
+        });
+        result_rx.await.map_err(|_| Error::HttpSend)?
+    }
+
+    /// Future that completes when no messages or ping
+    /// events have been received for the configured timeout.
+    fn idle_timeout(
+        options: &ClientOptions,
+    ) -> BoxFuture<'static, ()> {
+        if let Some(timeout) = options.idle_timeout {
+            delay(timeout)
+        } else {
+            Box::pin(future::pending())
+        }
+    }
+
+    async fn handle_close_message(self) -> Result<()> {
+        // NOTE: closed by the platform specific implementation
+        // NOTE: of close() in the NetworkTransport, see the
+        // NOTE: websocket event loop.
+        Ok(())
+    }
+
+    event_loop_run_impl!();
+}
+
+/// Writer for requests to the server.
+#[doc(hidden)]
+pub struct HttpWriter {
+    uri: String,
+    authorization: String,
+}
+
+/// Data for the first event on the stream.
+#[derive(Deserialize)]
+struct ConnectEvent {
+    /// Identifier for the connection.
+    id: String,
+    /// Secret for requests to the connection.
+    secret: String,
+}
+
+/// Send a request to the server using `fetch`.
+async fn fetch(
+    method: &str,
+    uri: &str,
+    authorization: &str,
+    body: Option<Vec<u8>>,
+) -> Result<()> {
+    let headers = Headers::new()?;
+    headers.set("Authorization", authorization)?;
+    let init = RequestInit::new();
+    init.set_method(method);
+    init.set_headers(&headers);
+    if let Some(body) = body {
+        init.set_body(&js_sys::Uint8Array::from(&body[..]));
+    }
+    let request = Request::new_with_str_and_init(uri, &init)?;
+
+    // Clients may run in a window or a worker
+    let global = js_sys::global();
+    let promise = if let Some(window) = global.dyn_ref::<Window>() {
+        window.fetch_with_request(&request)
+    } else if let Some(worker) = global.dyn_ref::<WorkerGlobalScope>()
+    {
+        worker.fetch_with_request(&request)
+    } else {
+        return Err(Error::HttpSend);
+    };
+
+    let response: Response =
+        JsFuture::from(promise).await?.dyn_into()?;
+    if response.ok() {
+        Ok(())
+    } else {
+        Err(Error::HttpSend)
+    }
+}
+
+/// Wait for a duration using a timer on the global scope.
+async fn sleep(duration: Duration) {
+    let millis = duration.as_millis().min(i32::MAX as u128) as i32;
+    let global = js_sys::global();
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        // Clients may run in a window or a worker
+        if let Some(window) = global.dyn_ref::<Window>() {
+            let _ = window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    &resolve, millis,
+                );
+        } else if let Some(worker) =
+            global.dyn_ref::<WorkerGlobalScope>()
+        {
+            let _ = worker
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    &resolve, millis,
+                );
+        }
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
+/// Future that completes after a duration.
+///
+/// Javascript futures are not `Send` so the timer runs
+/// on the local task.
+fn delay(duration: Duration) -> BoxFuture<'static, ()> {
+    let (tx, rx) = oneshot::channel();
+    spawn_local(async move {
+        sleep(duration).await;
+        let _ = tx.send(());
+    });
+    Box::pin(rx.map(|_| ()))
+}
@@ -136,6 +136,9 @@ where
                         output = Some(result);
                         break;
                     }
+                } else {
+                    return Err(
+                        mpc_client::Error::ConnectionLost.into());
                 }
             },
         }
@@ -159,6 +162,8 @@ pub async fn wait_for_close(
                     if let Event::Close = event {
                         break;
                     }
+                } else {
+                    break;
                 }
             },
         }
@@ -181,6 +186,9 @@ pub async fn wait_for_session_finish(
                             break;
                         }
                     }
+                } else {
+                    return Err(
+                        mpc_client::Error::ConnectionLost.into());
                 }
             },
         }
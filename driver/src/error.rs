@@ -1,3 +1,4 @@
+use mpc_protocol::{MeetingId, SessionId};
 use thiserror::Error;
 
 /// Errors generated by the driver.
@@ -12,6 +13,11 @@ pub enum Error {
     #[error("session identifier required")]
     SessionIdRequired,
 
+    /// Error generated when a peer connects before the
+    /// session state has been received.
+    #[error("peer connected before the session state was received")]
+    SessionStateRequired,
+
     /// Error generated when meeting identifiers are not unique.
     #[error("meeting identifiers must be unique")]
     MeetingIdentifiersNotUnique,
@@ -21,6 +27,39 @@ pub enum Error {
     #[error("meeting initiator must exist in list of identifiers")]
     MeetingInitiatorNotExist,
 
+    /// Error generated when the owner cancels a meeting
+    /// while waiting for it to become ready.
+    #[error(r#"meeting "{0}" was cancelled"#)]
+    MeetingCancelled(MeetingId),
+
+    /// Error generated when a meeting expires while
+    /// waiting for it to become ready.
+    #[error(r#"meeting "{0}" expired"#)]
+    MeetingExpired(MeetingId),
+
+    /// Error generated when meeting data could not be encrypted
+    /// or decrypted.
+    #[error("meeting data could not be encrypted or decrypted")]
+    MeetingData,
+
+    /// Error generated when a meeting participant that filled a
+    /// slot bound to a verifying key does not have a valid signature.
+    #[error(r#"invalid signature for participant in meeting "{0}""#)]
+    MeetingSignature(MeetingId),
+
+    /// Error generated when creating a meeting that encrypts the
+    /// meeting data and uses invitations; the data key is derived
+    /// from the join code which is not issued with invitations.
+    #[error(
+        "meeting data cannot be encrypted when using invitations"
+    )]
+    MeetingInvitationsEncrypted,
+
+    /// Error generated when a session proceeds with a quorum
+    /// that does not include this participant.
+    #[error(r#"participant was excluded from session "{0}""#)]
+    ExcludedFromSession(SessionId),
+
     #[cfg(feature = "gg20")]
     /// GG20 driver errors.
     #[error(transparent)]
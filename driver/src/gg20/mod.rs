@@ -31,6 +31,7 @@ pub async fn keygen(
     let is_initiator = participants.is_some();
 
     let parameters = options.parameters;
+    let limits = options.limits;
 
     // Create the client
     let (client, event_loop) = new_client(options).await?;
@@ -48,6 +49,8 @@ pub async fn keygen(
         SessionHandler::Initiator(SessionInitiator::new(
             transport,
             participants,
+            None,
+            limits,
         ))
     } else {
         SessionHandler::Participant(SessionParticipant::new(
@@ -88,6 +91,8 @@ pub async fn sign(
     let is_initiator = participants.is_some();
 
     let parameters = options.parameters;
+    let limits = options.limits;
+    let quorum = options.quorum;
 
     // Create the client
     let (client, event_loop) = new_client(options).await?;
@@ -105,6 +110,8 @@ pub async fn sign(
         SessionHandler::Initiator(SessionInitiator::new(
             transport,
             participants,
+            quorum,
+            limits,
         ))
     } else {
         SessionHandler::Participant(SessionParticipant::new(
@@ -153,6 +153,9 @@ pub(crate) async fn new_client(
         keypair: options.keypair,
         server_public_key: options.server.server_public_key,
         pattern: options.server.pattern,
+        idle_timeout: None,
+        token: options.server.token,
+        tls: None,
     };
     let url = options.url(&server_url);
     Ok(Client::new(&url, options).await?)
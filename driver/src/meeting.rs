@@ -1,43 +1,105 @@
 //! Create and join meeting points so session participants
 //! can exchange public keys.
 //!
-//! The meeting identifier is the shared secret that participants
-//! can use to exchange public keys so should only be given to parties
+//! The meeting identifier is public but joining a meeting requires
+//! the join code returned when the meeting is created, or a one-time
+//! invitation code for the slot. Codes should only be given to parties
 //! that should be included in a session.
+//!
+//! When `encrypt_data` is set in the meeting options the join code
+//! returned when creating a meeting is a secret that never leaves
+//! the client. The code sent to the server and the key used to
+//! encrypt the meeting data are both derived from the secret so
+//! the server only sees ciphertext. Participants must join using
+//! the secret to decrypt the meeting data.
 use crate::{Error, Event, MeetingOptions, Result, ServerOptions};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
 use futures::StreamExt;
 use mpc_client::{Client, ClientOptions, NetworkTransport};
-use mpc_protocol::{MeetingId, UserId};
+use mpc_protocol::{
+    ed25519_dalek::{Signer, SigningKey},
+    hex, join_meeting_message, JoinCode, MeetingId, MeetingSecret,
+    MeetingSlot, UserId,
+};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha3::{Digest, Sha3_256};
 use std::collections::HashSet;
 
+/// Context for deriving the server join code from a secret.
+const JOIN_CODE_CONTEXT: &[u8] = b"mpc-meeting-join-code";
+
+/// Context for deriving the data encryption key from a secret.
+const DATA_KEY_CONTEXT: &[u8] = b"mpc-meeting-data-key";
+
+/// Meeting data encrypted by the meeting creator.
+#[derive(Serialize, Deserialize)]
+struct EncryptedData {
+    #[serde(with = "hex::serde")]
+    nonce: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    ciphertext: Vec<u8>,
+}
+
 /// Create a new meeting point.
+///
+/// The order of the slots determines the order of the
+/// participant public keys when the meeting is ready.
+///
+/// Slots bound to a verifying key can only be joined by
+/// participants that sign the join request with the
+/// corresponding signing key; the slot for the initiator
+/// must not have a verifying key.
+///
+/// When `invitations` is set the returned secret includes a
+/// one-time invitation code for each participant slot and no
+/// join code; invitations cannot be combined with `encrypt_data`.
 pub async fn create(
     options: MeetingOptions,
-    identifiers: Vec<UserId>,
+    slots: Vec<MeetingSlot>,
     initiator: UserId,
+    invitations: bool,
     data: Value,
-) -> Result<MeetingId> {
-    let num_ids = identifiers.len();
-    let slots: HashSet<UserId> = identifiers.into_iter().collect();
+) -> Result<(MeetingId, MeetingSecret)> {
+    let unique: HashSet<&UserId> =
+        slots.iter().map(|slot| &slot.user_id).collect();
 
-    if slots.len() != num_ids {
+    if unique.len() != slots.len() {
         return Err(Error::MeetingIdentifiersNotUnique);
     }
 
-    if slots.get(&initiator).is_none() {
+    if unique.get(&initiator).is_none() {
         return Err(Error::MeetingInitiatorNotExist);
     }
 
+    if options.encrypt_data && invitations {
+        return Err(Error::MeetingInvitationsEncrypted);
+    }
+
+    let key = if options.encrypt_data {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        Some(JoinCode::from(key))
+    } else {
+        None
+    };
+
     let ServerOptions {
         server_url,
         server_public_key,
+        token,
         ..
     } = options.server;
     let options = ClientOptions {
         keypair: options.keypair,
         server_public_key,
         pattern: None,
+        idle_timeout: None,
+        token,
+        tls: None,
     };
     let url = options.url(&server_url);
     let (mut client, event_loop) = Client::new(&url, options).await?;
@@ -49,46 +111,89 @@ pub async fn create(
         let event = event?;
         match event {
             Event::ServerConnected { .. } => {
+                let (join_code, data) = if let Some(key) = &key {
+                    (
+                        Some(derive_join_code(key)),
+                        encrypt_data(key, &data)
+                            .ok_or(Error::MeetingData)?,
+                    )
+                } else {
+                    (None, data.clone())
+                };
+
                 client
                     .new_meeting(
                         initiator.clone(),
                         slots.clone(),
-                        data.clone(),
+                        invitations,
+                        join_code,
+                        data,
                     )
                     .await?;
             }
-            Event::MeetingCreated(meeting) => {
+            Event::MeetingCreated(meeting, mut secret) => {
                 let _ = client.close().await;
-                return Ok(meeting.meeting_id);
+                // Participants need the secret to decrypt the data
+                if let Some(key) = key {
+                    secret.join_code = Some(key);
+                }
+                return Ok((meeting.meeting_id, secret));
             }
             _ => {}
         }
     }
-    unreachable!();
+    Err(mpc_client::Error::ConnectionLost.into())
 }
 
 /// Join a meeting point.
 ///
 /// When all participants have joined the meeting point the public
-/// keys of all participants are returned.
+/// keys of all participants are returned in slot order.
 ///
 /// When  the user identifier is not given then the user is
 /// the creator of the meeting point who has already been
 /// registered as a participant when creating the meeting.
+///
+/// The signing key is required to join a slot bound to a
+/// verifying key.
+///
+/// The slots are the slots the meeting was created with; the
+/// participants returned by the relay must fill these slots in
+/// order and their signatures are verified using the verifying
+/// keys of the slots, not the keys returned by the relay, before
+/// the public keys are returned.
 pub async fn join(
     options: MeetingOptions,
     meeting_id: MeetingId,
     user_id: Option<UserId>,
+    join_code: JoinCode,
+    signing_key: Option<SigningKey>,
+    slots: Vec<MeetingSlot>,
 ) -> Result<(Vec<Vec<u8>>, Value)> {
+    let key = if options.encrypt_data {
+        Some(join_code.clone())
+    } else {
+        None
+    };
+    let join_code = if let Some(key) = &key {
+        derive_join_code(key)
+    } else {
+        join_code
+    };
+
     let ServerOptions {
         server_url,
         server_public_key,
+        token,
         ..
     } = options.server;
     let options = ClientOptions {
         keypair: options.keypair,
         server_public_key,
         pattern: None,
+        idle_timeout: None,
+        token,
+        tls: None,
     };
     let url = options.url(&server_url);
     let (mut client, event_loop) = Client::new(&url, options).await?;
@@ -101,21 +206,105 @@ pub async fn join(
         match event {
             Event::ServerConnected { .. } => {
                 if let Some(user_id) = &user_id {
+                    let signature = signing_key.as_ref().map(|key| {
+                        key.sign(&join_meeting_message(
+                            &meeting_id,
+                            client.public_key(),
+                        ))
+                    });
                     client
-                        .join_meeting(meeting_id, user_id.clone())
+                        .join_meeting(
+                            meeting_id,
+                            user_id.clone(),
+                            join_code.clone(),
+                            signature,
+                        )
                         .await?;
                 }
             }
             Event::MeetingReady(meeting) => {
                 let _ = client.close().await;
+                if meeting.registered_participants.len()
+                    != slots.len()
+                    || !meeting
+                        .registered_participants
+                        .iter()
+                        .zip(slots.iter())
+                        .all(|(p, slot)| {
+                            p.verify_slot(&meeting_id, slot)
+                        })
+                {
+                    return Err(Error::MeetingSignature(meeting_id));
+                }
                 let public_keys: Vec<Vec<u8>> = meeting
                     .registered_participants
                     .into_iter()
+                    .map(|p| p.public_key)
                     .collect();
-                return Ok((public_keys, meeting.data));
+                let data = if let Some(key) = &key {
+                    decrypt_data(key, meeting.data)
+                        .ok_or(Error::MeetingData)?
+                } else {
+                    meeting.data
+                };
+                return Ok((public_keys, data));
+            }
+            Event::MeetingCancelled(id) if id == meeting_id => {
+                let _ = client.close().await;
+                return Err(Error::MeetingCancelled(meeting_id));
+            }
+            Event::MeetingExpired(id) if id == meeting_id => {
+                let _ = client.close().await;
+                return Err(Error::MeetingExpired(meeting_id));
             }
             _ => {}
         }
     }
-    unreachable!();
+    Err(mpc_client::Error::ConnectionLost.into())
+}
+
+/// Derive a value from a meeting secret for a context.
+fn derive(secret: &JoinCode, context: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(context);
+    hasher.update(secret.as_ref());
+    hasher.finalize().into()
+}
+
+/// Derive the join code sent to the server from a meeting secret.
+fn derive_join_code(secret: &JoinCode) -> JoinCode {
+    derive(secret, JOIN_CODE_CONTEXT).into()
+}
+
+/// Encrypt meeting data with a key derived from a meeting secret.
+fn encrypt_data(secret: &JoinCode, data: &Value) -> Option<Value> {
+    let key = derive(secret, DATA_KEY_CONTEXT);
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let plaintext = serde_json::to_vec(data).ok()?;
+    let ciphertext =
+        cipher.encrypt(&nonce, plaintext.as_slice()).ok()?;
+    serde_json::to_value(EncryptedData {
+        nonce: nonce.to_vec(),
+        ciphertext,
+    })
+    .ok()
+}
+
+/// Decrypt meeting data with a key derived from a meeting secret.
+fn decrypt_data(secret: &JoinCode, data: Value) -> Option<Value> {
+    let encrypted: EncryptedData =
+        serde_json::from_value(data).ok()?;
+    if encrypted.nonce.len() != 12 {
+        return None;
+    }
+    let key = derive(secret, DATA_KEY_CONTEXT);
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    let plaintext = cipher
+        .decrypt(
+            Nonce::from_slice(&encrypted.nonce),
+            encrypted.ciphertext.as_slice(),
+        )
+        .ok()?;
+    serde_json::from_slice(&plaintext).ok()
 }
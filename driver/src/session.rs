@@ -1,8 +1,8 @@
-use crate::Result;
+use crate::{Error, Result};
 use async_trait::async_trait;
 use futures::{select, FutureExt, StreamExt};
 use mpc_client::{Event, EventStream, NetworkTransport, Transport};
-use mpc_protocol::SessionState;
+use mpc_protocol::{SessionLimits, SessionQuorum, SessionState};
 use tokio::sync::Mutex;
 
 /// Trait for types that handle session related events.
@@ -49,6 +49,8 @@ impl From<SessionHandler> for Transport {
 pub struct SessionInitiator {
     transport: Transport,
     session_participants: Vec<Vec<u8>>,
+    quorum: Option<SessionQuorum>,
+    limits: SessionLimits,
     session_state: Mutex<Option<SessionState>>,
     requested_session: bool,
 }
@@ -58,10 +60,14 @@ impl SessionInitiator {
     pub fn new(
         transport: Transport,
         session_participants: Vec<Vec<u8>>,
+        quorum: Option<SessionQuorum>,
+        limits: SessionLimits,
     ) -> Self {
         Self {
             transport,
             session_participants,
+            quorum,
+            limits,
             session_state: Mutex::new(None),
             requested_session: false,
         }
@@ -73,7 +79,11 @@ impl SessionInitiator {
             && self.transport.is_connected().await
         {
             self.transport
-                .new_session(self.session_participants.clone())
+                .new_session(
+                    self.session_participants.clone(),
+                    self.quorum,
+                    self.limits,
+                )
                 .await?;
 
             self.requested_session = true;
@@ -110,10 +120,15 @@ impl SessionEventHandler for SessionInitiator {
                 for key in connections {
                     self.transport.connect_peer(key).await?;
                 }
+
+                let mut state = self.session_state.lock().await;
+                *state = Some(session);
             }
             Event::PeerConnected { peer_key } => {
                 let state = self.session_state.lock().await;
-                let session = state.as_ref().unwrap();
+                let session = state
+                    .as_ref()
+                    .ok_or(Error::SessionStateRequired)?;
                 let connections =
                     session.connections(self.transport.public_key());
                 if connections.contains(&peer_key) {
@@ -159,12 +174,34 @@ impl SessionParticipant {
 #[async_trait]
 impl SessionEventHandler for SessionParticipant {
     /// Handle joining a session for a participant.
+    ///
+    /// When the session proceeds with a quorum that does
+    /// not include this participant an error is returned
+    /// once the session is ready.
     async fn handle_event(
         &mut self,
         event: Event,
     ) -> Result<Option<SessionState>> {
         match event {
+            Event::SessionInvitation(session) => {
+                tracing::info!(
+                    id = ?session.session_id.to_string(),
+                    "session invitation");
+
+                self.transport
+                    .join_session(session.session_id)
+                    .await?;
+            }
             Event::SessionReady(session) => {
+                if session
+                    .party_number(self.transport.public_key())
+                    .is_none()
+                {
+                    return Err(Error::ExcludedFromSession(
+                        session.session_id,
+                    ));
+                }
+
                 let mut state = self.session_state.lock().await;
                 *state = Some(session.clone());
 
@@ -198,6 +235,14 @@ impl SessionEventHandler for SessionParticipant {
                 }
             }
             Event::SessionActive(session) => {
+                if session
+                    .party_number(self.transport.public_key())
+                    .is_none()
+                {
+                    return Err(Error::ExcludedFromSession(
+                        session.session_id,
+                    ));
+                }
                 return Ok(Some(session));
             }
             _ => {}
@@ -233,6 +278,9 @@ where
                         session = Some(active_session);
                         break;
                     }
+                } else {
+                    return Err(
+                        mpc_client::Error::ConnectionLost.into());
                 }
             },
         }
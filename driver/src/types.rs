@@ -1,7 +1,9 @@
 //! Types passed across the Javascript/Webassembly boundary.
 use serde::{Deserialize, Serialize};
 
-use mpc_protocol::{hex, Keypair, Parameters};
+use mpc_protocol::{
+    hex, Keypair, Parameters, SessionLimits, SessionQuorum,
+};
 
 /// Supported multi-party computation protocols.
 #[derive(Copy, Clone, Serialize, Deserialize)]
@@ -75,11 +77,16 @@ impl From<crate::gg20::KeyShare> for KeyShare {
 
 /// Options for creating or joining a meeting point.
 #[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct MeetingOptions {
     /// Keypair for the participant.
     pub keypair: Keypair,
     /// Server options.
     pub server: ServerOptions,
+    /// Encrypt the meeting data so it is not visible
+    /// to the server.
+    #[serde(default)]
+    pub encrypt_data: bool,
 }
 
 /// Server options.
@@ -93,6 +100,9 @@ pub struct ServerOptions {
     pub server_public_key: Vec<u8>,
     /// Noise parameters pattern.
     pub pattern: Option<String>,
+    /// Bearer token for servers that require authentication.
+    #[serde(default)]
+    pub token: Option<String>,
 }
 
 /// Options used to drive a session to completion.
@@ -107,4 +117,13 @@ pub struct SessionOptions {
     pub server: ServerOptions,
     /// Parameters for key generation.
     pub parameters: Parameters,
+    /// Quorum for the session.
+    ///
+    /// Only used when signing as key generation requires
+    /// all the participants.
+    #[serde(default)]
+    pub quorum: Option<SessionQuorum>,
+    /// Limits requested for the session.
+    #[serde(default)]
+    pub limits: SessionLimits,
 }
@@ -106,6 +106,67 @@ pub(crate) mod types {
     pub const SESSION_CLOSE: u8 = 11;
     pub const SESSION_FINISHED: u8 = 12;
 
+    pub const CONNECTION_REPLACED: u8 = 13;
+
+    pub const MEETING_LEAVE: u8 = 14;
+    pub const MEETING_CANCEL: u8 = 15;
+    pub const MEETING_CANCELLED: u8 = 16;
+    pub const MEETING_EXPIRED: u8 = 17;
+
+    pub const SESSION_GET: u8 = 18;
+    pub const SESSION_INFO: u8 = 19;
+    pub const MEETING_GET: u8 = 20;
+    pub const MEETING_INFO: u8 = 21;
+    pub const SESSION_LIST: u8 = 22;
+    pub const SESSION_LIST_RESPONSE: u8 = 23;
+
+    pub const SESSION_EXPIRED: u8 = 24;
+    pub const SESSION_EXPIRING: u8 = 25;
+    pub const MEETING_EXPIRING: u8 = 26;
+
+    pub const SESSION_INVITATION: u8 = 27;
+    pub const SESSION_JOIN: u8 = 28;
+
     pub const ENCODING_BLOB: u8 = 1;
     pub const ENCODING_JSON: u8 = 2;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, IDENTITY, VERSION};
+    use crate::{ErrorCode, ResponseMessage, TransparentMessage};
+    use anyhow::Result;
+    use futures::executor::block_on;
+
+    #[test]
+    fn preamble_version_mismatch() -> Result<()> {
+        block_on(async {
+            let message = ResponseMessage::Transparent(
+                TransparentMessage::Error(
+                    ErrorCode::Unknown(999),
+                    "message".to_owned(),
+                ),
+            );
+            let mut buffer = encode(&message).await?;
+
+            // Unknown error codes keep the message
+            let decoded: ResponseMessage = decode(&buffer).await?;
+            assert!(matches!(
+                decoded,
+                ResponseMessage::Transparent(TransparentMessage::Error(
+                    ErrorCode::Unknown(999),
+                    ref message,
+                )) if message == "message"
+            ));
+
+            // Previous version is rejected
+            let offset = IDENTITY.len();
+            buffer[offset..offset + 2]
+                .copy_from_slice(&(VERSION - 1).to_le_bytes());
+            assert!(decode::<ResponseMessage>(&buffer)
+                .await
+                .is_err());
+            Ok(())
+        })
+    }
+}
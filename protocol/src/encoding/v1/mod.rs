@@ -2,8 +2,11 @@ use async_trait::async_trait;
 use binary_stream::futures::{
     BinaryReader, BinaryWriter, Decodable, Encodable,
 };
+use ed25519_dalek::{
+    Signature, VerifyingKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH,
+};
 use futures::io::{AsyncRead, AsyncSeek, AsyncWrite};
-use std::{collections::HashSet, io::Result};
+use std::io::Result;
 
 use crate::{
     encoding::{
@@ -11,13 +14,18 @@ use crate::{
         MAX_BUFFER_SIZE,
     },
     Chunk, Encoding, Error, HandshakeMessage, MeetingId,
-    MeetingState, OpaqueMessage, RequestMessage, ResponseMessage,
-    SealedEnvelope, ServerMessage, SessionId, SessionRequest,
-    SessionState, TransparentMessage,
+    MeetingInvitation, MeetingParticipant, MeetingSecret,
+    MeetingSlot, MeetingState, OpaqueMessage, RequestMessage,
+    ResponseMessage, SealedEnvelope, ServerMessage, SessionId,
+    SessionLimits, SessionQuorum, SessionRequest, SessionState,
+    SessionStatus, TransparentMessage,
 };
 
 /// Version for binary encoding.
-pub const VERSION: u16 = 1;
+///
+/// Must be incremented when the layout of a message changes
+/// so peers using a different layout are rejected.
+pub const VERSION: u16 = 2;
 
 /// Encode a length-prefixed buffer.
 async fn encode_buffer<W: AsyncWrite + AsyncSeek + Unpin + Send>(
@@ -70,6 +78,97 @@ async fn decode_payload<R: AsyncRead + AsyncSeek + Unpin + Send>(
     Ok((length, buffer))
 }
 
+/// Encode an optional verifying key for a meeting slot.
+async fn encode_verifying_key<
+    W: AsyncWrite + AsyncSeek + Unpin + Send,
+>(
+    writer: &mut BinaryWriter<W>,
+    verifying_key: &Option<VerifyingKey>,
+) -> Result<()> {
+    writer.write_bool(verifying_key.is_some()).await?;
+    if let Some(verifying_key) = verifying_key {
+        writer.write_bytes(verifying_key.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Decode an optional verifying key for a meeting slot.
+async fn decode_verifying_key<
+    R: AsyncRead + AsyncSeek + Unpin + Send,
+>(
+    reader: &mut BinaryReader<R>,
+) -> Result<Option<VerifyingKey>> {
+    let has_verifying_key = reader.read_bool().await?;
+    Ok(if has_verifying_key {
+        let bytes: [u8; PUBLIC_KEY_LENGTH] = reader
+            .read_bytes(PUBLIC_KEY_LENGTH)
+            .await?
+            .try_into()
+            .unwrap();
+        Some(
+            VerifyingKey::from_bytes(&bytes)
+                .map_err(encoding_error)?,
+        )
+    } else {
+        None
+    })
+}
+
+/// Encode an optional signature for joining a meeting slot.
+async fn encode_signature<
+    W: AsyncWrite + AsyncSeek + Unpin + Send,
+>(
+    writer: &mut BinaryWriter<W>,
+    signature: &Option<Signature>,
+) -> Result<()> {
+    writer.write_bool(signature.is_some()).await?;
+    if let Some(signature) = signature {
+        writer.write_bytes(signature.to_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Decode an optional signature for joining a meeting slot.
+async fn decode_signature<R: AsyncRead + AsyncSeek + Unpin + Send>(
+    reader: &mut BinaryReader<R>,
+) -> Result<Option<Signature>> {
+    let has_signature = reader.read_bool().await?;
+    Ok(if has_signature {
+        let bytes: [u8; SIGNATURE_LENGTH] = reader
+            .read_bytes(SIGNATURE_LENGTH)
+            .await?
+            .try_into()
+            .unwrap();
+        Some(Signature::from_bytes(&bytes))
+    } else {
+        None
+    })
+}
+
+/// Encode an optional meeting slot role.
+async fn encode_role<W: AsyncWrite + AsyncSeek + Unpin + Send>(
+    writer: &mut BinaryWriter<W>,
+    role: &Option<String>,
+) -> Result<()> {
+    writer.write_bool(role.is_some()).await?;
+    if let Some(role) = role {
+        writer.write_string(role).await?;
+    }
+    Ok(())
+}
+
+/// Decode an optional meeting slot role.
+async fn decode_role<R: AsyncRead + AsyncSeek + Unpin + Send>(
+    reader: &mut BinaryReader<R>,
+) -> Result<Option<String>> {
+    let has_role = reader.read_bool().await?;
+    Ok(if has_role {
+        Some(reader.read_string().await?)
+    } else {
+        None
+    })
+}
+
 //#[cfg_attr(target_arch="wasm32", async_trait(?Send))]
 //#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[async_trait]
@@ -167,11 +266,7 @@ impl Decodable for TransparentMessage {
         let id = reader.read_u8().await?;
         match id {
             types::ERROR => {
-                let code = reader
-                    .read_u16()
-                    .await?
-                    .try_into()
-                    .map_err(encoding_error)?;
+                let code = reader.read_u16().await?.into();
                 let message = reader.read_string().await?;
                 *self = TransparentMessage::Error(code, message);
             }
@@ -221,23 +316,55 @@ impl Encodable for ServerMessage {
             Self::NewMeeting {
                 owner_id,
                 slots,
+                invitations,
+                join_code,
                 data,
             } => {
                 writer.write_bytes(owner_id.as_ref()).await?;
                 writer.write_u32(slots.len() as u32).await?;
                 for slot in slots {
-                    writer.write_bytes(slot.as_ref()).await?;
+                    writer.write_bytes(slot.user_id.as_ref()).await?;
+                    encode_role(writer, &slot.role).await?;
+                    encode_verifying_key(writer, &slot.verifying_key)
+                        .await?;
+                }
+                writer.write_bool(*invitations).await?;
+                writer.write_bool(join_code.is_some()).await?;
+                if let Some(join_code) = join_code {
+                    writer.write_bytes(join_code.as_ref()).await?;
                 }
                 writer
                     .write_string(serde_json::to_string(&data)?)
                     .await?;
             }
-            Self::MeetingCreated(response) => {
+            Self::MeetingCreated(response, secret) => {
                 response.encode(writer).await?;
+                writer.write_bool(secret.join_code.is_some()).await?;
+                if let Some(join_code) = &secret.join_code {
+                    writer.write_bytes(join_code.as_ref()).await?;
+                }
+                writer
+                    .write_u16(secret.invitations.len() as u16)
+                    .await?;
+                for invitation in &secret.invitations {
+                    writer
+                        .write_bytes(invitation.user_id.as_ref())
+                        .await?;
+                    writer
+                        .write_bytes(invitation.join_code.as_ref())
+                        .await?;
+                }
             }
-            Self::JoinMeeting(meeting_id, user_id) => {
+            Self::JoinMeeting(
+                meeting_id,
+                user_id,
+                join_code,
+                signature,
+            ) => {
                 writer.write_bytes(meeting_id.as_bytes()).await?;
                 writer.write_bytes(user_id.as_ref()).await?;
+                writer.write_bytes(join_code.as_ref()).await?;
+                encode_signature(writer, signature).await?;
             }
             Self::MeetingReady(response) => {
                 response.encode(writer).await?;
@@ -270,6 +397,49 @@ impl Encodable for ServerMessage {
             Self::SessionFinished(session_id) => {
                 writer.write_bytes(session_id.as_bytes()).await?;
             }
+            Self::ConnectionReplaced => {}
+            Self::LeaveMeeting(meeting_id)
+            | Self::CancelMeeting(meeting_id)
+            | Self::MeetingCancelled(meeting_id)
+            | Self::MeetingExpired(meeting_id) => {
+                writer.write_bytes(meeting_id.as_bytes()).await?;
+            }
+            Self::GetSession(session_id) => {
+                writer.write_bytes(session_id.as_bytes()).await?;
+            }
+            Self::SessionInfo(status) => {
+                status.encode(writer).await?;
+            }
+            Self::GetMeeting(meeting_id) => {
+                writer.write_bytes(meeting_id.as_bytes()).await?;
+            }
+            Self::MeetingInfo(response) => {
+                response.encode(writer).await?;
+            }
+            Self::ListSessions => {}
+            Self::SessionExpired(session_id) => {
+                writer.write_bytes(session_id.as_bytes()).await?;
+            }
+            Self::SessionExpiring(session_id, expires_in) => {
+                writer.write_bytes(session_id.as_bytes()).await?;
+                writer.write_u64(expires_in).await?;
+            }
+            Self::MeetingExpiring(meeting_id, expires_in) => {
+                writer.write_bytes(meeting_id.as_bytes()).await?;
+                writer.write_u64(expires_in).await?;
+            }
+            Self::SessionList(sessions) => {
+                writer.write_u16(sessions.len() as u16).await?;
+                for status in sessions {
+                    status.encode(writer).await?;
+                }
+            }
+            Self::SessionInvitation(response) => {
+                response.encode(writer).await?;
+            }
+            Self::JoinSession(session_id) => {
+                writer.write_bytes(session_id.as_bytes()).await?;
+            }
             Self::Noop => unreachable!(),
         }
         Ok(())
@@ -288,11 +458,7 @@ impl Decodable for ServerMessage {
         let id = reader.read_u8().await?;
         match id {
             types::ERROR => {
-                let code = reader
-                    .read_u16()
-                    .await?
-                    .try_into()
-                    .map_err(encoding_error)?;
+                let code = reader.read_u16().await?.into();
                 let message = reader.read_string().await?;
                 *self = ServerMessage::Error(code, message);
             }
@@ -300,27 +466,85 @@ impl Decodable for ServerMessage {
                 let owner_id: [u8; 32] =
                     reader.read_bytes(32).await?.try_into().unwrap();
 
-                let mut slots = HashSet::new();
+                let mut slots = Vec::new();
                 let num_slots = reader.read_u32().await?;
                 for _ in 0..num_slots {
-                    let slot: [u8; 32] = reader
+                    let user_id: [u8; 32] = reader
                         .read_bytes(32)
                         .await?
                         .try_into()
                         .unwrap();
-                    slots.insert(slot.into());
+                    let role = decode_role(reader).await?;
+                    let verifying_key =
+                        decode_verifying_key(reader).await?;
+                    slots.push(MeetingSlot {
+                        user_id: user_id.into(),
+                        role,
+                        verifying_key,
+                    });
                 }
+                let invitations = reader.read_bool().await?;
+                let has_join_code = reader.read_bool().await?;
+                let join_code = if has_join_code {
+                    let join_code: [u8; 32] = reader
+                        .read_bytes(32)
+                        .await?
+                        .try_into()
+                        .unwrap();
+                    Some(join_code.into())
+                } else {
+                    None
+                };
                 let data = reader.read_string().await?;
                 *self = ServerMessage::NewMeeting {
                     owner_id: owner_id.into(),
                     slots,
+                    invitations,
+                    join_code,
                     data: serde_json::from_str(&data)?,
                 };
             }
             types::MEETING_CREATED => {
                 let mut meeting: MeetingState = Default::default();
                 meeting.decode(reader).await?;
-                *self = ServerMessage::MeetingCreated(meeting);
+
+                let has_join_code = reader.read_bool().await?;
+                let join_code = if has_join_code {
+                    let join_code: [u8; 32] = reader
+                        .read_bytes(32)
+                        .await?
+                        .try_into()
+                        .unwrap();
+                    Some(join_code.into())
+                } else {
+                    None
+                };
+                let mut invitations = Vec::new();
+                let size = reader.read_u16().await?;
+                for _ in 0..size {
+                    let user_id: [u8; 32] = reader
+                        .read_bytes(32)
+                        .await?
+                        .try_into()
+                        .unwrap();
+                    let join_code: [u8; 32] = reader
+                        .read_bytes(32)
+                        .await?
+                        .try_into()
+                        .unwrap();
+                    invitations.push(MeetingInvitation {
+                        user_id: user_id.into(),
+                        join_code: join_code.into(),
+                    });
+                }
+
+                *self = ServerMessage::MeetingCreated(
+                    meeting,
+                    MeetingSecret {
+                        join_code,
+                        invitations,
+                    },
+                );
             }
             types::MEETING_JOIN => {
                 let meeting_id = MeetingId::from_bytes(
@@ -333,10 +557,15 @@ impl Decodable for ServerMessage {
                 );
                 let user_id: [u8; 32] =
                     reader.read_bytes(32).await?.try_into().unwrap();
+                let join_code: [u8; 32] =
+                    reader.read_bytes(32).await?.try_into().unwrap();
+                let signature = decode_signature(reader).await?;
 
                 *self = ServerMessage::JoinMeeting(
                     meeting_id,
                     user_id.into(),
+                    join_code.into(),
+                    signature,
                 );
             }
             types::MEETING_READY => {
@@ -413,6 +642,135 @@ impl Decodable for ServerMessage {
                 );
                 *self = ServerMessage::SessionFinished(session_id);
             }
+            types::CONNECTION_REPLACED => {
+                *self = ServerMessage::ConnectionReplaced;
+            }
+            types::MEETING_LEAVE
+            | types::MEETING_CANCEL
+            | types::MEETING_CANCELLED
+            | types::MEETING_EXPIRED => {
+                let meeting_id = MeetingId::from_bytes(
+                    reader
+                        .read_bytes(16)
+                        .await?
+                        .as_slice()
+                        .try_into()
+                        .map_err(encoding_error)?,
+                );
+                *self = match id {
+                    types::MEETING_LEAVE => {
+                        ServerMessage::LeaveMeeting(meeting_id)
+                    }
+                    types::MEETING_CANCEL => {
+                        ServerMessage::CancelMeeting(meeting_id)
+                    }
+                    types::MEETING_CANCELLED => {
+                        ServerMessage::MeetingCancelled(meeting_id)
+                    }
+                    _ => ServerMessage::MeetingExpired(meeting_id),
+                };
+            }
+            types::SESSION_GET => {
+                let session_id = SessionId::from_bytes(
+                    reader
+                        .read_bytes(16)
+                        .await?
+                        .as_slice()
+                        .try_into()
+                        .map_err(encoding_error)?,
+                );
+                *self = ServerMessage::GetSession(session_id);
+            }
+            types::SESSION_INFO => {
+                let mut status: SessionStatus = Default::default();
+                status.decode(reader).await?;
+                *self = ServerMessage::SessionInfo(status);
+            }
+            types::MEETING_GET => {
+                let meeting_id = MeetingId::from_bytes(
+                    reader
+                        .read_bytes(16)
+                        .await?
+                        .as_slice()
+                        .try_into()
+                        .map_err(encoding_error)?,
+                );
+                *self = ServerMessage::GetMeeting(meeting_id);
+            }
+            types::MEETING_INFO => {
+                let mut meeting: MeetingState = Default::default();
+                meeting.decode(reader).await?;
+                *self = ServerMessage::MeetingInfo(meeting);
+            }
+            types::SESSION_LIST => {
+                *self = ServerMessage::ListSessions;
+            }
+            types::SESSION_EXPIRED => {
+                let session_id = SessionId::from_bytes(
+                    reader
+                        .read_bytes(16)
+                        .await?
+                        .as_slice()
+                        .try_into()
+                        .map_err(encoding_error)?,
+                );
+                *self = ServerMessage::SessionExpired(session_id);
+            }
+            types::SESSION_EXPIRING => {
+                let session_id = SessionId::from_bytes(
+                    reader
+                        .read_bytes(16)
+                        .await?
+                        .as_slice()
+                        .try_into()
+                        .map_err(encoding_error)?,
+                );
+                let expires_in = reader.read_u64().await?;
+                *self = ServerMessage::SessionExpiring(
+                    session_id, expires_in,
+                );
+            }
+            types::MEETING_EXPIRING => {
+                let meeting_id = MeetingId::from_bytes(
+                    reader
+                        .read_bytes(16)
+                        .await?
+                        .as_slice()
+                        .try_into()
+                        .map_err(encoding_error)?,
+                );
+                let expires_in = reader.read_u64().await?;
+                *self = ServerMessage::MeetingExpiring(
+                    meeting_id, expires_in,
+                );
+            }
+            types::SESSION_LIST_RESPONSE => {
+                let mut sessions = Vec::new();
+                let size = reader.read_u16().await? as usize;
+                for _ in 0..size {
+                    let mut status: SessionStatus =
+                        Default::default();
+                    status.decode(reader).await?;
+                    sessions.push(status);
+                }
+                *self = ServerMessage::SessionList(sessions);
+            }
+            types::SESSION_INVITATION => {
+                let mut session: SessionState = Default::default();
+                session.decode(reader).await?;
+                *self = ServerMessage::SessionInvitation(session);
+            }
+            types::SESSION_JOIN => {
+                let session_id = SessionId::from_bytes(
+                    reader
+                        .read_bytes(16)
+                        .await?
+                        .as_slice()
+                        .try_into()
+                        .map_err(encoding_error)?,
+                );
+                *self = ServerMessage::JoinSession(session_id);
+            }
             _ => {
                 return Err(encoding_error(
                     crate::Error::EncodingKind(id),
@@ -710,6 +1068,12 @@ impl Encodable for SessionRequest {
         for key in self.participant_keys.iter() {
             encode_buffer(writer, key).await?;
         }
+        writer.write_bool(self.quorum.is_some()).await?;
+        if let Some(quorum) = &self.quorum {
+            writer.write_u16(quorum.min_participants).await?;
+            writer.write_u64(quorum.deadline).await?;
+        }
+        self.limits.encode(writer).await?;
         Ok(())
     }
 }
@@ -725,6 +1089,14 @@ impl Decodable for SessionRequest {
             let key = decode_buffer(reader).await?;
             self.participant_keys.push(key);
         }
+        let has_quorum = reader.read_bool().await?;
+        if has_quorum {
+            self.quorum = Some(SessionQuorum {
+                min_participants: reader.read_u16().await?,
+                deadline: reader.read_u64().await?,
+            });
+        }
+        self.limits.decode(reader).await?;
         Ok(())
     }
 }
@@ -739,8 +1111,14 @@ impl Encodable for MeetingState {
         writer
             .write_u16(self.registered_participants.len() as u16)
             .await?;
-        for key in &self.registered_participants {
-            encode_buffer(writer, key).await?;
+        for participant in &self.registered_participants {
+            writer.write_bytes(participant.user_id.as_ref()).await?;
+            encode_buffer(writer, &participant.public_key).await?;
+            writer.write_u16(participant.slot).await?;
+            encode_role(writer, &participant.role).await?;
+            encode_verifying_key(writer, &participant.verifying_key)
+                .await?;
+            encode_signature(writer, &participant.signature).await?;
         }
         writer
             .write_string(serde_json::to_string(&self.data)?)
@@ -765,8 +1143,21 @@ impl Decodable for MeetingState {
         );
         let size = reader.read_u16().await? as usize;
         for _ in 0..size {
-            let key = decode_buffer(reader).await?;
-            self.registered_participants.push(key);
+            let user_id: [u8; 32] =
+                reader.read_bytes(32).await?.try_into().unwrap();
+            let public_key = decode_buffer(reader).await?;
+            let slot = reader.read_u16().await?;
+            let role = decode_role(reader).await?;
+            let verifying_key = decode_verifying_key(reader).await?;
+            let signature = decode_signature(reader).await?;
+            self.registered_participants.push(MeetingParticipant {
+                user_id: user_id.into(),
+                public_key,
+                slot,
+                role,
+                verifying_key,
+                signature,
+            });
         }
 
         let data = reader.read_string().await?;
@@ -787,6 +1178,13 @@ impl Encodable for SessionState {
         for key in &self.all_participants {
             encode_buffer(writer, key).await?;
         }
+        writer
+            .write_u16(self.active_participants.len() as u16)
+            .await?;
+        for key in &self.active_participants {
+            encode_buffer(writer, key).await?;
+        }
+        self.limits.encode(writer).await?;
         Ok(())
     }
 }
@@ -810,6 +1208,100 @@ impl Decodable for SessionState {
             let key = decode_buffer(reader).await?;
             self.all_participants.push(key);
         }
+        let size = reader.read_u16().await? as usize;
+        for _ in 0..size {
+            let key = decode_buffer(reader).await?;
+            self.active_participants.push(key);
+        }
+        self.limits.decode(reader).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Encodable for SessionStatus {
+    async fn encode<W: AsyncWrite + AsyncSeek + Unpin + Send>(
+        &self,
+        writer: &mut BinaryWriter<W>,
+    ) -> Result<()> {
+        self.state.encode(writer).await?;
+        writer.write_u16(self.connected.len() as u16).await?;
+        for key in &self.connected {
+            encode_buffer(writer, key).await?;
+        }
+        writer.write_u32(self.connections.len() as u32).await?;
+        for (peer, other) in &self.connections {
+            encode_buffer(writer, peer).await?;
+            encode_buffer(writer, other).await?;
+        }
+        writer.write_u64(self.expires_in).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Decodable for SessionStatus {
+    async fn decode<R: AsyncRead + AsyncSeek + Unpin + Send>(
+        &mut self,
+        reader: &mut BinaryReader<R>,
+    ) -> Result<()> {
+        self.state.decode(reader).await?;
+        let size = reader.read_u16().await? as usize;
+        for _ in 0..size {
+            let key = decode_buffer(reader).await?;
+            self.connected.push(key);
+        }
+        let size = reader.read_u32().await? as usize;
+        for _ in 0..size {
+            let peer = decode_buffer(reader).await?;
+            let other = decode_buffer(reader).await?;
+            self.connections.push((peer, other));
+        }
+        self.expires_in = reader.read_u64().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Encodable for SessionLimits {
+    async fn encode<W: AsyncWrite + AsyncSeek + Unpin + Send>(
+        &self,
+        writer: &mut BinaryWriter<W>,
+    ) -> Result<()> {
+        for value in [
+            self.timeout,
+            self.wait_timeout,
+            self.max_message_size,
+            self.max_lifetime,
+        ] {
+            writer.write_bool(value.is_some()).await?;
+            if let Some(value) = value {
+                writer.write_u64(value).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Decodable for SessionLimits {
+    async fn decode<R: AsyncRead + AsyncSeek + Unpin + Send>(
+        &mut self,
+        reader: &mut BinaryReader<R>,
+    ) -> Result<()> {
+        for value in [
+            &mut self.timeout,
+            &mut self.wait_timeout,
+            &mut self.max_message_size,
+            &mut self.max_lifetime,
+        ] {
+            let has_value = reader.read_bool().await?;
+            *value = if has_value {
+                Some(reader.read_u64().await?)
+            } else {
+                None
+            };
+        }
         Ok(())
     }
 }
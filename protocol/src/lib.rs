@@ -24,6 +24,7 @@ pub use error::Error;
 pub use keypair::*;
 pub use protocol::*;
 
+pub use ed25519_dalek;
 pub use hex;
 pub use http;
 pub use log;
@@ -1,12 +1,16 @@
 use crate::{encoding::types, PartyNumber, Result, TAGLEN};
-use http::StatusCode;
+use ed25519_dalek::{Signature, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use snow::{HandshakeState, TransportState};
 use std::{
-    collections::{HashMap, HashSet},
-    time::{Duration, SystemTime},
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    fmt,
+    hash::Hash,
+    time::{Duration, Instant},
 };
+use subtle::ConstantTimeEq;
 
 /// Identifier for meeting points.
 pub type MeetingId = uuid::Uuid;
@@ -16,8 +20,10 @@ pub type SessionId = uuid::Uuid;
 
 /// User identifier wraps an SHA-256 hash of a
 /// unique arbitrary value.
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
-pub struct UserId([u8; 32]);
+#[derive(
+    Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize,
+)]
+pub struct UserId(#[serde(with = "hex::serde")] [u8; 32]);
 
 impl AsRef<[u8; 32]> for UserId {
     fn as_ref(&self) -> &[u8; 32] {
@@ -31,6 +37,37 @@ impl From<[u8; 32]> for UserId {
     }
 }
 
+/// Secret code used to join a meeting point.
+///
+/// Comparison is constant time and the debug output
+/// is redacted so codes do not leak into logs.
+#[derive(Clone, Eq, Serialize, Deserialize)]
+pub struct JoinCode(#[serde(with = "hex::serde")] [u8; 32]);
+
+impl AsRef<[u8; 32]> for JoinCode {
+    fn as_ref(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl From<[u8; 32]> for JoinCode {
+    fn from(value: [u8; 32]) -> Self {
+        Self(value)
+    }
+}
+
+impl PartialEq for JoinCode {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
+
+impl fmt::Debug for JoinCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("JoinCode(..)")
+    }
+}
+
 /// Parameters used during key generation.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct Parameters {
@@ -52,6 +89,78 @@ impl Default for Parameters {
     }
 }
 
+/// Error codes sent by the server.
+///
+/// The numeric values are part of the wire protocol and
+/// must not be changed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ErrorCode {
+    /// Internal server error.
+    Internal,
+    /// Request was invalid, eg: a handshake message
+    /// when not in the handshake state.
+    BadRequest,
+    /// Permission denied.
+    PermissionDenied,
+    /// Too many requests.
+    RateLimited,
+    /// Target peer is not connected to the server.
+    PeerOffline,
+    /// Another connection is active for the public key.
+    DuplicateConnection,
+    /// Session does not exist.
+    SessionNotFound,
+    /// Peer is not a participant in the session.
+    NotSessionParticipant,
+    /// Meeting does not exist.
+    MeetingNotFound,
+    /// Meeting has no available slots.
+    MeetingFull,
+    /// Peer has not joined the meeting.
+    NotMeetingParticipant,
+    /// Error code not known to this version of the
+    /// protocol, the message is still available.
+    Unknown(u16),
+}
+
+impl From<ErrorCode> for u16 {
+    fn from(value: ErrorCode) -> Self {
+        match value {
+            ErrorCode::Internal => 1,
+            ErrorCode::BadRequest => 2,
+            ErrorCode::PermissionDenied => 3,
+            ErrorCode::RateLimited => 4,
+            ErrorCode::PeerOffline => 5,
+            ErrorCode::DuplicateConnection => 6,
+            ErrorCode::SessionNotFound => 7,
+            ErrorCode::NotSessionParticipant => 8,
+            ErrorCode::MeetingNotFound => 9,
+            ErrorCode::MeetingFull => 10,
+            ErrorCode::NotMeetingParticipant => 11,
+            ErrorCode::Unknown(code) => code,
+        }
+    }
+}
+
+impl From<u16> for ErrorCode {
+    fn from(value: u16) -> Self {
+        match value {
+            1 => ErrorCode::Internal,
+            2 => ErrorCode::BadRequest,
+            3 => ErrorCode::PermissionDenied,
+            4 => ErrorCode::RateLimited,
+            5 => ErrorCode::PeerOffline,
+            6 => ErrorCode::DuplicateConnection,
+            7 => ErrorCode::SessionNotFound,
+            8 => ErrorCode::NotSessionParticipant,
+            9 => ErrorCode::MeetingNotFound,
+            10 => ErrorCode::MeetingFull,
+            11 => ErrorCode::NotMeetingParticipant,
+            _ => ErrorCode::Unknown(value),
+        }
+    }
+}
+
 /// Enumeration of protocol states.
 pub enum ProtocolState {
     /// Noise handshake state.
@@ -93,7 +202,7 @@ pub enum TransparentMessage {
     #[doc(hidden)]
     Noop,
     /// Return an error message to the client.
-    Error(StatusCode, String),
+    Error(ErrorCode, String),
     /// Handshake message.
     ServerHandshake(HandshakeMessage),
     /// Relayed peer handshake message.
@@ -127,22 +236,42 @@ pub enum ServerMessage {
     #[doc(hidden)]
     Noop,
     /// Return an error message to the client.
-    Error(StatusCode, String),
+    Error(ErrorCode, String),
     /// Request a new meeting point.
     NewMeeting {
         /// The identifier for the owner of the meeting point.
         ///
-        /// The owner id must exist in the set of slots.
+        /// The owner id must exist in the list of slots.
         owner_id: UserId,
         /// Slots for participants in the meeting.
-        slots: HashSet<UserId>,
+        ///
+        /// The order of the slots determines the order
+        /// of the participants when the meeting is ready.
+        slots: Vec<MeetingSlot>,
+        /// Create a one-time invitation code for each slot.
+        ///
+        /// Meetings with invitations do not have a join code
+        /// shared by all participants.
+        invitations: bool,
+        /// Join code chosen by the owner.
+        ///
+        /// When not given the server generates a random join code;
+        /// must not be given when invitations are enabled.
+        join_code: Option<JoinCode>,
         /// Data associated aith the meeting point.
         data: Value,
     },
     /// Response to a new meeting point request.
-    MeetingCreated(MeetingState),
-    /// Participant joins a meeting.
-    JoinMeeting(MeetingId, UserId),
+    ///
+    /// Only the owner of the meeting receives the secrets.
+    MeetingCreated(MeetingState, MeetingSecret),
+    /// Participant joins a meeting using the join code
+    /// for the meeting or an invitation code for the slot.
+    ///
+    /// When the slot is bound to a verifying key the join
+    /// request must include a signature over the message
+    /// returned by [join_meeting_message].
+    JoinMeeting(MeetingId, UserId, JoinCode, Option<Signature>),
     /// Notification dispatched to all participants
     /// in a meeting when the limit for the meeting
     /// has been reached.
@@ -159,8 +288,12 @@ pub enum ServerMessage {
     /// Response to a new session request.
     SessionCreated(SessionState),
     /// Notification dispatched to all participants
-    /// in a session when they have all completed
-    /// the server handshake.
+    /// in a session when they have all joined the
+    /// session.
+    ///
+    /// When the session proceeds with a quorum the
+    /// connected participants that are not taking part
+    /// are also sent the state for the session.
     SessionReady(SessionState),
     /// Notification dispatched to all participants
     /// in a session when they have all established
@@ -174,6 +307,69 @@ pub enum ServerMessage {
     CloseSession(SessionId),
     /// Message sent when a session was closed.
     SessionFinished(SessionId),
+    /// Notification dispatched to a client when another
+    /// connection using the same public key has replaced
+    /// this connection.
+    ///
+    /// The server closes the socket after sending this message.
+    ConnectionReplaced,
+    /// Participant leaves a meeting.
+    LeaveMeeting(MeetingId),
+    /// Request to cancel a meeting.
+    ///
+    /// Only the owner of a meeting may cancel it.
+    CancelMeeting(MeetingId),
+    /// Notification dispatched to all participants
+    /// in a meeting when the owner cancels the meeting.
+    MeetingCancelled(MeetingId),
+    /// Notification dispatched to all participants
+    /// in a meeting when the meeting expired before
+    /// all participants joined.
+    MeetingExpired(MeetingId),
+    /// Request the status of a session.
+    ///
+    /// Only session participants may query a session.
+    GetSession(SessionId),
+    /// Response to a session status request.
+    SessionInfo(SessionStatus),
+    /// Request the state of a meeting.
+    ///
+    /// Only meeting participants may query a meeting.
+    GetMeeting(MeetingId),
+    /// Response to a meeting state request.
+    MeetingInfo(MeetingState),
+    /// Request the status of all sessions the caller
+    /// participates in.
+    ListSessions,
+    /// Response to a list sessions request.
+    SessionList(Vec<SessionStatus>),
+    /// Notification dispatched to all participants
+    /// in a session when the session expired.
+    SessionExpired(SessionId),
+    /// Notification dispatched to all participants in
+    /// a session when the session is about to expire with
+    /// the number of seconds remaining.
+    ///
+    /// A session expires at the earlier of the idle timeout,
+    /// which any activity in the session resets, and the
+    /// maximum lifetime, which is an absolute limit from when
+    /// the session was created that activity never resets.
+    SessionExpiring(SessionId, u64),
+    /// Notification dispatched to all participants in
+    /// a meeting when the meeting is about to expire with
+    /// the number of seconds remaining.
+    MeetingExpiring(MeetingId, u64),
+    /// Notification dispatched to a connected participant
+    /// when it is invited to a session that is waiting for
+    /// participants to join.
+    SessionInvitation(SessionState),
+    /// Participant joins a session it was invited to.
+    ///
+    /// Only participants that have joined and are connected
+    /// count towards the session being ready; a participant
+    /// that joins once the session is ready is sent the
+    /// current state for the session.
+    JoinSession(SessionId),
 }
 
 impl From<&ServerMessage> for u8 {
@@ -182,10 +378,12 @@ impl From<&ServerMessage> for u8 {
             ServerMessage::Noop => types::NOOP,
             ServerMessage::Error(_, _) => types::ERROR,
             ServerMessage::NewMeeting { .. } => types::MEETING_NEW,
-            ServerMessage::MeetingCreated(_) => {
+            ServerMessage::MeetingCreated(_, _) => {
                 types::MEETING_CREATED
             }
-            ServerMessage::JoinMeeting(_, _) => types::MEETING_JOIN,
+            ServerMessage::JoinMeeting(_, _, _, _) => {
+                types::MEETING_JOIN
+            }
             ServerMessage::MeetingReady(_) => types::MEETING_READY,
             ServerMessage::NewSession(_) => types::SESSION_NEW,
             ServerMessage::SessionConnection { .. } => {
@@ -203,6 +401,38 @@ impl From<&ServerMessage> for u8 {
             ServerMessage::SessionFinished(_) => {
                 types::SESSION_FINISHED
             }
+            ServerMessage::ConnectionReplaced => {
+                types::CONNECTION_REPLACED
+            }
+            ServerMessage::LeaveMeeting(_) => types::MEETING_LEAVE,
+            ServerMessage::CancelMeeting(_) => types::MEETING_CANCEL,
+            ServerMessage::MeetingCancelled(_) => {
+                types::MEETING_CANCELLED
+            }
+            ServerMessage::MeetingExpired(_) => {
+                types::MEETING_EXPIRED
+            }
+            ServerMessage::GetSession(_) => types::SESSION_GET,
+            ServerMessage::SessionInfo(_) => types::SESSION_INFO,
+            ServerMessage::GetMeeting(_) => types::MEETING_GET,
+            ServerMessage::MeetingInfo(_) => types::MEETING_INFO,
+            ServerMessage::ListSessions => types::SESSION_LIST,
+            ServerMessage::SessionList(_) => {
+                types::SESSION_LIST_RESPONSE
+            }
+            ServerMessage::SessionExpired(_) => {
+                types::SESSION_EXPIRED
+            }
+            ServerMessage::SessionExpiring(_, _) => {
+                types::SESSION_EXPIRING
+            }
+            ServerMessage::MeetingExpiring(_, _) => {
+                types::MEETING_EXPIRING
+            }
+            ServerMessage::SessionInvitation(_) => {
+                types::SESSION_INVITATION
+            }
+            ServerMessage::JoinSession(_) => types::SESSION_JOIN,
         }
     }
 }
@@ -376,6 +606,18 @@ pub struct SealedEnvelope {
     pub broadcast: bool,
 }
 
+impl SealedEnvelope {
+    /// Length of the payload data in all the chunks.
+    pub fn len(&self) -> usize {
+        self.chunks.iter().map(|c| c.length).sum()
+    }
+
+    /// Determine if the payload is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 /// Session is a namespace for a group of participants
 /// to communicate for a series of rounds.
 ///
@@ -389,15 +631,38 @@ pub struct Session {
     owner_key: Vec<u8>,
 
     /// Public keys of the other session participants.
-    participant_keys: HashSet<Vec<u8>>,
+    participant_keys: Vec<Vec<u8>>,
 
     /// Connections between peers established in this
     /// session context.
     connections: HashSet<(Vec<u8>, Vec<u8>)>,
 
+    /// Participants taking part when the session
+    /// proceeds with a quorum.
+    active_keys: Option<Vec<Vec<u8>>>,
+
+    /// Participants that have joined this session,
+    /// the owner joins when the session is created.
+    joined: HashSet<Vec<u8>>,
+
+    /// Whether participants have been notified that
+    /// this session is ready.
+    ready: bool,
+
+    /// Limits for the session.
+    limits: SessionLimits,
+
+    /// Creation time so the server can enforce
+    /// the maximum lifetime.
+    created: Instant,
+
     /// Last access time so the server can reap
     /// stale sessions.
-    last_access: SystemTime,
+    last_access: Instant,
+
+    /// Deadline participants have been warned about
+    /// so each deadline is only warned once.
+    warned: Option<Instant>,
 }
 
 impl Session {
@@ -406,6 +671,75 @@ impl Session {
         self.owner_key.as_slice()
     }
 
+    /// Limits for this session.
+    pub fn limits(&self) -> &SessionLimits {
+        &self.limits
+    }
+
+    /// Deadline when this session expires.
+    ///
+    /// A session expires when it has not seen any message
+    /// activity for the session timeout (or the default
+    /// timeout when not set) or when it has exceeded the
+    /// maximum lifetime.
+    ///
+    /// Returns `None` when the deadline is too far in the
+    /// future to be represented.
+    pub fn deadline(&self, timeout: u64) -> Option<Instant> {
+        let timeout = self.limits.timeout.unwrap_or(timeout);
+        let idle = self
+            .last_access
+            .checked_add(Duration::from_secs(timeout));
+        let lifetime = self.limits.max_lifetime.and_then(|secs| {
+            self.created.checked_add(Duration::from_secs(secs))
+        });
+        match (idle, lifetime) {
+            (Some(idle), Some(lifetime)) => Some(idle.min(lifetime)),
+            (idle, lifetime) => idle.or(lifetime),
+        }
+    }
+
+    /// Determine if this session has expired.
+    pub fn is_expired(&self, timeout: u64) -> bool {
+        self.deadline(timeout)
+            .map(|deadline| deadline <= Instant::now())
+            .unwrap_or(false)
+    }
+
+    /// Seconds remaining before this session expires.
+    pub fn expires_in(&self, timeout: u64) -> u64 {
+        self.deadline(timeout)
+            .map(|deadline| {
+                deadline.saturating_duration_since(Instant::now())
+            })
+            .map(|d| d.as_secs())
+            .unwrap_or(u64::MAX)
+    }
+
+    /// State for this session.
+    pub fn state(&self, session_id: SessionId) -> SessionState {
+        let all_participants: Vec<Vec<u8>> = self
+            .public_keys()
+            .into_iter()
+            .map(|k| k.to_vec())
+            .collect();
+        let active_participants = self
+            .active_keys
+            .clone()
+            .unwrap_or_else(|| all_participants.clone());
+        SessionState {
+            session_id,
+            all_participants,
+            active_participants,
+            limits: self.limits,
+        }
+    }
+
+    /// Peer connections registered in this session.
+    pub fn connections(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        self.connections.iter().cloned().collect()
+    }
+
     /// Get all participant's public keys
     pub fn public_keys(&self) -> Vec<&[u8]> {
         let mut keys = vec![self.owner_key.as_slice()];
@@ -418,6 +752,50 @@ impl Session {
         keys
     }
 
+    /// Determine if a public key is a participant in this session.
+    ///
+    /// The session owner is also a participant.
+    pub fn is_participant(&self, public_key: &[u8]) -> bool {
+        self.owner_key == public_key
+            || self.participant_keys.iter().any(|k| k == public_key)
+    }
+
+    /// Set the participants taking part in this session.
+    ///
+    /// Only connections between these participants are
+    /// required for the session to become active.
+    pub fn set_active_participants(&mut self, keys: Vec<Vec<u8>>) {
+        self.active_keys = Some(keys);
+    }
+
+    /// Record that a participant has joined this session.
+    ///
+    /// Returns whether the public key is a participant.
+    pub fn join(&mut self, public_key: &[u8]) -> bool {
+        if self.is_participant(public_key) {
+            self.joined.insert(public_key.to_vec());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Determine if a participant has joined this session.
+    pub fn is_joined(&self, public_key: &[u8]) -> bool {
+        self.joined.contains(public_key)
+    }
+
+    /// Mark this session as ready.
+    pub fn set_ready(&mut self) {
+        self.ready = true;
+    }
+
+    /// Whether participants have been notified that this
+    /// session is ready.
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
     /// Register a connection between peers.
     pub fn register_connection(
         &mut self,
@@ -432,7 +810,11 @@ impl Session {
     /// A session is active when all participants have created
     /// their peer connections.
     pub fn is_active(&self) -> bool {
-        let all_participants = self.public_keys();
+        let all_participants = if let Some(keys) = &self.active_keys {
+            keys.iter().map(|k| k.as_slice()).collect()
+        } else {
+            self.public_keys()
+        };
 
         fn check_connection(
             connections: &HashSet<(Vec<u8>, Vec<u8>)>,
@@ -475,35 +857,193 @@ impl Session {
 /// Meeting point information.
 #[derive(Debug)]
 pub struct Meeting {
-    /// Map of user identifiers to public keys.
-    slots: HashMap<UserId, Option<Vec<u8>>>,
+    /// Public key of the owner.
+    owner_key: Vec<u8>,
+
+    /// Secrets required to join the meeting.
+    secret: MeetingSecret,
+
+    /// Slots in declaration order with the participant
+    /// that has joined each slot.
+    slots: Vec<(MeetingSlot, Option<SlotParticipant>)>,
+
+    /// Public keys that have used the invitation for
+    /// each slot.
+    redeemed: HashMap<UserId, Vec<u8>>,
 
     /// Last access time so the server can reap
     /// stale meetings.
-    last_access: SystemTime,
+    last_access: Instant,
+
+    /// Deadline participants have been warned about
+    /// so each deadline is only warned once.
+    warned: Option<Instant>,
 
     /// Associated data for the meeting.
     data: Value,
 }
 
 impl Meeting {
+    /// Public key of the meeting owner.
+    pub fn owner_key(&self) -> &[u8] {
+        self.owner_key.as_slice()
+    }
+
+    /// Verify a join code for a user.
+    ///
+    /// The code may be the join code for the meeting or the
+    /// invitation code for the slot; once an invitation has
+    /// been used only the same public key may join the slot
+    /// with the invitation code.
+    pub fn verify_join_code(
+        &self,
+        user_id: &UserId,
+        join_code: &JoinCode,
+        public_key: &[u8],
+    ) -> bool {
+        if self.secret.join_code.as_ref() == Some(join_code) {
+            return true;
+        }
+
+        let redeemed_by_other = matches!(
+            self.redeemed.get(user_id),
+            Some(key) if key.as_slice() != public_key
+        );
+        !redeemed_by_other
+            && self.secret.invitations.iter().any(|i| {
+                &i.user_id == user_id && &i.join_code == join_code
+            })
+    }
+
+    /// Determine if a public key may fill the slot for a user.
+    ///
+    /// Returns `None` when there is no slot for the user
+    /// identifier; a slot is only available when it is empty
+    /// or already filled by the same public key.
+    pub fn is_slot_available(
+        &self,
+        user_id: &UserId,
+        public_key: &[u8],
+    ) -> Option<bool> {
+        self.slots.iter().find(|(s, _)| &s.user_id == user_id).map(
+            |(_, participant)| match participant {
+                Some(p) => p.public_key.as_slice() == public_key,
+                None => true,
+            },
+        )
+    }
+
+    /// Verify the signature for a user joining this meeting.
+    ///
+    /// Slots that are not bound to a verifying key do not
+    /// require a signature.
+    pub fn verify_signature(
+        &self,
+        meeting_id: &MeetingId,
+        user_id: &UserId,
+        public_key: &[u8],
+        signature: Option<&Signature>,
+    ) -> bool {
+        let slot =
+            self.slots.iter().find(|(s, _)| &s.user_id == user_id);
+        match slot.and_then(|(s, _)| s.verifying_key.as_ref()) {
+            Some(verifying_key) => signature
+                .map(|signature| {
+                    let message =
+                        join_meeting_message(meeting_id, public_key);
+                    verifying_key
+                        .verify_strict(&message, signature)
+                        .is_ok()
+                })
+                .unwrap_or(false),
+            None => true,
+        }
+    }
+
     /// Add a participant public key to this meeting.
-    pub fn join(&mut self, user_id: UserId, public_key: Vec<u8>) {
-        self.slots.insert(user_id, Some(public_key));
-        self.last_access = SystemTime::now();
+    ///
+    /// The invitation for the slot is bound to the public
+    /// key once joined so the participant may leave and join
+    /// the slot again.
+    ///
+    /// Returns whether a slot exists for the user identifier.
+    fn join(
+        &mut self,
+        user_id: &UserId,
+        public_key: Vec<u8>,
+        signature: Option<Signature>,
+    ) -> bool {
+        if let Some((_, participant)) =
+            self.slots.iter_mut().find(|(s, _)| &s.user_id == user_id)
+        {
+            if self
+                .secret
+                .invitations
+                .iter()
+                .any(|i| &i.user_id == user_id)
+            {
+                self.redeemed
+                    .entry(user_id.clone())
+                    .or_insert_with(|| public_key.clone());
+            }
+            *participant = Some(SlotParticipant {
+                public_key,
+                signature,
+            });
+            self.touch();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Remove a participant public key from this meeting.
+    ///
+    /// Returns whether the public key was a participant.
+    fn leave(&mut self, public_key: &[u8]) -> bool {
+        let mut removed = false;
+        for (_, slot) in self.slots.iter_mut() {
+            if slot.as_ref().map(|p| &p.public_key[..])
+                == Some(public_key)
+            {
+                *slot = None;
+                removed = true;
+            }
+        }
+        self.touch();
+        removed
     }
 
     /// Whether this meeting point is full.
     pub fn is_full(&self) -> bool {
-        self.slots.values().all(|s| s.is_some())
+        self.slots.iter().all(|(_, s)| s.is_some())
     }
 
-    /// Public keys of the meeting participants.
+    /// Public keys of the meeting participants in slot order.
     pub fn participants(&self) -> Vec<Vec<u8>> {
         self.slots
-            .values()
-            .filter(|s| s.is_some())
-            .map(|s| s.as_ref().unwrap().to_owned())
+            .iter()
+            .filter_map(|(_, s)| {
+                s.as_ref().map(|p| p.public_key.clone())
+            })
+            .collect()
+    }
+
+    /// Participants that have joined this meeting in slot order.
+    pub fn registered_participants(&self) -> Vec<MeetingParticipant> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (slot, participant))| {
+                participant.as_ref().map(|p| MeetingParticipant {
+                    user_id: slot.user_id.clone(),
+                    public_key: p.public_key.clone(),
+                    slot: index as u16,
+                    role: slot.role.clone(),
+                    verifying_key: slot.verifying_key,
+                    signature: p.signature,
+                })
+            })
             .collect()
     }
 
@@ -511,36 +1051,348 @@ impl Meeting {
     pub fn data(&self) -> &Value {
         &self.data
     }
+
+    /// Seconds remaining before this meeting expires.
+    pub fn expires_in(&self, timeout: u64) -> u64 {
+        self.deadline(timeout)
+            .map(|deadline| {
+                deadline.saturating_duration_since(Instant::now())
+            })
+            .map(|d| d.as_secs())
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Deadline when this meeting expires.
+    ///
+    /// A meeting expires when there has been no activity
+    /// for the timeout.
+    pub fn deadline(&self, timeout: u64) -> Option<Instant> {
+        self.last_access.checked_add(Duration::from_secs(timeout))
+    }
+
+    /// Update the last access time.
+    fn touch(&mut self) {
+        self.last_access = Instant::now();
+    }
+
+    /// State for this meeting.
+    pub fn state(&self, meeting_id: MeetingId) -> MeetingState {
+        MeetingState {
+            meeting_id,
+            registered_participants: self.registered_participants(),
+            data: self.data.clone(),
+        }
+    }
+
+    /// Determine if a public key has joined this meeting.
+    pub fn is_participant(&self, public_key: &[u8]) -> bool {
+        self.slots.iter().any(|(_, s)| {
+            s.as_ref().map(|p| &p.public_key[..]) == Some(public_key)
+        })
+    }
+}
+
+/// Participant that has filled a meeting slot.
+#[derive(Debug)]
+struct SlotParticipant {
+    /// Noise public key of the participant.
+    public_key: Vec<u8>,
+    /// Signature provided when joining the slot.
+    signature: Option<Signature>,
+}
+
+/// Message signed by a participant joining a meeting slot
+/// that is bound to a verifying key.
+///
+/// The message binds the meeting identifier to the noise
+/// public key of the participant.
+pub fn join_meeting_message(
+    meeting_id: &MeetingId,
+    public_key: &[u8],
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(
+        MEETING_JOIN_CONTEXT.len() + 16 + public_key.len(),
+    );
+    message.extend_from_slice(MEETING_JOIN_CONTEXT);
+    message.extend_from_slice(meeting_id.as_bytes());
+    message.extend_from_slice(public_key);
+    message
+}
+
+/// Context for signatures when joining a meeting.
+const MEETING_JOIN_CONTEXT: &[u8] = b"mpc-meeting-join";
+
+/// Queue of keys ordered by the earliest deadline.
+///
+/// Entries are not updated when a deadline moves later;
+/// instead an entry is rescheduled when it is popped
+/// before the current deadline for the key.
+struct Deadlines<K: Ord> {
+    queue: BinaryHeap<Reverse<(Instant, K)>>,
+}
+
+impl<K: Ord> Default for Deadlines<K> {
+    fn default() -> Self {
+        Self {
+            queue: BinaryHeap::new(),
+        }
+    }
+}
+
+impl<K: Ord + Copy> Deadlines<K> {
+    /// Schedule a deadline for a key.
+    fn schedule(&mut self, deadline: Instant, key: K) {
+        self.queue.push(Reverse((deadline, key)));
+    }
+
+    /// Earliest scheduled deadline.
+    fn next(&self) -> Option<Instant> {
+        self.queue.peek().map(|Reverse((deadline, _))| *deadline)
+    }
+
+    /// Pop a key whose scheduled deadline has passed.
+    fn pop_due(&mut self, now: Instant) -> Option<K> {
+        if self.next()? <= now {
+            self.queue.pop().map(|Reverse((_, key))| key)
+        } else {
+            None
+        }
+    }
+
+    /// Pop the keys that are due and reschedule those
+    /// whose current deadline is later.
+    ///
+    /// The current deadline for a key is determined by the
+    /// closure which returns `None` when the key has been
+    /// removed or will never expire.
+    fn expired<F>(&mut self, deadline: F) -> Vec<K>
+    where
+        F: FnMut(&K) -> Option<Instant>,
+    {
+        self.due(Instant::now(), deadline)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    /// Pop the keys with a current deadline before the horizon
+    /// and reschedule those whose current deadline is later.
+    ///
+    /// Returns the keys that are due with the current deadline.
+    fn due<F>(
+        &mut self,
+        horizon: Instant,
+        mut deadline: F,
+    ) -> Vec<(K, Instant)>
+    where
+        F: FnMut(&K) -> Option<Instant>,
+    {
+        let mut due = Vec::new();
+        let mut rescheduled = Vec::new();
+        while let Some(key) = self.pop_due(horizon) {
+            match deadline(&key) {
+                Some(current) if current <= horizon => {
+                    due.push((key, current))
+                }
+                Some(current) => rescheduled.push((current, key)),
+                None => {}
+            }
+        }
+        for (current, key) in rescheduled {
+            self.schedule(current, key);
+        }
+        due
+    }
+
+    /// Pop the keys with a current deadline within the warning
+    /// period.
+    ///
+    /// Keys are checked again once the deadline has passed so
+    /// a deadline that moves later is warned again.
+    fn expiring<F>(
+        &mut self,
+        warning: Duration,
+        deadline: F,
+    ) -> Vec<(K, Instant)>
+    where
+        F: FnMut(&K) -> Option<Instant>,
+    {
+        let now = Instant::now();
+        let horizon = now.checked_add(warning).unwrap_or(now);
+        let due = self.due(horizon, deadline);
+        for (key, current) in &due {
+            if let Some(next) = current.checked_add(warning) {
+                self.schedule(next, *key);
+            }
+        }
+        due
+    }
+
+    /// Earliest time the keys must be checked for the
+    /// warning period.
+    fn next_warning(&self, warning: Duration) -> Option<Instant> {
+        self.next()
+            .map(|next| next.checked_sub(warning).unwrap_or(next))
+    }
+}
+
+/// Index of the keys for the sessions or meetings that
+/// each public key participates in.
+struct ParticipantIndex<K> {
+    keys: HashMap<Vec<u8>, HashSet<K>>,
+}
+
+impl<K> Default for ParticipantIndex<K> {
+    fn default() -> Self {
+        Self {
+            keys: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash + Copy> ParticipantIndex<K> {
+    /// Replace the public keys indexed for a key.
+    fn update(
+        &mut self,
+        key: K,
+        previous: Vec<Vec<u8>>,
+        current: Vec<Vec<u8>>,
+    ) {
+        for public_key in previous {
+            if let Some(keys) = self.keys.get_mut(&public_key) {
+                keys.remove(&key);
+                if keys.is_empty() {
+                    self.keys.remove(&public_key);
+                }
+            }
+        }
+        for public_key in current {
+            self.keys.entry(public_key).or_default().insert(key);
+        }
+    }
+
+    /// Keys that a public key participates in.
+    fn get(&self, public_key: &[u8]) -> impl Iterator<Item = &K> {
+        self.keys.get(public_key).into_iter().flatten()
+    }
+
+    /// Determine if two public keys participate in the same key.
+    fn shares(&self, a: &[u8], b: &[u8]) -> bool {
+        match (self.keys.get(a), self.keys.get(b)) {
+            (Some(a), Some(b)) => !a.is_disjoint(b),
+            _ => false,
+        }
+    }
 }
 
 /// Manages a collection of meeting points.
 #[derive(Default)]
 pub struct MeetingManager {
     meetings: HashMap<MeetingId, Meeting>,
+    participants: ParticipantIndex<MeetingId>,
+    deadlines: Deadlines<MeetingId>,
+    warnings: Deadlines<MeetingId>,
 }
 
 impl MeetingManager {
     /// Create a new meeting point.
+    ///
+    /// Returns `None` if the slots contain duplicate user
+    /// identifiers or the owner does not have a slot; the owner
+    /// joins without a signature so the owner slot must not be
+    /// bound to a verifying key.
     pub fn new_meeting(
         &mut self,
         owner_key: Vec<u8>,
         owner_id: UserId,
-        slots: HashSet<UserId>,
+        slots: Vec<MeetingSlot>,
+        secret: MeetingSecret,
         data: Value,
-    ) -> MeetingId {
+    ) -> Option<MeetingId> {
+        let user_ids: HashSet<&UserId> =
+            slots.iter().map(|s| &s.user_id).collect();
+        if user_ids.len() != slots.len()
+            || !slots.iter().any(|s| {
+                s.user_id == owner_id && s.verifying_key.is_none()
+            })
+        {
+            return None;
+        }
+
         let meeting_id = MeetingId::new_v4();
-        let slots: HashMap<UserId, Option<Vec<u8>>> =
-            slots.into_iter().map(|id| (id, None)).collect();
+        let slots: Vec<(MeetingSlot, Option<SlotParticipant>)> =
+            slots.into_iter().map(|slot| (slot, None)).collect();
 
         let mut meeting = Meeting {
+            owner_key: owner_key.clone(),
+            secret,
             slots,
-            last_access: SystemTime::now(),
+            redeemed: Default::default(),
+            last_access: Instant::now(),
+            warned: None,
             data,
         };
-        meeting.join(owner_id, owner_key);
-
+        meeting.join(&owner_id, owner_key, None);
+
+        // Check immediately so the deadline is
+        // scheduled using the current timeout
+        self.deadlines.schedule(meeting.last_access, meeting_id);
+        self.warnings.schedule(meeting.last_access, meeting_id);
+        self.participants.update(
+            meeting_id,
+            vec![],
+            meeting.participants(),
+        );
         self.meetings.insert(meeting_id, meeting);
-        meeting_id
+        Some(meeting_id)
+    }
+
+    /// Add a participant public key to a meeting.
+    ///
+    /// Returns whether the meeting has a slot for the
+    /// user identifier.
+    pub fn join_meeting(
+        &mut self,
+        id: &MeetingId,
+        user_id: &UserId,
+        public_key: Vec<u8>,
+        signature: Option<Signature>,
+    ) -> bool {
+        if let Some(meeting) = self.meetings.get_mut(id) {
+            let previous = meeting.participants();
+            let joined = meeting.join(user_id, public_key, signature);
+            self.participants.update(
+                *id,
+                previous,
+                meeting.participants(),
+            );
+            joined
+        } else {
+            false
+        }
+    }
+
+    /// Remove a participant public key from a meeting.
+    ///
+    /// Returns whether the public key was a participant.
+    pub fn leave_meeting(
+        &mut self,
+        id: &MeetingId,
+        public_key: &[u8],
+    ) -> bool {
+        if let Some(meeting) = self.meetings.get_mut(id) {
+            let left = meeting.leave(public_key);
+            if left {
+                self.participants.update(
+                    *id,
+                    vec![public_key.to_vec()],
+                    vec![],
+                );
+            }
+            left
+        } else {
+            false
+        }
     }
 
     /// Remove a meeting.
@@ -548,7 +1400,10 @@ impl MeetingManager {
         &mut self,
         id: &MeetingId,
     ) -> Option<Meeting> {
-        self.meetings.remove(id)
+        let meeting = self.meetings.remove(id)?;
+        self.participants
+            .update(*id, meeting.participants(), vec![]);
+        Some(meeting)
     }
 
     /// Get a meeting.
@@ -556,6 +1411,11 @@ impl MeetingManager {
         self.meetings.get(id)
     }
 
+    /// Determine if two public keys have joined the same meeting.
+    pub fn shares_meeting(&self, a: &[u8], b: &[u8]) -> bool {
+        self.participants.shares(a, b)
+    }
+
     /// Get a mutable meeting.
     pub fn get_meeting_mut(
         &mut self,
@@ -564,22 +1424,55 @@ impl MeetingManager {
         self.meetings.get_mut(id)
     }
 
-    /// Get the keys of meetings that have expired.
-    pub fn expired_keys(&self, timeout: u64) -> Vec<MeetingId> {
-        self.meetings
-            .iter()
-            .filter(|(_, v)| {
-                let now = SystemTime::now();
-                let ttl = Duration::from_millis(timeout * 1000);
-                if let Some(current) = v.last_access.checked_add(ttl)
-                {
-                    current < now
-                } else {
-                    false
+    /// Get the keys of meetings that expire within the
+    /// warning period and the seconds remaining.
+    ///
+    /// Each deadline for a meeting is only returned once.
+    pub fn expiring_keys(
+        &mut self,
+        timeout: u64,
+        warning: u64,
+    ) -> Vec<(MeetingId, u64)> {
+        let meetings = &mut self.meetings;
+        let expiring = self
+            .warnings
+            .expiring(Duration::from_secs(warning), |key| {
+                meetings.get(key).and_then(|m| m.deadline(timeout))
+            });
+        expiring
+            .into_iter()
+            .filter_map(|(key, deadline)| {
+                let meeting = meetings.get_mut(&key)?;
+                if meeting.warned == Some(deadline) {
+                    return None;
                 }
+                meeting.warned = Some(deadline);
+                Some((key, meeting.expires_in(timeout)))
             })
-            .map(|(k, _)| *k)
-            .collect::<Vec<_>>()
+            .collect()
+    }
+
+    /// Earliest deadline for a meeting to be checked for expiry.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.deadlines.next()
+    }
+
+    /// Earliest time for meetings to be checked for the
+    /// expiry warning period.
+    pub fn next_warning(&self, warning: u64) -> Option<Instant> {
+        self.warnings.next_warning(Duration::from_secs(warning))
+    }
+
+    /// Get the keys of meetings that have expired.
+    ///
+    /// Only meetings with a scheduled deadline that has
+    /// passed are inspected; callers should remove the
+    /// expired meetings as they are no longer scheduled.
+    pub fn expired_keys(&mut self, timeout: u64) -> Vec<MeetingId> {
+        let meetings = &self.meetings;
+        self.deadlines.expired(|key| {
+            meetings.get(key).and_then(|m| m.deadline(timeout))
+        })
     }
 }
 
@@ -587,6 +1480,10 @@ impl MeetingManager {
 #[derive(Default)]
 pub struct SessionManager {
     sessions: HashMap<SessionId, Session>,
+    participants: ParticipantIndex<SessionId>,
+    owners: HashMap<Vec<u8>, usize>,
+    deadlines: Deadlines<SessionId>,
+    warnings: Deadlines<SessionId>,
 }
 
 impl SessionManager {
@@ -595,14 +1492,37 @@ impl SessionManager {
         &mut self,
         owner_key: Vec<u8>,
         participant_keys: Vec<Vec<u8>>,
+        limits: SessionLimits,
     ) -> SessionId {
         let session_id = SessionId::new_v4();
+        let now = Instant::now();
+        let joined = HashSet::from([owner_key.clone()]);
+        *self.owners.entry(owner_key.clone()).or_default() += 1;
         let session = Session {
             owner_key,
-            participant_keys: participant_keys.into_iter().collect(),
+            participant_keys,
             connections: Default::default(),
-            last_access: SystemTime::now(),
+            active_keys: None,
+            joined,
+            ready: false,
+            limits,
+            created: now,
+            last_access: now,
+            warned: None,
         };
+        // Check immediately so the deadline is
+        // scheduled using the current timeout
+        self.deadlines.schedule(now, session_id);
+        self.warnings.schedule(now, session_id);
+        self.participants.update(
+            session_id,
+            vec![],
+            session
+                .public_keys()
+                .into_iter()
+                .map(Vec::from)
+                .collect(),
+        );
         self.sessions.insert(session_id, session);
         session_id
     }
@@ -612,6 +1532,17 @@ impl SessionManager {
         self.sessions.get(id)
     }
 
+    /// Number of sessions owned by a public key.
+    pub fn owned_sessions(&self, owner_key: &[u8]) -> usize {
+        self.owners.get(owner_key).copied().unwrap_or_default()
+    }
+
+    /// Determine if two public keys are participants
+    /// in the same session.
+    pub fn shares_session(&self, a: &[u8], b: &[u8]) -> bool {
+        self.participants.shares(a, b)
+    }
+
     /// Get a mutable session.
     pub fn get_session_mut(
         &mut self,
@@ -625,7 +1556,24 @@ impl SessionManager {
         &mut self,
         id: &SessionId,
     ) -> Option<Session> {
-        self.sessions.remove(id)
+        let session = self.sessions.remove(id)?;
+        if let Some(owned) = self.owners.get_mut(session.owner_key())
+        {
+            *owned -= 1;
+            if *owned == 0 {
+                self.owners.remove(session.owner_key());
+            }
+        }
+        self.participants.update(
+            *id,
+            session
+                .public_keys()
+                .into_iter()
+                .map(Vec::from)
+                .collect(),
+            vec![],
+        );
+        Some(session)
     }
 
     /// Retrieve and update the last access time for a session.
@@ -634,29 +1582,195 @@ impl SessionManager {
         id: &SessionId,
     ) -> Option<&Session> {
         if let Some(session) = self.sessions.get_mut(id) {
-            session.last_access = SystemTime::now();
+            session.last_access = Instant::now();
             Some(&*session)
         } else {
             None
         }
     }
 
-    /// Get the keys of sessions that have expired.
-    pub fn expired_keys(&self, timeout: u64) -> Vec<SessionId> {
-        self.sessions
-            .iter()
-            .filter(|(_, v)| {
-                let now = SystemTime::now();
-                let ttl = Duration::from_millis(timeout * 1000);
-                if let Some(current) = v.last_access.checked_add(ttl)
-                {
-                    current < now
-                } else {
-                    false
+    /// Get the keys of sessions that expire within the
+    /// warning period and the seconds remaining.
+    ///
+    /// Each deadline for a session is only returned once so
+    /// the maximum lifetime is not warned again after more
+    /// activity for the session.
+    pub fn expiring_keys(
+        &mut self,
+        timeout: u64,
+        warning: u64,
+    ) -> Vec<(SessionId, u64)> {
+        let sessions = &mut self.sessions;
+        let expiring = self
+            .warnings
+            .expiring(Duration::from_secs(warning), |key| {
+                sessions.get(key).and_then(|s| s.deadline(timeout))
+            });
+        expiring
+            .into_iter()
+            .filter_map(|(key, deadline)| {
+                let session = sessions.get_mut(&key)?;
+                if session.warned == Some(deadline) {
+                    return None;
                 }
+                session.warned = Some(deadline);
+                Some((key, session.expires_in(timeout)))
             })
-            .map(|(k, _)| *k)
-            .collect::<Vec<_>>()
+            .collect()
+    }
+
+    /// Sessions that a public key participates in.
+    pub fn participant_sessions(
+        &self,
+        public_key: &[u8],
+    ) -> Vec<(SessionId, &Session)> {
+        self.participants
+            .get(public_key)
+            .filter_map(|k| self.sessions.get(k).map(|s| (*k, s)))
+            .collect()
+    }
+
+    /// Earliest deadline for a session to be checked for expiry.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.deadlines.next()
+    }
+
+    /// Earliest time for sessions to be checked for the
+    /// expiry warning period.
+    pub fn next_warning(&self, warning: u64) -> Option<Instant> {
+        self.warnings.next_warning(Duration::from_secs(warning))
+    }
+
+    /// Get the keys of sessions that have expired.
+    ///
+    /// The timeout is used for sessions that do not
+    /// have a timeout limit.
+    ///
+    /// Only sessions with a scheduled deadline that has
+    /// passed are inspected; callers should remove the
+    /// expired sessions as they are no longer scheduled.
+    pub fn expired_keys(&mut self, timeout: u64) -> Vec<SessionId> {
+        let sessions = &self.sessions;
+        self.deadlines.expired(|key| {
+            sessions.get(key).and_then(|s| s.deadline(timeout))
+        })
+    }
+}
+
+/// Slot declared by the creator of a meeting point.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MeetingSlot {
+    /// User identifier for the slot.
+    pub user_id: UserId,
+    /// Optional role for the participant.
+    pub role: Option<String>,
+    /// Verifying key that binds the slot to an identity.
+    ///
+    /// Participants joining the slot must sign the join
+    /// request with the corresponding signing key. The owner
+    /// slot is filled when the meeting is created so it is
+    /// never signed.
+    pub verifying_key: Option<VerifyingKey>,
+}
+
+impl From<UserId> for MeetingSlot {
+    fn from(user_id: UserId) -> Self {
+        Self {
+            user_id,
+            role: None,
+            verifying_key: None,
+        }
+    }
+}
+
+/// Secrets for a meeting point returned to the owner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingSecret {
+    /// Join code shared with all participants.
+    ///
+    /// Not issued when the meeting uses invitations.
+    pub join_code: Option<JoinCode>,
+    /// One-time invitation codes for each slot.
+    pub invitations: Vec<MeetingInvitation>,
+}
+
+/// One-time invitation to join a meeting slot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingInvitation {
+    /// User identifier for the slot.
+    pub user_id: UserId,
+    /// Invitation code for the slot.
+    pub join_code: JoinCode,
+}
+
+/// Participant that has joined a meeting point.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MeetingParticipant {
+    /// User identifier of the participant.
+    pub user_id: UserId,
+    /// Public key of the participant.
+    pub public_key: Vec<u8>,
+    /// Index of the slot declared by the meeting creator.
+    pub slot: u16,
+    /// Role assigned to the slot.
+    pub role: Option<String>,
+    /// Verifying key bound to the slot.
+    pub verifying_key: Option<VerifyingKey>,
+    /// Signature provided by the participant when joining.
+    pub signature: Option<Signature>,
+}
+
+impl MeetingParticipant {
+    /// Verify the participant filled the slot with the signing
+    /// key for the verifying key bound to the slot.
+    ///
+    /// The verifying key is supplied by the relay so callers
+    /// that do not trust the relay should use
+    /// [MeetingParticipant::verify_slot] with the slot they
+    /// expect.
+    ///
+    /// Returns `true` when the slot is not bound to a
+    /// verifying key.
+    pub fn verify(&self, meeting_id: &MeetingId) -> bool {
+        self.verify_key(meeting_id, self.verifying_key.as_ref())
+    }
+
+    /// Verify the participant filled an expected slot.
+    ///
+    /// The user identifier must match the slot and when the
+    /// slot is bound to a verifying key the signature must be
+    /// valid for that key, the verifying key returned by the
+    /// relay is ignored.
+    pub fn verify_slot(
+        &self,
+        meeting_id: &MeetingId,
+        slot: &MeetingSlot,
+    ) -> bool {
+        self.user_id == slot.user_id
+            && self
+                .verify_key(meeting_id, slot.verifying_key.as_ref())
+    }
+
+    fn verify_key(
+        &self,
+        meeting_id: &MeetingId,
+        verifying_key: Option<&VerifyingKey>,
+    ) -> bool {
+        match (verifying_key, &self.signature) {
+            (Some(verifying_key), Some(signature)) => {
+                let message = join_meeting_message(
+                    meeting_id,
+                    &self.public_key,
+                );
+                verifying_key
+                    .verify_strict(&message, signature)
+                    .is_ok()
+            }
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
     }
 }
 
@@ -665,8 +1779,8 @@ impl SessionManager {
 pub struct MeetingState {
     /// Meeting identifier.
     pub meeting_id: MeetingId,
-    /// Public keys of the registered participants.
-    pub registered_participants: Vec<Vec<u8>>,
+    /// Participants that have joined in slot order.
+    pub registered_participants: Vec<MeetingParticipant>,
     /// Data for the meeting state.
     pub data: Value,
 }
@@ -679,6 +1793,63 @@ pub struct MeetingState {
 pub struct SessionRequest {
     /// Public keys of the session participants.
     pub participant_keys: Vec<Vec<u8>>,
+    /// Quorum that allows the session to proceed
+    /// without all the participants.
+    pub quorum: Option<SessionQuorum>,
+    /// Limits requested for the session.
+    pub limits: SessionLimits,
+}
+
+/// Limits for a session.
+///
+/// Values requested by a client are clamped to the server
+/// maximums; the session state returned by the server
+/// contains the effective limits with every value set.
+#[derive(
+    Default, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLimits {
+    /// Seconds without message activity before the
+    /// session expires.
+    pub timeout: Option<u64>,
+    /// Seconds to wait for the session to become
+    /// ready and active.
+    pub wait_timeout: Option<u64>,
+    /// Maximum size in bytes of a message relayed in the
+    /// session context.
+    pub max_message_size: Option<u64>,
+    /// Maximum lifetime of the session in seconds.
+    pub max_lifetime: Option<u64>,
+}
+
+/// Quorum for a session.
+///
+/// When not all participants have joined the session by
+/// the deadline the session proceeds with the connected
+/// participants that have joined as long as there are at
+/// least the minimum number of participants.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionQuorum {
+    /// Minimum number of participants including the owner.
+    pub min_participants: u16,
+    /// Seconds to wait for all participants before
+    /// proceeding with the quorum.
+    pub deadline: u64,
+}
+
+/// Status of a session returned when querying the server.
+#[derive(Default, Debug, Clone)]
+pub struct SessionStatus {
+    /// Session state.
+    pub state: SessionState,
+    /// Public keys of the participants connected to the server.
+    pub connected: Vec<Vec<u8>>,
+    /// Peer connections registered in the session.
+    pub connections: Vec<(Vec<u8>, Vec<u8>)>,
+    /// Seconds remaining before the session expires.
+    pub expires_in: u64,
 }
 
 /// Response from creating new session.
@@ -688,12 +1859,22 @@ pub struct SessionState {
     pub session_id: SessionId,
     /// Public keys of all participants.
     pub all_participants: Vec<Vec<u8>>,
+    /// Public keys of the participants taking part in
+    /// the session.
+    ///
+    /// Party numbers, connections and recipients are
+    /// derived from these participants which are a subset
+    /// of all participants when the session proceeds with
+    /// a quorum.
+    pub active_participants: Vec<Vec<u8>>,
+    /// Effective limits for the session.
+    pub limits: SessionLimits,
 }
 
 impl SessionState {
-    /// Total number of participants in this session.
+    /// Total number of participants taking part in this session.
     pub fn len(&self) -> usize {
-        self.all_participants.len()
+        self.active_participants.len()
     }
 
     /// Get the party index from a public key.
@@ -701,7 +1882,7 @@ impl SessionState {
         &self,
         public_key: impl AsRef<[u8]>,
     ) -> Option<PartyNumber> {
-        self.all_participants
+        self.active_participants
             .iter()
             .position(|k| k == public_key.as_ref())
             .map(|pos| PartyNumber::new((pos + 1) as u16).unwrap())
@@ -712,7 +1893,9 @@ impl SessionState {
         &self,
         party_number: PartyNumber,
     ) -> Option<&[u8]> {
-        for (index, key) in self.all_participants.iter().enumerate() {
+        for (index, key) in
+            self.active_participants.iter().enumerate()
+        {
             if index + 1 == party_number.get() as usize {
                 return Some(key.as_slice());
             }
@@ -722,15 +1905,15 @@ impl SessionState {
 
     /// Get the connections a peer should make.
     pub fn connections(&self, own_key: &[u8]) -> &[Vec<u8>] {
-        if self.all_participants.is_empty() {
+        if self.active_participants.is_empty() {
             return &[];
         }
 
         if let Some(position) =
-            self.all_participants.iter().position(|k| k == own_key)
+            self.active_participants.iter().position(|k| k == own_key)
         {
-            if position < self.all_participants.len() - 1 {
-                &self.all_participants[position + 1..]
+            if position < self.active_participants.len() - 1 {
+                &self.active_participants[position + 1..]
             } else {
                 &[]
             }
@@ -741,7 +1924,7 @@ impl SessionState {
 
     /// Get the recipients for a broadcast message.
     pub fn recipients(&self, own_key: &[u8]) -> Vec<Vec<u8>> {
-        self.all_participants
+        self.active_participants
             .iter()
             .filter(|&k| k != own_key)
             .map(|k| k.to_vec())
@@ -751,9 +1934,13 @@ impl SessionState {
 
 #[cfg(test)]
 mod tests {
-    use super::Chunk;
+    use super::{
+        Chunk, Deadlines, ErrorCode, MeetingManager, MeetingSecret,
+        MeetingSlot, SessionLimits, SessionManager, UserId,
+    };
     use crate::PATTERN;
     use anyhow::Result;
+    use std::time::{Duration, Instant};
 
     #[test]
     fn chunks_split_join() -> Result<()> {
@@ -804,4 +1991,128 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn error_code_unknown() {
+        assert_eq!(ErrorCode::MeetingFull, ErrorCode::from(10u16));
+        assert_eq!(ErrorCode::Unknown(999), ErrorCode::from(999u16));
+        assert_eq!(999, u16::from(ErrorCode::Unknown(999)));
+    }
+
+    #[test]
+    fn deadlines_expire_and_reschedule() {
+        let now = Instant::now();
+        let later = now + Duration::from_secs(60);
+
+        let mut deadlines: Deadlines<u8> = Default::default();
+        deadlines.schedule(later, 3);
+        deadlines.schedule(now, 2);
+        deadlines.schedule(now, 1);
+        assert_eq!(Some(now), deadlines.next());
+
+        // Key 1 has expired, key 2 moved later
+        let expired = deadlines.expired(|key| match key {
+            1 => Some(now),
+            2 => Some(later),
+            _ => None,
+        });
+        assert_eq!(vec![1], expired);
+
+        // Only the rescheduled and pending keys remain
+        assert_eq!(Some(later), deadlines.next());
+        assert_eq!(2, deadlines.queue.len());
+    }
+
+    #[test]
+    fn deadlines_expiring_once() {
+        let now = Instant::now();
+        let soon = now + Duration::from_secs(30);
+        let later = now + Duration::from_secs(300);
+        let warning = Duration::from_secs(60);
+
+        let mut deadlines: Deadlines<u8> = Default::default();
+        deadlines.schedule(now, 1);
+        deadlines.schedule(now, 2);
+
+        // Only the key within the warning period is due
+        let expiring = deadlines.expiring(warning, |key| match key {
+            1 => Some(soon),
+            _ => Some(later),
+        });
+        assert_eq!(vec![(1, soon)], expiring);
+
+        // Key is checked again once the deadline has passed
+        let expiring = deadlines.expiring(warning, |_| Some(soon));
+        assert!(expiring.is_empty());
+        assert_eq!(Some(soon), deadlines.next_warning(warning));
+    }
+
+    #[test]
+    fn session_lifetime_warned_once() {
+        let limits = SessionLimits {
+            max_lifetime: Some(30),
+            ..Default::default()
+        };
+        let mut sessions: SessionManager = Default::default();
+        let id = sessions.new_session(vec![1], vec![vec![2]], limits);
+
+        let expiring = sessions.expiring_keys(300, 60);
+        assert_eq!(1, expiring.len());
+        assert_eq!(id, expiring[0].0);
+
+        // Activity does not move the lifetime deadline
+        sessions.touch_session(&id);
+        assert!(sessions.sessions[&id].warned.is_some());
+        assert!(sessions.expiring_keys(300, 60).is_empty());
+    }
+
+    #[test]
+    fn participant_index() {
+        let mut sessions: SessionManager = Default::default();
+        let id = sessions.new_session(
+            vec![1],
+            vec![vec![2]],
+            Default::default(),
+        );
+        assert!(sessions.shares_session(&[1], &[2]));
+        assert!(!sessions.shares_session(&[1], &[3]));
+        assert_eq!(1, sessions.participant_sessions(&[2]).len());
+        assert_eq!(1, sessions.owned_sessions(&[1]));
+        assert_eq!(0, sessions.owned_sessions(&[2]));
+
+        sessions.remove_session(&id);
+        assert!(!sessions.shares_session(&[1], &[2]));
+        assert!(sessions.participant_sessions(&[2]).is_empty());
+        assert_eq!(0, sessions.owned_sessions(&[1]));
+
+        let owner_id: UserId = [1; 32].into();
+        let user_id: UserId = [2; 32].into();
+        let mut meetings: MeetingManager = Default::default();
+        let id = meetings
+            .new_meeting(
+                vec![1],
+                owner_id.clone(),
+                vec![
+                    MeetingSlot::from(owner_id),
+                    MeetingSlot::from(user_id.clone()),
+                ],
+                MeetingSecret {
+                    join_code: None,
+                    invitations: vec![],
+                },
+                Default::default(),
+            )
+            .unwrap();
+        assert!(!meetings.shares_meeting(&[1], &[2]));
+
+        // Index follows participants joining and leaving
+        assert!(meetings.join_meeting(&id, &user_id, vec![2], None));
+        assert!(meetings.shares_meeting(&[1], &[2]));
+        assert!(meetings.leave_meeting(&id, &[2]));
+        assert!(!meetings.shares_meeting(&[1], &[2]));
+
+        assert!(meetings.join_meeting(&id, &user_id, vec![2], None));
+        meetings.remove_meeting(&id);
+        assert!(!meetings.shares_meeting(&[1], &[2]));
+    }
 }
@@ -0,0 +1,185 @@
+//! Bearer token authentication.
+use jsonwebtoken::{
+    decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey,
+    Validation,
+};
+use mpc_protocol::hex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::{Error, Result};
+
+/// Configuration for token authentication.
+///
+/// Exactly one of `secret` or `jwks` must be specified.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AuthConfig {
+    /// Shared secret for tokens signed using HMAC (HS256).
+    pub secret: Option<String>,
+
+    /// Path to a JSON Web Key Set used to verify tokens.
+    pub jwks: Option<PathBuf>,
+
+    /// Expected issuer of tokens.
+    pub issuer: Option<String>,
+
+    /// Expected audience of tokens.
+    pub audience: Option<String>,
+
+    /// Algorithm for tokens verified with a key from the key
+    /// set that does not declare an `alg`.
+    ///
+    /// Tokens whose header does not match the algorithm for
+    /// the key are rejected.
+    pub algorithm: Option<Algorithm>,
+
+    /// Key set loaded from the JWKS file.
+    #[serde(skip)]
+    pub(crate) keys: Option<JwkSet>,
+}
+
+/// Claims for an authentication token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// Subject that identifies the user.
+    pub sub: String,
+
+    /// Public key the token is bound to.
+    #[serde(with = "hex::serde")]
+    pub public_key: Vec<u8>,
+
+    /// Expiry time as seconds since the UNIX epoch.
+    pub exp: u64,
+
+    /// Maximum number of sessions the user may own
+    /// at the same time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_sessions: Option<usize>,
+}
+
+impl AuthConfig {
+    /// Verify a token and return the claims.
+    pub(crate) fn verify(&self, token: &str) -> Result<Claims> {
+        let (key, algorithm) = if let Some(secret) = &self.secret {
+            (
+                DecodingKey::from_secret(secret.as_bytes()),
+                Algorithm::HS256,
+            )
+        } else if let Some(keys) = &self.keys {
+            let header = decode_header(token)?;
+            let kid = header.kid.ok_or(Error::TokenKeyId)?;
+            let jwk = keys.find(&kid).ok_or_else(|| {
+                Error::TokenKeyNotFound(kid.clone())
+            })?;
+
+            // Never let the token choose the algorithm
+            let algorithm =
+                jwk.common.algorithm.or(self.algorithm).ok_or_else(
+                    || Error::TokenAlgorithm(kid.clone()),
+                )?;
+            if header.alg != algorithm {
+                return Err(Error::TokenAlgorithmMismatch(
+                    header.alg, algorithm,
+                ));
+            }
+            (DecodingKey::from_jwk(jwk)?, algorithm)
+        } else {
+            return Err(Error::AuthConfig);
+        };
+
+        let mut validation = Validation::new(algorithm);
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if let Some(audience) = &self.audience {
+            validation.set_audience(&[audience]);
+        }
+
+        let data = decode::<Claims>(token, &key, &validation)?;
+        Ok(data.claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuthConfig, Claims};
+    use crate::Error;
+    use anyhow::Result;
+    use jsonwebtoken::{
+        encode, jwk::JwkSet, Algorithm, EncodingKey, Header,
+    };
+
+    /// Length is a multiple of three so the key does not
+    /// need base64 padding.
+    const SECRET: &[u8] = b"auth-test-secret-24bytes";
+
+    /// Key set with a single HMAC key.
+    fn keys(alg: Option<&str>) -> Result<JwkSet> {
+        let alg = alg
+            .map(|alg| format!(r#","alg":"{}""#, alg))
+            .unwrap_or_default();
+        Ok(serde_json::from_str(&format!(
+            r#"{{"keys":[{{"kty":"oct","kid":"key"{},"k":"{}"}}]}}"#,
+            alg,
+            base64::Engine::encode(
+                &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+                SECRET,
+            ),
+        ))?)
+    }
+
+    fn token(alg: Algorithm) -> Result<String> {
+        let mut header = Header::new(alg);
+        header.kid = Some("key".to_owned());
+        let claims = Claims {
+            sub: "user".to_owned(),
+            public_key: vec![0; 32],
+            exp: u64::MAX / 2,
+            max_sessions: None,
+        };
+        Ok(encode(
+            &header,
+            &claims,
+            &EncodingKey::from_secret(SECRET),
+        )?)
+    }
+
+    #[test]
+    fn jwks_algorithm_from_key() -> Result<()> {
+        let auth = AuthConfig {
+            keys: Some(keys(Some("HS256"))?),
+            ..Default::default()
+        };
+        assert!(auth.verify(&token(Algorithm::HS256)?).is_ok());
+        assert!(matches!(
+            auth.verify(&token(Algorithm::HS384)?),
+            Err(Error::TokenAlgorithmMismatch(
+                Algorithm::HS384,
+                Algorithm::HS256
+            ))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn jwks_algorithm_from_config() -> Result<()> {
+        // Key without an algorithm requires the configuration
+        let mut auth = AuthConfig {
+            keys: Some(keys(None)?),
+            ..Default::default()
+        };
+        assert!(matches!(
+            auth.verify(&token(Algorithm::HS256)?),
+            Err(Error::TokenAlgorithm(_))
+        ));
+
+        auth.algorithm = Some(Algorithm::HS384);
+        assert!(auth.verify(&token(Algorithm::HS384)?).is_ok());
+        assert!(matches!(
+            auth.verify(&token(Algorithm::HS256)?),
+            Err(Error::TokenAlgorithmMismatch(_, _))
+        ));
+        Ok(())
+    }
+}
@@ -1,11 +1,15 @@
 //! Server configuration.
-use mpc_protocol::{decode_keypair, hex, Keypair};
+use ipnet::IpNet;
+use mpc_protocol::{decode_keypair, hex, Keypair, SessionLimits};
 use serde::{Deserialize, Serialize};
-use std::path::{Path, PathBuf};
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+};
 use tokio::fs;
 use url::Url;
 
-use crate::{Error, Result};
+use crate::{AuthConfig, Error, Result};
 
 /// Configuration for the web server.
 #[derive(Default, Serialize, Deserialize)]
@@ -20,9 +24,21 @@ pub struct ServerConfig {
     /// Settings for session management.
     pub session: SessionConfig,
 
+    /// Settings for websocket keep-alive.
+    pub heartbeat: HeartbeatConfig,
+
     /// Configuration for TLS encryption.
     pub tls: Option<TlsConfig>,
 
+    /// Additional addresses to listen on.
+    pub listeners: Vec<ListenerConfig>,
+
+    /// Listen on a Unix domain socket.
+    pub unix: Option<UnixSocketConfig>,
+
+    /// Listen for QUIC connections.
+    pub quic: Option<QuicConfig>,
+
     /// Allow access to clients with these
     /// public keys.
     pub allow: Option<Vec<AccessKey>>,
@@ -31,8 +47,69 @@ pub struct ServerConfig {
     /// public keys.
     pub deny: Option<Vec<AccessKey>>,
 
+    /// Allow access to clients connecting from these networks.
+    pub allow_networks: Option<Vec<IpNet>>,
+
+    /// Deny access to clients connecting from these networks.
+    pub deny_networks: Option<Vec<IpNet>>,
+
+    /// Networks of trusted proxies.
+    ///
+    /// When a connection is from a trusted proxy the client
+    /// address is read from the `X-Forwarded-For` header.
+    pub trusted_proxies: Option<Vec<IpNet>>,
+
+    /// Require clients to present a bearer token.
+    pub auth: Option<AuthConfig>,
+
     /// Configuration for CORS.
     pub cors: CorsConfig,
+
+    /// Disconnect active clients that are no longer
+    /// allowed access when the configuration is reloaded.
+    pub disconnect_denied: bool,
+
+    /// Policy for a connection that uses the same public key
+    /// as an existing active connection.
+    pub duplicate_connection: DuplicateConnectionPolicy,
+
+    /// Policy for relaying peer handshakes and messages.
+    pub relay: RelayPolicy,
+
+    /// Webhooks for meeting and session lifecycle events.
+    pub webhooks: Option<WebhookConfig>,
+}
+
+/// Policy that determines which peers may exchange
+/// handshakes and messages via the relay.
+///
+/// Defaults to `any` so existing configurations keep
+/// relaying between any connected peers, new deployments
+/// should set `relay = "participants"`.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RelayPolicy {
+    /// Relay between any connected peers.
+    #[default]
+    Any,
+    /// Only relay between peers that are participants in
+    /// a shared session or meeting; registering session
+    /// connections also requires the caller and the peer
+    /// to be session participants.
+    Participants,
+}
+
+/// Policy applied when a client connects with a public key
+/// that is already in use by an active connection.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DuplicateConnectionPolicy {
+    /// Reject the new connection.
+    Reject,
+    /// Replace the existing connection, the old connection
+    /// is sent a connection replaced message and closed.
+    #[default]
+    Replace,
 }
 
 impl ServerConfig {
@@ -55,6 +132,45 @@ impl ServerConfig {
             true
         }
     }
+
+    /// Determine if an address is allowed access.
+    pub fn is_allowed_network(&self, addr: &IpAddr) -> bool {
+        if let Some(deny) = &self.deny_networks {
+            if deny.iter().any(|n| n.contains(addr)) {
+                return false;
+            }
+        }
+
+        if let Some(allow) = &self.allow_networks {
+            allow.iter().any(|n| n.contains(addr))
+        } else {
+            true
+        }
+    }
+
+    /// Determine if any TLS configuration binds client
+    /// certificates to public keys.
+    pub fn is_public_key_bound(&self) -> bool {
+        let listener_tls = self
+            .listeners
+            .iter()
+            .filter_map(|listener| listener.tls.as_ref());
+        let quic_tls = self.quic.iter().map(|quic| &quic.tls);
+        self.tls
+            .iter()
+            .chain(listener_tls)
+            .chain(quic_tls)
+            .any(|tls| tls.bind_public_key)
+    }
+
+    /// Determine if an address belongs to a trusted proxy.
+    pub fn is_trusted_proxy(&self, addr: &IpAddr) -> bool {
+        if let Some(proxies) = &self.trusted_proxies {
+            proxies.iter().any(|n| n.contains(addr))
+        } else {
+            false
+        }
+    }
 }
 
 #[derive(Default, Serialize, Deserialize)]
@@ -65,16 +181,96 @@ pub struct AccessKey {
 
 /// Certificate and key for TLS.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct TlsConfig {
     /// Path to the certificate.
     pub cert: PathBuf,
     /// Path to the certificate key file.
     pub key: PathBuf,
+    /// Path to a bundle of CA certificates for verifying
+    /// client certificates.
+    ///
+    /// When set clients must present a certificate signed
+    /// by one of the certificate authorities.
+    pub client_ca: Option<PathBuf>,
+    /// Require the subject common name or a subject alternative
+    /// name of the client certificate to be the hex-encoded
+    /// public key of the client.
+    ///
+    /// Connections without a client certificate, such as those
+    /// on listeners without TLS or the Unix domain socket, are
+    /// refused while any TLS configuration binds public keys.
+    #[serde(default)]
+    pub bind_public_key: bool,
+}
+
+/// Additional address for the server to listen on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenerConfig {
+    /// Address to bind to.
+    pub bind: SocketAddr,
+    /// Configuration for TLS encryption on this listener.
+    pub tls: Option<TlsConfig>,
+    /// Routes served by this listener.
+    ///
+    /// When no routes are given all routes are served.
+    pub routes: Option<Vec<Route>>,
+}
+
+/// Route served by the relay.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Route {
+    /// Websocket endpoint for clients.
+    Websocket,
+    /// HTTP endpoints for clients that cannot use websockets.
+    Http,
+    /// Endpoint for the server public key.
+    PublicKey,
+}
+
+/// QUIC endpoint to listen on.
+///
+/// Clients send request messages and receive response messages
+/// on a bidirectional stream and send the messages relayed to
+/// peers on a unidirectional stream for each peer, the connection
+/// is always encrypted with TLS.
+///
+/// Messages relayed to a client are written on the bidirectional
+/// stream so head-of-line blocking is only avoided for the messages
+/// a client sends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuicConfig {
+    /// UDP address to bind to.
+    pub bind: SocketAddr,
+    /// Configuration for TLS encryption.
+    pub tls: TlsConfig,
+}
+
+/// Unix domain socket to listen on.
+///
+/// Connections on the socket are not encrypted with TLS and
+/// do not have a network address; access is controlled by the
+/// permissions for the socket file so the network access lists
+/// do not apply and `X-Forwarded-For` headers are ignored.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct UnixSocketConfig {
+    /// Path to the socket file.
+    ///
+    /// A socket file left behind by a previous run is removed.
+    pub path: PathBuf,
+    /// Permissions for the socket file (eg: `0o660`).
+    ///
+    /// The permissions are set before the socket is moved to
+    /// the path so the parent directory must be writable.
+    pub mode: Option<u32>,
 }
 
 /// Configuration for server sessions.
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
+#[serde(default, rename_all = "kebab-case")]
 pub struct SessionConfig {
     /// Timeout for sessions in seconds.
     ///
@@ -84,7 +280,12 @@ pub struct SessionConfig {
     /// Default is 5 minutes.
     pub timeout: u64,
 
-    /// Interval in seconds to reap expired sessions.
+    /// Maximum interval in seconds between checks for
+    /// expired sessions.
+    ///
+    /// Sessions and meetings are reaped close to their
+    /// deadline so this only bounds how long the purge
+    /// task sleeps when nothing is scheduled.
     ///
     /// Default is every 15 minutes.
     pub interval: u64,
@@ -104,6 +305,76 @@ pub struct SessionConfig {
     ///
     /// Default is 5 minutes.
     pub wait_timeout: u64,
+
+    /// Maximum timeout in seconds a client may request
+    /// for a session.
+    ///
+    /// Default is 1 hour.
+    pub max_timeout: u64,
+
+    /// Maximum wait timeout in seconds a client may request
+    /// for a session.
+    ///
+    /// Default is 1 hour.
+    pub max_wait_timeout: u64,
+
+    /// Maximum size in bytes of a message relayed in
+    /// the context of a session.
+    ///
+    /// Also applies to messages relayed without a session.
+    ///
+    /// Default is 16MiB.
+    pub max_message_size: u64,
+
+    /// Maximum lifetime of a session in seconds.
+    ///
+    /// Default is 24 hours.
+    pub max_lifetime: u64,
+
+    /// Seconds before a session or meeting expires to warn
+    /// the participants so they can refresh activity.
+    ///
+    /// Participants are warned once for each deadline, a
+    /// deadline that moves later after more activity is
+    /// warned again.
+    ///
+    /// Default is no warning.
+    pub expiry_warning: Option<u64>,
+}
+
+impl SessionConfig {
+    /// Effective limits for a session.
+    ///
+    /// Requested values are clamped to the maximums and
+    /// missing values use the server defaults.
+    pub fn limits(&self, requested: &SessionLimits) -> SessionLimits {
+        SessionLimits {
+            timeout: Some(
+                requested
+                    .timeout
+                    .unwrap_or(self.timeout)
+                    .min(self.max_timeout),
+            ),
+            wait_timeout: Some(
+                requested
+                    .wait_timeout
+                    .unwrap_or(self.wait_timeout)
+                    .min(self.max_wait_timeout),
+            ),
+            max_message_size: Some(
+                requested
+                    .max_message_size
+                    .unwrap_or(self.max_message_size)
+                    .min(self.max_message_size),
+            ),
+            max_lifetime: Some(
+                requested
+                    .max_lifetime
+                    .unwrap_or(self.max_lifetime)
+                    .min(self.max_lifetime),
+            ),
+        }
+    }
 }
 
 impl Default for SessionConfig {
@@ -113,6 +384,86 @@ impl Default for SessionConfig {
             interval: 900,
             wait_interval: 15,
             wait_timeout: 300,
+            max_timeout: 3600,
+            max_wait_timeout: 3600,
+            max_message_size: 16 * 1024 * 1024,
+            max_lifetime: 86400,
+            expiry_warning: None,
+        }
+    }
+}
+
+/// Webhooks that receive meeting and session lifecycle events.
+///
+/// Events are JSON objects sent in `POST` requests with an
+/// HMAC-SHA256 signature of the body in the `X-Relay-Signature`
+/// header. Events only contain identifiers, public keys and
+/// timestamps, never message payloads.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct WebhookConfig {
+    /// URLs that events are sent to.
+    pub urls: Vec<Url>,
+
+    /// Shared secret used to sign events.
+    pub secret: String,
+
+    /// Maximum number of events waiting to be sent,
+    /// applies to the main queue and the queue for each URL.
+    ///
+    /// Events are dropped when a queue is full.
+    ///
+    /// Default is 1024.
+    pub queue_size: usize,
+
+    /// Maximum number of attempts to send an event
+    /// to a URL.
+    ///
+    /// Default is 5.
+    pub max_attempts: u32,
+
+    /// Timeout in seconds for each request.
+    ///
+    /// Default is 10 seconds.
+    pub timeout: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            urls: Vec::new(),
+            secret: String::new(),
+            queue_size: 1024,
+            max_attempts: 5,
+            timeout: 10,
+        }
+    }
+}
+
+/// Configuration for websocket keep-alive.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct HeartbeatConfig {
+    /// Interval in seconds between pings sent to clients.
+    ///
+    /// Default is 30 seconds.
+    pub interval: u64,
+
+    /// Timeout in seconds for idle connections.
+    ///
+    /// Connections that have not received any message
+    /// (including pong replies) for this amount of time
+    /// are disconnected.
+    ///
+    /// Default is 90 seconds.
+    pub timeout: u64,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            interval: 30,
+            timeout: 90,
         }
     }
 }
@@ -129,8 +480,8 @@ impl ServerConfig {
         let contents = fs::read_to_string(path.as_ref()).await?;
         let mut config: ServerConfig = toml::from_str(&contents)?;
 
-        if config.session.interval <= config.session.timeout {
-            return Err(Error::SessionTimeoutConfig);
+        if config.session.interval == 0 {
+            return Err(Error::SessionIntervalConfig);
         }
 
         if config.session.wait_timeout <= config.session.wait_interval
@@ -138,6 +489,17 @@ impl ServerConfig {
             return Err(Error::SessionWaitConfig);
         }
 
+        if config.session.max_timeout < config.session.timeout
+            || config.session.max_wait_timeout
+                < config.session.wait_timeout
+        {
+            return Err(Error::SessionLimitsConfig);
+        }
+
+        if config.heartbeat.timeout <= config.heartbeat.interval {
+            return Err(Error::HeartbeatConfig);
+        }
+
         if config.key == PathBuf::default() {
             return Err(Error::KeyFileRequired);
         }
@@ -155,13 +517,61 @@ impl ServerConfig {
         let contents = fs::read_to_string(&config.key).await?;
         let keypair = decode_keypair(contents)?;
 
-        if let Some(tls) = config.tls.as_mut() {
+        if let Some(auth) = config.auth.as_mut() {
+            match (&auth.secret, &mut auth.jwks) {
+                (Some(_), None) => {}
+                (None, Some(jwks)) => {
+                    if jwks.is_relative() {
+                        *jwks = dir.join(&*jwks).canonicalize()?;
+                    }
+                    let contents = fs::read_to_string(&*jwks).await?;
+                    auth.keys =
+                        Some(serde_json::from_str(&contents)?);
+                }
+                _ => return Err(Error::AuthConfig),
+            }
+        }
+
+        if let Some(webhooks) = &config.webhooks {
+            if webhooks.secret.is_empty() {
+                return Err(Error::WebhookSecret);
+            }
+            if let Some(url) = webhooks
+                .urls
+                .iter()
+                .find(|url| url.scheme() != "http")
+            {
+                return Err(Error::WebhookScheme(url.clone()));
+            }
+        }
+
+        if let Some(unix) = config.unix.as_mut() {
+            if unix.path.is_relative() {
+                unix.path = dir.join(&unix.path);
+            }
+        }
+
+        let listener_tls = config
+            .listeners
+            .iter_mut()
+            .filter_map(|listener| listener.tls.as_mut());
+        let quic_tls =
+            config.quic.iter_mut().map(|quic| &mut quic.tls);
+        for tls in
+            config.tls.iter_mut().chain(listener_tls).chain(quic_tls)
+        {
             if tls.cert.is_relative() {
                 tls.cert = dir.join(&tls.cert).canonicalize()?;
             }
             if tls.key.is_relative() {
                 tls.key = dir.join(&tls.key).canonicalize()?;
             }
+            if let Some(client_ca) = tls.client_ca.as_mut() {
+                if client_ca.is_relative() {
+                    *client_ca =
+                        dir.join(&*client_ca).canonicalize()?;
+                }
+            }
         }
 
         Ok((config, keypair))
@@ -182,3 +592,109 @@ pub struct CorsConfig {
     /// List of additional CORS origins for the server.
     pub origins: Vec<Url>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{RelayPolicy, ServerConfig};
+    use crate::Error;
+    use anyhow::Result;
+    use mpc_protocol::{encode_keypair, generate_keypair};
+    use std::net::IpAddr;
+
+    /// Load a configuration file with the test key.
+    async fn load(
+        name: &str,
+        contents: &str,
+    ) -> Result<crate::Result<ServerConfig>> {
+        let dir = std::env::temp_dir().join(format!(
+            "mpc-relay-config-{}-{}",
+            std::process::id(),
+            name,
+        ));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("key.pem"),
+            encode_keypair(&generate_keypair()?),
+        )?;
+        let file = dir.join("config.toml");
+        std::fs::write(
+            &file,
+            format!("key = \"key.pem\"\n{}", contents),
+        )?;
+        let result = ServerConfig::load(&file).await;
+        std::fs::remove_dir_all(&dir)?;
+        Ok(result.map(|(config, _)| config))
+    }
+
+    #[tokio::test]
+    async fn load_session_interval() -> Result<()> {
+        // Interval only bounds the sleep between deadlines
+        // so it may be less than the session timeout
+        let config = load(
+            "interval",
+            "[session]\ntimeout = 300\ninterval = 60\n",
+        )
+        .await??;
+        assert_eq!(60, config.session.interval);
+        assert_eq!(300, config.session.timeout);
+
+        let result =
+            load("interval-zero", "[session]\ninterval = 0\n")
+                .await?;
+        assert!(matches!(result, Err(Error::SessionIntervalConfig)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn load_heartbeat_defaults() -> Result<()> {
+        // Config files without a heartbeat table are accepted
+        let config = load("heartbeat", "").await??;
+        assert_eq!(30, config.heartbeat.interval);
+        assert_eq!(90, config.heartbeat.timeout);
+
+        // Missing heartbeat fields use the defaults
+        let config =
+            load("heartbeat-partial", "[heartbeat]\ninterval = 10\n")
+                .await??;
+        assert_eq!(10, config.heartbeat.interval);
+        assert_eq!(90, config.heartbeat.timeout);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn load_relay_policy() -> Result<()> {
+        // Any connected peers are relayed unless opted in
+        let config = load("relay", "").await??;
+        assert!(matches!(config.relay, RelayPolicy::Any));
+
+        let config =
+            load("relay-participants", "relay = \"participants\"\n")
+                .await??;
+        assert!(matches!(config.relay, RelayPolicy::Participants));
+        Ok(())
+    }
+
+    #[test]
+    fn allowed_network_precedence() -> Result<()> {
+        let mut config = ServerConfig::default();
+        let addr: IpAddr = "10.1.2.3".parse()?;
+        let other: IpAddr = "192.168.1.1".parse()?;
+        assert!(config.is_allowed_network(&addr));
+
+        // Only allowed networks have access
+        config.allow_networks = Some(vec!["10.0.0.0/8".parse()?]);
+        assert!(config.is_allowed_network(&addr));
+        assert!(!config.is_allowed_network(&other));
+
+        // Deny takes precedence over allow
+        config.deny_networks = Some(vec!["10.1.0.0/16".parse()?]);
+        assert!(!config.is_allowed_network(&addr));
+        assert!(config.is_allowed_network(&"10.2.0.1".parse()?));
+
+        // Without an allow list everything not denied has access
+        config.allow_networks = None;
+        assert!(config.is_allowed_network(&other));
+        assert!(!config.is_allowed_network(&addr));
+        Ok(())
+    }
+}
@@ -1,7 +1,8 @@
-use axum::extract::ws::Message;
-use mpc_protocol::{MeetingId, SessionId};
+use axum::{extract::ws::Message, http::StatusCode};
+use mpc_protocol::{ErrorCode, MeetingId, SessionId};
 use std::path::PathBuf;
 use thiserror::Error;
+use url::Url;
 
 /// Errors generated by the relay server.
 #[derive(Debug, Error)]
@@ -27,6 +28,12 @@ pub enum Error {
     #[error("peer already exists")]
     PeerAlreadyExists,
 
+    /// Error generated when a client connects with a public key
+    /// that is already in use and the duplicate connection
+    /// policy is to reject the new connection.
+    #[error(r#"connection for "{0}" already exists"#)]
+    DuplicateConnection(String),
+
     /// Error generated when a peer could not be found.
     #[error(r#"peer "{0}" not found "#)]
     PeerNotFound(String),
@@ -45,9 +52,41 @@ pub enum Error {
     MeetingNotFound(MeetingId),
 
     /// Error generated when a participant attempts to join a meeting
-    /// that is full.
-    #[error(r#"meeting "{0}" is full"#)]
-    MeetingFull(MeetingId),
+    /// slot that another participant has already joined.
+    #[error(r#"meeting "{0}" slot has already been joined"#)]
+    MeetingSlotFilled(MeetingId),
+
+    /// Error generated when a new meeting with invitations
+    /// also has a join code.
+    #[error("meeting with invitations must not have a join code")]
+    MeetingJoinCode,
+
+    /// Error generated when the slots for a new meeting contain
+    /// duplicate user identifiers, do not include the owner or
+    /// bind the owner slot to a verifying key.
+    #[error("meeting slots must be unique and include the owner without a verifying key")]
+    MeetingSlots,
+
+    /// Error generated when a participant attempts to join a meeting
+    /// with a join code that does not match the meeting secret or an
+    /// invitation for the slot.
+    #[error(r#"invalid join code for meeting "{0}""#)]
+    InvalidJoinCode(MeetingId),
+
+    /// Error generated when a participant attempts to join a meeting
+    /// slot bound to a verifying key without a valid signature.
+    #[error(r#"invalid signature to join meeting "{0}""#)]
+    InvalidSignature(MeetingId),
+
+    /// Error generated when a participant attempts to join a meeting
+    /// without a slot for the user identifier.
+    #[error(r#"meeting "{0}" does not have a slot for the user"#)]
+    MeetingSlotNotFound(MeetingId),
+
+    /// Error generated when a participant attempts to leave a
+    /// meeting they have not joined.
+    #[error(r#"meeting "{0}" does not have participant "{1}""#)]
+    NotMeetingParticipant(MeetingId, String),
 
     /// Error generated when a session could not be found.
     #[error(r#"session "{0}" not found"#)]
@@ -58,10 +97,27 @@ pub enum Error {
     #[error(r#"session "{0}" does not have participant "{1}""#)]
     NotSessionParticipant(SessionId, String),
 
-    /// Error generated when the session timeout is not greater
-    /// than the interval.
-    #[error("session timeout must be greater than the interval")]
-    SessionTimeoutConfig,
+    /// Error generated when a message relayed in the context of
+    /// a session exceeds the maximum message size.
+    #[error(r#"message for session "{0}" exceeds {1} bytes"#)]
+    MessageTooLarge(SessionId, u64),
+
+    /// Error generated when a message relayed without a
+    /// session exceeds the maximum message size.
+    #[error("peer message exceeds {0} bytes")]
+    PeerMessageTooLarge(u64),
+
+    /// Error generated when a session quorum requires more
+    /// participants than were invited or a deadline beyond the
+    /// session wait timeout.
+    #[error(
+        "session quorum must be satisfiable before the wait timeout"
+    )]
+    SessionQuorum,
+
+    /// Error generated when the session interval is zero.
+    #[error("session interval must be greater than zero")]
+    SessionIntervalConfig,
 
     /// Error generated when the session wait timeout is not greater
     /// than the wait interval.
@@ -70,10 +126,132 @@ pub enum Error {
     )]
     SessionWaitConfig,
 
+    /// Error generated when the session maximums are less than
+    /// the default timeouts.
+    #[error("session maximums must not be less than the timeouts")]
+    SessionLimitsConfig,
+
+    /// Error generated when the heartbeat timeout is not greater
+    /// than the heartbeat interval.
+    #[error("heartbeat timeout must be greater than the interval")]
+    HeartbeatConfig,
+
+    /// Error generated when the auth configuration does not
+    /// specify exactly one of a secret or a JWKS file.
+    #[error("auth config requires one of secret or jwks")]
+    AuthConfig,
+
+    /// Error generated when a token header does not have
+    /// a key identifier.
+    #[error("token header does not have a key identifier")]
+    TokenKeyId,
+
+    /// Error generated when the key for a token could not be
+    /// found in the key set.
+    #[error(r#"token key "{0}" not found"#)]
+    TokenKeyNotFound(String),
+
+    /// Error generated when the key for a token does not declare
+    /// an algorithm and no algorithm is configured.
+    #[error(r#"token key "{0}" does not have an algorithm"#)]
+    TokenAlgorithm(String),
+
+    /// Error generated when the algorithm in a token header is
+    /// not the algorithm for the key.
+    #[error("token algorithm {0:?} does not match {1:?}")]
+    TokenAlgorithmMismatch(
+        jsonwebtoken::Algorithm,
+        jsonwebtoken::Algorithm,
+    ),
+
+    /// Error generated when the number of sessions owned by
+    /// a client would exceed the limit in the token claims.
+    #[error("session limit {0} exceeded")]
+    SessionLimit(usize),
+
+    /// Error generated when a spawned server stops before
+    /// it starts listening.
+    #[error("server stopped before listening")]
+    NotListening,
+
+    /// Error generated when a key file does not contain
+    /// a private key.
+    #[error(r#"no private key in "{0}""#)]
+    NoPrivateKey(PathBuf),
+
+    /// Error generated when TLS requires client certificates
+    /// to be bound to public keys without a client CA bundle.
+    #[error(
+        "binding certificates to public keys requires a client CA"
+    )]
+    ClientCaRequired,
+
+    /// Error generated when starting a server on the configured
+    /// listeners without a listener, Unix domain socket or QUIC
+    /// endpoint in the config.
+    #[error(
+        "server config requires a listener, unix socket or quic endpoint"
+    )]
+    ListenerRequired,
+
+    /// Error generated when a QUIC frame exceeds the
+    /// maximum frame size.
+    #[error("frame of {0} bytes exceeds the maximum frame size")]
+    FrameTooLarge(usize),
+
+    /// Error generated when a QUIC client does not send
+    /// the connect frame.
+    #[error("quic stream finished before the connect frame")]
+    NoConnectFrame,
+
+    /// Error generated when webhooks are configured
+    /// without a secret for signing events.
+    #[error("webhook config requires a secret")]
+    WebhookSecret,
+
+    /// Error generated when a webhook URL does not use
+    /// the HTTP scheme.
+    #[error(r#"webhook url "{0}" must use the http scheme"#)]
+    WebhookScheme(Url),
+
+    /// Error generated when a webhook responds with
+    /// an unsuccessful status code.
+    #[error("webhook responded with status {0}")]
+    WebhookStatus(StatusCode),
+
+    /// Error generated when a webhook does not respond
+    /// before the timeout.
+    #[error("webhook request timed out")]
+    WebhookTimeout,
+
     /// Error generated by input/output.
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
+    /// Error generated by the HTTP server.
+    #[error(transparent)]
+    Hyper(#[from] hyper::Error),
+
+    /// Error generated by the TLS library.
+    #[error(transparent)]
+    Rustls(#[from] rustls::Error),
+
+    /// Error generated by a QUIC connection.
+    #[error(transparent)]
+    QuicConnection(#[from] quinn::ConnectionError),
+
+    /// Error generated writing to a QUIC stream.
+    #[error(transparent)]
+    QuicWrite(#[from] quinn::WriteError),
+
+    /// Error generated reading from a QUIC stream.
+    #[error(transparent)]
+    QuicRead(#[from] quinn::ReadExactError),
+
+    /// Error generated when a task fails to complete.
+    #[error(transparent)]
+    Join(#[from] tokio::task::JoinError),
+
     /// Error generated by the protocol library.
     #[error(transparent)]
     Protocol(#[from] mpc_protocol::Error),
@@ -86,6 +264,14 @@ pub enum Error {
     #[error(transparent)]
     Snow(#[from] mpc_protocol::snow::error::Error),
 
+    /// Error generated parsing JSON.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// Error generated verifying a token.
+    #[error(transparent)]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+
     /// Error generated parsing TOML.
     #[error(transparent)]
     Toml(#[from] toml::de::Error),
@@ -94,6 +280,10 @@ pub enum Error {
     #[error(transparent)]
     HeaderValue(#[from] axum::http::header::InvalidHeaderValue),
 
+    /// Error generated building an HTTP request.
+    #[error(transparent)]
+    Http(#[from] axum::http::Error),
+
     /// Error generated sending a buffer over a channel.
     #[error(transparent)]
     BufferMpscSend(
@@ -106,3 +296,79 @@ pub enum Error {
         #[from] tokio::sync::mpsc::error::SendError<Message>,
     ),
 }
+
+impl Error {
+    /// Error code sent to clients for this error.
+    ///
+    /// Variants are listed exhaustively so that new errors
+    /// must be assigned a code.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::PermissionDenied
+            | Error::InvalidJoinCode(_)
+            | Error::InvalidSignature(_)
+            | Error::TokenKeyId
+            | Error::TokenKeyNotFound(_)
+            | Error::TokenAlgorithmMismatch(_, _)
+            | Error::Jwt(_) => ErrorCode::PermissionDenied,
+            Error::PeerAlreadyExists
+            | Error::DuplicateConnection(_) => {
+                ErrorCode::DuplicateConnection
+            }
+            Error::PeerNotFound(_) => ErrorCode::PeerOffline,
+            Error::NotHandshakeState
+            | Error::FrameTooLarge(_)
+            | Error::NoConnectFrame => ErrorCode::BadRequest,
+            Error::MeetingNotFound(_) => ErrorCode::MeetingNotFound,
+            Error::MeetingSlotFilled(_) => ErrorCode::MeetingFull,
+            Error::MeetingSlots
+            | Error::MeetingJoinCode
+            | Error::MeetingSlotNotFound(_) => ErrorCode::BadRequest,
+            Error::NotMeetingParticipant(_, _) => {
+                ErrorCode::NotMeetingParticipant
+            }
+            Error::SessionLimit(_) => ErrorCode::RateLimited,
+            Error::SessionQuorum
+            | Error::MessageTooLarge(_, _)
+            | Error::PeerMessageTooLarge(_) => ErrorCode::BadRequest,
+            Error::SessionNotFound(_) => ErrorCode::SessionNotFound,
+            Error::NotSessionParticipant(_, _) => {
+                ErrorCode::NotSessionParticipant
+            }
+            Error::NotFile(_)
+            | Error::KeyFileRequired
+            | Error::KeyNotFound(_)
+            | Error::NoParentDir
+            | Error::SessionIntervalConfig
+            | Error::SessionWaitConfig
+            | Error::SessionLimitsConfig
+            | Error::HeartbeatConfig
+            | Error::AuthConfig
+            | Error::TokenAlgorithm(_)
+            | Error::NotListening
+            | Error::NoPrivateKey(_)
+            | Error::ClientCaRequired
+            | Error::ListenerRequired
+            | Error::WebhookSecret
+            | Error::WebhookScheme(_)
+            | Error::WebhookStatus(_)
+            | Error::WebhookTimeout
+            | Error::Io(_)
+            | Error::Hyper(_)
+            | Error::Rustls(_)
+            | Error::QuicConnection(_)
+            | Error::QuicWrite(_)
+            | Error::QuicRead(_)
+            | Error::Join(_)
+            | Error::Protocol(_)
+            | Error::Axum(_)
+            | Error::Snow(_)
+            | Error::Json(_)
+            | Error::Toml(_)
+            | Error::HeaderValue(_)
+            | Error::Http(_)
+            | Error::BufferMpscSend(_)
+            | Error::MessageMpscSend(_) => ErrorCode::Internal,
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! HTTP transport for clients that cannot use websockets.
+//!
+//! Response messages are streamed to the client as server-sent
+//! events and request messages are sent in POST requests, the
+//! frames are the same compressed and encrypted buffers that
+//! are sent over a websocket.
+//!
+//! The first event on the stream is a `connect` event with
+//! a JSON object containing the connection `id` that must be
+//! included in the path for POST and DELETE requests and a
+//! `secret` that must be sent as a bearer token in the
+//! `Authorization` header for those requests. Frames are sent
+//! as base64-encoded `message` events and a `ping` event must
+//! be answered with a POST request that has an empty body.
+use axum::{
+    body::Bytes,
+    extract::{ws::Message, Extension, Path, Query, TypedHeader},
+    headers::{authorization::Bearer, Authorization},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, Sse},
+        IntoResponse, Response,
+    },
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{convert::Infallible, sync::Arc, time::Instant};
+use subtle::ConstantTimeEq;
+
+use crate::{
+    server::{Service, State},
+    tls::PeerCertificate,
+    websocket::{
+        self, accept, disconnect, heartbeat, Accepted, Connection,
+        PeerAddr, WebSocketQuery,
+    },
+};
+use mpc_protocol::{hex, uuid::Uuid, zlib};
+
+/// Query string for requests to an existing connection.
+#[derive(Debug, Deserialize)]
+pub struct ConnectionQuery {
+    /// Public key for the connection.
+    #[serde(with = "hex::serde")]
+    pub public_key: Vec<u8>,
+}
+
+/// Data for the first event on the stream.
+#[derive(Debug, Serialize)]
+struct ConnectEvent {
+    /// Identifier for the connection.
+    id: Uuid,
+    /// Secret for requests to the connection.
+    secret: String,
+}
+
+/// Disconnects a connection when the event stream is dropped.
+struct StreamGuard {
+    state: State,
+    conn: Connection,
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        tokio::spawn(disconnect(
+            Arc::clone(&self.state),
+            Arc::clone(&self.conn),
+        ));
+    }
+}
+
+/// Open an event stream for a new connection.
+pub async fn connect(
+    Extension(state): Extension<State>,
+    Extension(service): Extension<Service>,
+    peer: PeerAddr,
+    certificate: Option<Extension<PeerCertificate>>,
+    Query(query): Query<WebSocketQuery>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+    headers: HeaderMap,
+) -> std::result::Result<Response, StatusCode> {
+    tracing::debug!("event stream request");
+
+    let Accepted {
+        conn,
+        outgoing_rx,
+        service_reader,
+        ..
+    } = accept(
+        Arc::clone(&state),
+        peer,
+        certificate,
+        query,
+        bearer,
+        headers,
+    )
+    .await?;
+
+    service.listen_socket(Arc::clone(&conn), service_reader);
+    tokio::spawn(heartbeat(Arc::clone(&state), Arc::clone(&conn)));
+
+    let connect = {
+        let mut writer = conn.write().await;
+        let secret = hex::encode(rand::random::<[u8; 32]>());
+        writer.secret = Some(secret.clone());
+        ConnectEvent {
+            id: writer.id,
+            secret,
+        }
+    };
+    let guard = StreamGuard { state, conn };
+    let connected = Event::default()
+        .event("connect")
+        .json_data(connect)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let events =
+        stream::once(async move { Ok::<_, Infallible>(connected) })
+            .chain(stream::unfold(
+                (outgoing_rx, guard),
+                |(mut outgoing_rx, guard)| async move {
+                    loop {
+                        let event = match outgoing_rx.recv().await {
+                            Some(Message::Binary(buffer)) => {
+                                Event::default()
+                                    .event("message")
+                                    .data(STANDARD.encode(buffer))
+                            }
+                            Some(Message::Ping(_)) => {
+                                Event::default()
+                                    .event("ping")
+                                    .data("")
+                            }
+                            Some(Message::Close(_)) | None => {
+                                return None
+                            }
+                            Some(_) => continue,
+                        };
+                        return Some((
+                            Ok(event),
+                            (outgoing_rx, guard),
+                        ));
+                    }
+                },
+            ));
+
+    Ok(Sse::new(events).into_response())
+}
+
+/// Send a request message frame for a connection.
+///
+/// An empty body answers a ping event.
+pub async fn send(
+    Extension(state): Extension<State>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ConnectionQuery>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+    body: Bytes,
+) -> std::result::Result<StatusCode, StatusCode> {
+    let conn = find(&state, &id, &query.public_key, bearer).await?;
+
+    let incoming = {
+        let mut writer = conn.write().await;
+        writer.last_seen = Instant::now();
+        writer.incoming.clone()
+    };
+
+    if !body.is_empty() {
+        let inflated = zlib::inflate(&body).map_err(|_| {
+            tracing::warn!("could not inflate message buffer");
+            StatusCode::BAD_REQUEST
+        })?;
+        incoming
+            .send(inflated)
+            .await
+            .map_err(|_| StatusCode::GONE)?;
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Close a connection.
+pub async fn close(
+    Extension(state): Extension<State>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ConnectionQuery>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+) -> std::result::Result<StatusCode, StatusCode> {
+    let conn = find(&state, &id, &query.public_key, bearer).await?;
+    websocket::close(state, conn).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Find a pending or active connection by identifier.
+///
+/// The bearer token must match the secret issued when the
+/// event stream was opened; the secret is compared in constant
+/// time and an unknown connection and an invalid secret both
+/// respond with not found so connection identifiers are not
+/// revealed.
+async fn find(
+    state: &State,
+    id: &Uuid,
+    public_key: &[u8],
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+) -> std::result::Result<Connection, StatusCode> {
+    let TypedHeader(bearer) =
+        bearer.ok_or(StatusCode::UNAUTHORIZED)?;
+    let reader = state.read().await;
+    let conn = reader
+        .pending
+        .get(id)
+        .or_else(|| reader.active.get(public_key))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let found = {
+        let conn = conn.read().await;
+        &conn.id == id
+            && conn.public_key == public_key
+            && conn.secret.as_ref().is_some_and(|secret| {
+                secret
+                    .as_bytes()
+                    .ct_eq(bearer.token().as_bytes())
+                    .into()
+            })
+    };
+    found.then(|| Arc::clone(conn)).ok_or(StatusCode::NOT_FOUND)
+}
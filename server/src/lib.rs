@@ -4,15 +4,25 @@
 
 #![deny(missing_docs)]
 
+mod auth;
 mod config;
 mod error;
+mod http;
+mod quic;
 mod server;
 mod service;
+mod tls;
+mod webhook;
 mod websocket;
 
-pub use config::ServerConfig;
+pub use auth::{AuthConfig, Claims};
+pub use config::{
+    DuplicateConnectionPolicy, ListenerConfig, QuicConfig,
+    RelayPolicy, Route, ServerConfig, TlsConfig, UnixSocketConfig,
+    WebhookConfig,
+};
 pub use error::Error;
-pub use server::RelayServer;
+pub use server::{RelayHandle, RelayServer, SpawnedServer};
 
 pub use axum;
 
@@ -0,0 +1,261 @@
+//! QUIC transport for lower latency relaying.
+//!
+//! A client opens a single bidirectional stream and sends a
+//! connect frame with the JSON-encoded query for the connection
+//! (the public key and optional token), the server replies with
+//! a frame containing the HTTP status code for the connection.
+//!
+//! Afterwards the frames are the same compressed and encrypted
+//! buffers that are sent over a websocket. Each frame is prefixed
+//! with the length as a big-endian 32-bit integer, an empty frame
+//! from the server is a ping that the client answers with an
+//! empty frame.
+//!
+//! Messages sent by a client to each peer are written on a
+//! unidirectional stream for the peer so that a lost packet for
+//! one peer does not delay the messages for other peers. QUIC
+//! does not order delivery across streams so the messages relayed
+//! to a client are written on the bidirectional stream in order
+//! with the messages for the server channel.
+//!
+//! Head-of-line blocking is therefore only avoided when sending,
+//! on the receive side a lost packet delays all the messages for
+//! the client until it is retransmitted, as with a websocket.
+use axum::{
+    extract::{ws::Message, Extension},
+    http::{HeaderMap, StatusCode},
+};
+use quinn::{
+    Connecting, Connection as QuicConnection, Endpoint,
+    ReadExactError, RecvStream, SendStream,
+};
+use std::{sync::Arc, time::Instant};
+use tokio::sync::mpsc;
+
+use crate::{
+    config::QuicConfig,
+    server::{Service, State},
+    tls::{PeerCertificate, TlsSource},
+    websocket::{
+        accept, disconnect, heartbeat, Accepted, Connection,
+        PeerAddr, WebSocketQuery,
+    },
+    Error, Result,
+};
+use mpc_protocol::zlib;
+
+/// Protocol identifier negotiated with ALPN.
+pub(crate) const ALPN: &[u8] = b"mpc-relay";
+
+/// Maximum size of a frame.
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// Bind a QUIC endpoint.
+pub(crate) async fn bind(quic: &QuicConfig) -> Result<Endpoint> {
+    Ok(Endpoint::server(server_config(quic).await?, quic.bind)?)
+}
+
+/// Load the server configuration for a QUIC endpoint.
+pub(crate) async fn server_config(
+    quic: &QuicConfig,
+) -> Result<quinn::ServerConfig> {
+    let crypto = crate::tls::quic_config(&quic.tls, ALPN).await?;
+    Ok(quinn::ServerConfig::with_crypto(Arc::new(crypto)))
+}
+
+/// Accept connections on a QUIC endpoint.
+pub(crate) async fn run(
+    state: State,
+    service: Service,
+    endpoint: Endpoint,
+) -> Result<()> {
+    while let Some(connecting) = endpoint.accept().await {
+        let state = Arc::clone(&state);
+        let service = Arc::clone(&service);
+        tokio::spawn(async move {
+            if let Err(e) =
+                handle_connection(state, service, connecting).await
+            {
+                tracing::debug!(error = %e, "quic connection");
+            }
+        });
+    }
+    Ok(())
+}
+
+async fn handle_connection(
+    state: State,
+    service: Service,
+    connecting: Connecting,
+) -> Result<()> {
+    let connection = connecting.await?;
+    let (mut send, mut recv) = connection.accept_bi().await?;
+
+    tracing::debug!("quic connect request");
+
+    let query: WebSocketQuery = match read_frame(&mut recv).await? {
+        Some(buffer) => serde_json::from_slice(&buffer)?,
+        None => return Err(Error::NoConnectFrame),
+    };
+
+    let certificate = connection
+        .peer_identity()
+        .and_then(|identity| {
+            identity.downcast::<Vec<rustls::Certificate>>().ok()
+        })
+        .and_then(|certs| certs.first().map(|cert| cert.0.clone()));
+    let certificate =
+        PeerCertificate::new(certificate, TlsSource::Quic);
+
+    let accepted = accept(
+        Arc::clone(&state),
+        PeerAddr::Network(connection.remote_address()),
+        Some(Extension(certificate)),
+        query,
+        None,
+        HeaderMap::new(),
+    )
+    .await;
+
+    let Accepted {
+        conn,
+        outgoing_rx,
+        service_reader,
+        ..
+    } = match accepted {
+        Ok(accepted) => {
+            write_frame(
+                &mut send,
+                &StatusCode::OK.as_u16().to_be_bytes(),
+            )
+            .await?;
+            accepted
+        }
+        Err(status) => {
+            write_frame(&mut send, &status.as_u16().to_be_bytes())
+                .await?;
+            send.finish().await?;
+            return Ok(());
+        }
+    };
+
+    service.listen_socket(Arc::clone(&conn), service_reader);
+
+    tokio::spawn(write(
+        send,
+        connection.clone(),
+        Arc::clone(&state),
+        Arc::clone(&conn),
+        outgoing_rx,
+    ));
+    tokio::spawn(accept_peers(connection, Arc::clone(&conn)));
+    tokio::spawn(heartbeat(Arc::clone(&state), Arc::clone(&conn)));
+
+    let result = read(recv, Arc::clone(&conn)).await;
+    disconnect(state, conn).await;
+    result
+}
+
+/// Read the streams opened by the client for the messages
+/// relayed to each peer.
+async fn accept_peers(connection: QuicConnection, conn: Connection) {
+    while let Ok(recv) = connection.accept_uni().await {
+        let conn = Arc::clone(&conn);
+        tokio::spawn(async move {
+            if let Err(e) = read(recv, conn).await {
+                tracing::debug!(error = %e, "quic peer stream");
+            }
+        });
+    }
+}
+
+/// Read frames from a stream until it is finished.
+async fn read(mut recv: RecvStream, conn: Connection) -> Result<()> {
+    let tx = {
+        let reader = conn.read().await;
+        reader.incoming.clone()
+    };
+
+    loop {
+        let buffer = match read_frame(&mut recv).await? {
+            Some(buffer) => buffer,
+            None => return Ok(()),
+        };
+
+        {
+            let mut writer = conn.write().await;
+            writer.last_seen = Instant::now();
+        }
+
+        // Empty frames answer pings
+        if buffer.is_empty() {
+            continue;
+        }
+
+        if let Ok(inflated) = zlib::inflate(&buffer) {
+            tx.send(inflated).await?;
+        } else {
+            tracing::warn!("could not inflate message buffer");
+        }
+    }
+}
+
+async fn write(
+    mut send: SendStream,
+    connection: QuicConnection,
+    state: State,
+    conn: Connection,
+    mut outgoing_rx: mpsc::Receiver<Message>,
+) -> Result<()> {
+    while let Some(message) = outgoing_rx.recv().await {
+        let result = match message {
+            Message::Binary(buffer) => {
+                write_frame(&mut send, &buffer).await
+            }
+            Message::Ping(_) => write_frame(&mut send, &[]).await,
+            Message::Close(_) => {
+                let _ = send.finish().await;
+                connection.close(0u32.into(), b"close");
+                break;
+            }
+            _ => Ok(()),
+        };
+
+        if result.is_err() {
+            disconnect(state, Arc::clone(&conn)).await;
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+/// Read a length-prefixed frame.
+///
+/// Returns `None` when the stream is finished.
+async fn read_frame(
+    recv: &mut RecvStream,
+) -> Result<Option<Vec<u8>>> {
+    let mut length = [0u8; 4];
+    match recv.read_exact(&mut length).await {
+        Ok(_) => {}
+        Err(ReadExactError::FinishedEarly) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_FRAME_SIZE {
+        return Err(Error::FrameTooLarge(length));
+    }
+    let mut buffer = vec![0u8; length];
+    recv.read_exact(&mut buffer).await?;
+    Ok(Some(buffer))
+}
+
+/// Write a length-prefixed frame.
+async fn write_frame(
+    send: &mut SendStream,
+    buffer: &[u8],
+) -> Result<()> {
+    send.write_all(&(buffer.len() as u32).to_be_bytes()).await?;
+    send.write_all(buffer).await?;
+    Ok(())
+}
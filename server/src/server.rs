@@ -1,61 +1,231 @@
-use futures::StreamExt;
 use std::{
-    collections::HashMap, net::SocketAddr, sync::Arc, time::Duration,
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::{mpsc, Notify, RwLock},
+    task::JoinHandle,
 };
-use tokio::sync::RwLock;
-use tokio_stream::wrappers::IntervalStream;
 
 use axum::{
     extract::Extension,
-    http::{HeaderValue, Method, StatusCode},
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE},
+        request::Parts,
+        HeaderValue, Method, StatusCode,
+    },
     response::{IntoResponse, Response},
-    routing::get,
+    routing::{get, post},
     Router,
 };
 use axum_server::{tls_rustls::RustlsConfig, Handle};
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tower_http::{
+    cors::{AllowOrigin, CorsLayer},
+    trace::TraceLayer,
+};
 use uuid::Uuid;
 
 use mpc_protocol::{
-    hex, uuid, Keypair, MeetingManager, SessionManager,
+    hex, uuid, Keypair, MeetingManager, ServerMessage, SessionManager,
 };
 
 use crate::{
-    config::{ServerConfig, TlsConfig},
-    Result,
+    config::{ListenerConfig, Route, ServerConfig},
+    Error, Result,
 };
 
-use crate::{service::RelayService, websocket::Connection};
+use crate::{
+    service::{notify_peers, RelayService},
+    tls::{CertificateAcceptor, TlsSource},
+    webhook::{self, WebhookEvent, WebhookMessage},
+    websocket::Connection,
+};
 
 pub type State = Arc<RwLock<ServerState>>;
 pub(crate) type Service = Arc<RelayService>;
+type Origins = Arc<std::sync::RwLock<Vec<HeaderValue>>>;
 
 async fn purge_expired(state: State, interval_secs: u64) {
-    let interval =
-        tokio::time::interval(Duration::from_secs(interval_secs));
-    let mut stream = IntervalStream::new(interval);
-    while stream.next().await.is_some() {
+    let interval = Duration::from_secs(interval_secs);
+    let scheduled = {
+        let reader = state.read().await;
+        Arc::clone(&reader.scheduled)
+    };
+    loop {
+        // Wait until the earliest deadline or warning, the
+        // interval or a new meeting or session is scheduled
+        let next_deadline = {
+            let reader = state.read().await;
+            let warning = reader.config.session.expiry_warning;
+            [
+                reader.meetings.next_deadline(),
+                reader.sessions.next_deadline(),
+                warning.and_then(|w| reader.meetings.next_warning(w)),
+                warning.and_then(|w| reader.sessions.next_warning(w)),
+            ]
+            .into_iter()
+            .flatten()
+            .min()
+        };
+        let wait = next_deadline
+            .map(|deadline| {
+                deadline.saturating_duration_since(Instant::now())
+            })
+            .unwrap_or(interval)
+            .min(interval);
+        let _ =
+            tokio::time::timeout(wait, scheduled.notified()).await;
+
         let mut writer = state.write().await;
-        let expired_meetings = writer
-            .meetings
-            .expired_keys(writer.config.session.timeout);
+        let timeout = writer.config.session.timeout;
+
+        // Without a warning period the warnings are discarded
+        // but still checked so expired keys leave the queue
+        let warning = writer.config.session.expiry_warning;
+        let mut warnings =
+            expiring(&mut writer, timeout, warning.unwrap_or(0));
+        if warning.is_none() {
+            warnings.clear();
+        }
+
+        let expired_meetings = writer.meetings.expired_keys(timeout);
         tracing::debug!(
             expired_meetings = %expired_meetings.len());
+        let mut notifications = Vec::new();
         for key in expired_meetings {
-            writer.meetings.remove_meeting(&key);
+            if let Some(meeting) =
+                writer.meetings.remove_meeting(&key)
+            {
+                notifications.push((key, meeting.participants()));
+            }
         }
 
-        let expired_sessions = writer
-            .sessions
-            .expired_keys(writer.config.session.timeout);
+        let expired_sessions = writer.sessions.expired_keys(timeout);
         tracing::debug!(
             expired_sessions = %expired_sessions.len());
+        let mut session_notifications = Vec::new();
         for key in expired_sessions {
-            writer.sessions.remove_session(&key);
+            if let Some(session) =
+                writer.sessions.remove_session(&key)
+            {
+                let participants: Vec<_> = session
+                    .public_keys()
+                    .into_iter()
+                    .map(|k| k.to_vec())
+                    .collect();
+                session_notifications.push((key, participants));
+            }
+        }
+        drop(writer);
+
+        // Warn participants so they can refresh activity
+        for (participants, message) in warnings {
+            if let Err(e) = notify_peers(
+                Arc::clone(&state),
+                participants,
+                message,
+            )
+            .await
+            {
+                tracing::error!("{}", e);
+            }
+        }
+
+        // Let participants know the session expired
+        for (session_id, participants) in session_notifications {
+            webhook::dispatch(
+                &state,
+                WebhookEvent::SessionExpired {
+                    session_id,
+                    participants: webhook::public_keys(&participants),
+                },
+            )
+            .await;
+            if let Err(e) = notify_peers(
+                Arc::clone(&state),
+                participants,
+                ServerMessage::SessionExpired(session_id),
+            )
+            .await
+            {
+                tracing::error!("{}", e);
+            }
+        }
+
+        // Let waiting participants know the meeting expired
+        for (meeting_id, participants) in notifications {
+            if let Err(e) = notify_peers(
+                Arc::clone(&state),
+                participants,
+                ServerMessage::MeetingExpired(meeting_id),
+            )
+            .await
+            {
+                tracing::error!("{}", e);
+            }
         }
     }
 }
 
+/// Wait until a server task is listening.
+///
+/// Returns the error for the task when it finishes before
+/// listening, eg: when the TLS certificate cannot be loaded.
+async fn listening(
+    handle: &Handle,
+    task: &mut JoinHandle<Result<()>>,
+) -> Result<SocketAddr> {
+    let result = tokio::select! {
+        addr = handle.listening() => match addr {
+            Some(addr) => return Ok(addr),
+            None => task.await,
+        },
+        result = &mut *task => result,
+    };
+    Err(match result {
+        Ok(Err(e)) => e,
+        _ => Error::NotListening,
+    })
+}
+
+/// Notifications for meetings and sessions that expire
+/// within the warning period.
+fn expiring(
+    state: &mut ServerState,
+    timeout: u64,
+    warning: u64,
+) -> Vec<(Vec<Vec<u8>>, ServerMessage)> {
+    let mut notifications = Vec::new();
+    for (key, expires_in) in
+        state.meetings.expiring_keys(timeout, warning)
+    {
+        if let Some(meeting) = state.meetings.get_meeting(&key) {
+            notifications.push((
+                meeting.participants(),
+                ServerMessage::MeetingExpiring(key, expires_in),
+            ));
+        }
+    }
+
+    for (key, expires_in) in
+        state.sessions.expiring_keys(timeout, warning)
+    {
+        if let Some(session) = state.sessions.get_session(&key) {
+            notifications.push((
+                session
+                    .public_keys()
+                    .into_iter()
+                    .map(|k| k.to_vec())
+                    .collect(),
+                ServerMessage::SessionExpiring(key, expires_in),
+            ));
+        }
+    }
+    notifications
+}
+
 pub struct ServerState {
     /// Server keypair.
     pub(crate) keypair: Keypair,
@@ -76,11 +246,29 @@ pub struct ServerState {
 
     /// Session manager.
     pub(crate) sessions: SessionManager,
+
+    /// Notified when a meeting or session is created so
+    /// the purge task can wait for the new deadline.
+    pub(crate) scheduled: Arc<Notify>,
+
+    /// TLS configuration when running on HTTPS.
+    pub(crate) tls: Option<RustlsConfig>,
+
+    /// TLS configuration for listeners running on HTTPS.
+    pub(crate) listener_tls: HashMap<SocketAddr, RustlsConfig>,
+
+    /// Endpoint when listening for QUIC connections.
+    pub(crate) quic: Option<quinn::Endpoint>,
+
+    /// Queue of events for the webhooks.
+    pub(crate) webhooks: Option<mpsc::Sender<WebhookMessage>>,
 }
 
 /// Relay web server.
+#[derive(Clone)]
 pub struct RelayServer {
     state: State,
+    origins: Origins,
 }
 
 impl RelayServer {
@@ -94,31 +282,392 @@ impl RelayServer {
                 active: Default::default(),
                 meetings: Default::default(),
                 sessions: Default::default(),
+                scheduled: Default::default(),
+                tls: None,
+                listener_tls: Default::default(),
+                quic: None,
+                webhooks: None,
             })),
+            origins: Default::default(),
         }
     }
 
+    /// Reload the server configuration.
+    ///
+    /// The access lists, CORS origins, session settings, TLS
+    /// certificates, client CA bundles and public key bindings
+    /// are swapped in together; the certificates are all loaded
+    /// before any are applied so if the new configuration cannot
+    /// be applied the current configuration is left untouched.
+    ///
+    /// When `disconnect_denied` is set clients that are no
+    /// longer allowed access are disconnected.
+    ///
+    /// The server key, the interval for purging expired sessions,
+    /// the listeners, the Unix domain socket, the QUIC endpoint,
+    /// the webhook queue and whether TLS is enabled cannot be
+    /// changed without restarting the server.
+    pub async fn reload(&self, config: ServerConfig) -> Result<()> {
+        let origins = cors_origins(&config)?;
+
+        let (rustls, listener_tls, endpoint) = {
+            let reader = self.state.read().await;
+            if reader.config.session.interval
+                != config.session.interval
+            {
+                tracing::warn!(
+                    "session interval changes require a restart"
+                );
+            }
+            if reader.config.key != config.key {
+                tracing::warn!(
+                    "server key changes require a restart"
+                );
+            }
+            (
+                reader.tls.clone(),
+                reader.listener_tls.clone(),
+                reader.quic.clone(),
+            )
+        };
+
+        // Load all the certificates before any are applied so
+        // a failure leaves the running configuration untouched
+        let mut reloads = Vec::new();
+        match (rustls, &config.tls) {
+            (Some(rustls), Some(tls)) => {
+                reloads.push((rustls, crate::tls::load(tls).await?));
+            }
+            (None, None) => {}
+            _ => {
+                tracing::warn!(
+                    "enabling or disabling TLS requires a restart"
+                );
+            }
+        }
+
+        for listener in &config.listeners {
+            if let (Some(tls), Some(rustls)) =
+                (&listener.tls, listener_tls.get(&listener.bind))
+            {
+                reloads.push((
+                    rustls.clone(),
+                    crate::tls::load(tls).await?,
+                ));
+            }
+        }
+
+        let quic = match (endpoint, &config.quic) {
+            (Some(endpoint), Some(quic)) => Some((
+                endpoint,
+                crate::quic::server_config(quic).await?,
+            )),
+            _ => None,
+        };
+
+        for (rustls, tls) in reloads {
+            rustls.reload_from_config(tls);
+        }
+        if let Some((endpoint, quic)) = quic {
+            endpoint.set_server_config(Some(quic));
+        }
+
+        *self.origins.write().unwrap() = origins;
+
+        let connections: Vec<Connection> = {
+            let mut writer = self.state.write().await;
+            writer.config = config;
+            if writer.config.disconnect_denied {
+                writer
+                    .pending
+                    .values()
+                    .chain(writer.active.values())
+                    .map(Arc::clone)
+                    .collect()
+            } else {
+                Vec::new()
+            }
+        };
+
+        for conn in connections {
+            let (public_key, addr) = {
+                let reader = conn.read().await;
+                (reader.public_key.clone(), reader.addr)
+            };
+            let allowed = {
+                let reader = self.state.read().await;
+                reader.config.is_allowed_access(&public_key)
+                    && addr
+                        .map(|addr| {
+                            reader.config.is_allowed_network(&addr)
+                        })
+                        .unwrap_or(true)
+            };
+            if !allowed {
+                tracing::info!(
+                    public_key = ?hex::encode(&public_key),
+                    "disconnect denied",
+                );
+                crate::websocket::close(
+                    Arc::clone(&self.state),
+                    conn,
+                )
+                .await;
+            }
+        }
+
+        tracing::info!("config reloaded");
+        Ok(())
+    }
+
     /// Start the server.
+    ///
+    /// The server also serves the listeners and the Unix
+    /// domain socket in the configuration, they are listening
+    /// before the server starts listening on the address.
     pub async fn start(
         &self,
         addr: SocketAddr,
         handle: Handle,
     ) -> Result<()> {
-        let reader = self.state.read().await;
-        let interval = reader.config.session.interval;
-        let tls = reader.config.tls.as_ref().cloned();
-        drop(reader);
+        let tls = {
+            let reader = self.state.read().await;
+            reader.config.tls.as_ref().cloned()
+        };
+
+        let _listeners = self.spawn_listeners().await?;
+
+        if let Some(tls) = tls {
+            let rustls = crate::tls::rustls_config(&tls).await?;
+            {
+                let mut writer = self.state.write().await;
+                writer.tls = Some(rustls.clone());
+            }
+            let acceptor =
+                CertificateAcceptor::new(rustls, TlsSource::Server);
+            self.run_tls(addr, handle, acceptor, None).await
+        } else {
+            self.run(addr, handle, None).await
+        }
+    }
+
+    /// Start the server listening only on the listeners and
+    /// the Unix domain socket in the configuration.
+    ///
+    /// Returns when one of the listeners stops, the server
+    /// runs until the returned future is dropped.
+    pub async fn start_listeners(&self) -> Result<()> {
+        {
+            let reader = self.state.read().await;
+            if reader.config.listeners.is_empty()
+                && reader.config.quic.is_none()
+                && (cfg!(not(unix)) || reader.config.unix.is_none())
+            {
+                return Err(Error::ListenerRequired);
+            }
+        }
+
+        let mut listeners = self.spawn_listeners().await?;
+        listeners.wait().await
+    }
+
+    /// Start the server in a background task and wait
+    /// until it is listening.
+    ///
+    /// Bind to port zero to listen on an ephemeral port,
+    /// the returned server has the actual address.
+    ///
+    /// The server is shut down when the returned value
+    /// is dropped.
+    pub async fn spawn(
+        &self,
+        addr: SocketAddr,
+    ) -> Result<SpawnedServer> {
+        let (public_key, tls) = {
+            let reader = self.state.read().await;
+            (
+                reader.keypair.public_key().to_vec(),
+                reader.config.tls.is_some(),
+            )
+        };
+
+        let handle = Handle::new();
+        let server = self.clone();
+        let server_handle = handle.clone();
+        let mut task = tokio::task::spawn(async move {
+            server.start(addr, server_handle).await
+        });
+
+        let addr = listening(&handle, &mut task).await?;
+        let quic_addr = {
+            let reader = self.state.read().await;
+            reader
+                .quic
+                .as_ref()
+                .and_then(|endpoint| endpoint.local_addr().ok())
+        };
+        Ok(SpawnedServer {
+            addr,
+            quic_addr,
+            public_key,
+            tls,
+            handle,
+        })
+    }
+
+    /// Create a router to mount the relay in an existing
+    /// axum application.
+    ///
+    /// Routes are registered under the path prefix (eg: `/relay`)
+    /// so the router can be merged into another router and
+    /// clients connect using the prefixed server URL
+    /// (eg: `wss://example.com/relay`). An empty prefix
+    /// mounts the relay at the root.
+    ///
+    /// The application must be served with connect info
+    /// for [SocketAddr] and is responsible for TLS; the TLS
+    /// configuration for the relay is ignored.
+    ///
+    /// The background tasks for expiring sessions and meetings
+    /// are spawned and stopped when the returned handle
+    /// is shut down.
+    pub async fn router(
+        &self,
+        prefix: &str,
+    ) -> Result<(Router, RelayHandle)> {
+        let app = self.build_router(prefix, None).await?;
+        let tasks = self.spawn_tasks().await;
+        let handle = RelayHandle {
+            state: Arc::clone(&self.state),
+            tasks,
+        };
+        Ok((app, handle))
+    }
+
+    /// Spawn the background tasks, the listeners and the Unix
+    /// domain socket in the configuration and wait until the
+    /// listeners are listening.
+    async fn spawn_listeners(&self) -> Result<Listeners> {
+        let (listeners, unix, quic) = {
+            let reader = self.state.read().await;
+            (
+                reader.config.listeners.clone(),
+                reader.config.unix.as_ref().cloned(),
+                reader.config.quic.as_ref().cloned(),
+            )
+        };
 
-        // Spawn task to reap expired sessions
-        tokio::task::spawn(purge_expired(
+        let mut running = Listeners {
+            tasks: self.spawn_tasks().await,
+            handles: Vec::new(),
+            servers: Vec::new(),
+        };
+
+        #[cfg(unix)]
+        if let Some(unix) = unix {
+            let listener = bind_unix(&unix)?;
+            let server = self.clone();
+            running.servers.push(tokio::task::spawn(async move {
+                server.run_unix(listener, unix).await
+            }));
+        }
+
+        #[cfg(not(unix))]
+        if unix.is_some() {
+            tracing::warn!("unix sockets are not supported");
+        }
+
+        if let Some(quic) = quic {
+            let endpoint = crate::quic::bind(&quic).await?;
+            tracing::info!(addr = %endpoint.local_addr()?, "quic");
+            {
+                let mut writer = self.state.write().await;
+                writer.quic = Some(endpoint.clone());
+            }
+            let state = Arc::clone(&self.state);
+            let service =
+                Arc::new(RelayService::new(Arc::clone(&self.state)));
+            running.servers.push(tokio::task::spawn(
+                crate::quic::run(state, service, endpoint),
+            ));
+        }
+
+        for listener in listeners {
+            let handle = Handle::new();
+            let server = self.clone();
+            let server_handle = handle.clone();
+            let mut task = tokio::task::spawn(async move {
+                server.run_listener(listener, server_handle).await
+            });
+
+            listening(&handle, &mut task).await?;
+
+            running.handles.push(handle);
+            running.servers.push(task);
+        }
+
+        Ok(running)
+    }
+
+    /// Spawn the background tasks.
+    async fn spawn_tasks(&self) -> Vec<JoinHandle<()>> {
+        let (interval, queue_size) = {
+            let reader = self.state.read().await;
+            (
+                reader.config.session.interval,
+                reader
+                    .config
+                    .webhooks
+                    .as_ref()
+                    .map(|webhooks| webhooks.queue_size),
+            )
+        };
+
+        // Spawn task to warn about and reap expired sessions
+        let mut tasks = vec![tokio::task::spawn(purge_expired(
             Arc::clone(&self.state),
             interval,
-        ));
+        ))];
 
-        if let Some(tls) = tls {
-            self.run_tls(addr, handle, tls).await
+        // Spawn task to send events to the webhooks
+        if let Some(queue_size) = queue_size {
+            let (tx, rx) = mpsc::channel(queue_size.max(1));
+            {
+                let mut writer = self.state.write().await;
+                writer.webhooks = Some(tx);
+            }
+            tasks.push(tokio::task::spawn(webhook::deliver(
+                Arc::clone(&self.state),
+                rx,
+            )));
+        }
+
+        tasks
+    }
+
+    /// Start a listener from the configuration.
+    async fn run_listener(
+        &self,
+        listener: ListenerConfig,
+        handle: Handle,
+    ) -> Result<()> {
+        let routes = listener.routes.as_deref();
+        if let Some(tls) = &listener.tls {
+            let rustls = crate::tls::rustls_config(tls).await?;
+            {
+                let mut writer = self.state.write().await;
+                writer
+                    .listener_tls
+                    .insert(listener.bind, rustls.clone());
+            }
+            let acceptor = CertificateAcceptor::new(
+                rustls,
+                TlsSource::Listener(listener.bind),
+            );
+            self.run_tls(listener.bind, handle, acceptor, routes)
+                .await
         } else {
-            self.run(addr, handle).await
+            self.run(listener.bind, handle, routes).await
         }
     }
 
@@ -127,20 +676,22 @@ impl RelayServer {
         &self,
         addr: SocketAddr,
         handle: Handle,
-        tls: TlsConfig,
+        acceptor: CertificateAcceptor,
+        routes: Option<&[Route]>,
     ) -> Result<()> {
-        let tls =
-            RustlsConfig::from_pem_file(&tls.cert, &tls.key).await?;
-        let app = self.router(Arc::clone(&self.state)).await?;
+        let app = self.build_router("", routes).await?;
         let public_key = {
             let reader = self.state.read().await;
             reader.keypair.public_key().to_vec()
         };
         tracing::info!("listening on {}", addr);
         tracing::info!("public key {}", hex::encode(&public_key));
-        axum_server::bind_rustls(addr, tls)
+        axum_server::bind(addr)
+            .acceptor(acceptor)
             .handle(handle)
-            .serve(app.into_make_service())
+            .serve(
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
             .await?;
         Ok(())
     }
@@ -150,8 +701,9 @@ impl RelayServer {
         &self,
         addr: SocketAddr,
         handle: Handle,
+        routes: Option<&[Route]>,
     ) -> Result<()> {
-        let app = self.router(Arc::clone(&self.state)).await?;
+        let app = self.build_router("", routes).await?;
         let public_key = {
             let reader = self.state.read().await;
             reader.keypair.public_key().to_vec()
@@ -160,34 +712,107 @@ impl RelayServer {
         tracing::info!("public key {}", hex::encode(&public_key));
         axum_server::bind(addr)
             .handle(handle)
+            .serve(
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Start the server running on a Unix domain socket.
+    #[cfg(unix)]
+    async fn run_unix(
+        &self,
+        listener: tokio::net::UnixListener,
+        unix: crate::UnixSocketConfig,
+    ) -> Result<()> {
+        // Connections on the socket do not have a network
+        // address so they are marked as Unix peers
+        let app = self
+            .build_router("", None)
+            .await?
+            .layer(Extension(crate::websocket::PeerAddr::Unix));
+        let public_key = {
+            let reader = self.state.read().await;
+            reader.keypair.public_key().to_vec()
+        };
+        tracing::info!("listening on {}", unix.path.display());
+        tracing::info!("public key {}", hex::encode(&public_key));
+        axum::Server::builder(UnixAccept(listener))
             .serve(app.into_make_service())
             .await?;
         Ok(())
     }
 
-    async fn router(&self, state: State) -> Result<Router> {
+    async fn build_router(
+        &self,
+        prefix: &str,
+        routes: Option<&[Route]>,
+    ) -> Result<Router> {
+        let state = Arc::clone(&self.state);
         let origins = {
             let reader = state.read().await;
-            let mut origins = Vec::new();
-            for url in reader.config.cors.origins.iter() {
-                tracing::info!(url = %url, "cors");
-                origins.push(HeaderValue::from_str(
-                    url.as_str().trim_end_matches('/'),
-                )?);
-            }
-            origins
+            cors_origins(&reader.config)?
         };
+        *self.origins.write().unwrap() = origins;
 
+        // Origins are checked on each request so that
+        // they can be changed when the config is reloaded
+        let origins = Arc::clone(&self.origins);
         let cors = CorsLayer::new()
-            .allow_methods(vec![Method::GET])
-            //.allow_headers(vec![])
+            .allow_methods(vec![
+                Method::GET,
+                Method::POST,
+                Method::DELETE,
+            ])
+            .allow_headers(vec![AUTHORIZATION, CONTENT_TYPE])
             //.expose_headers(vec![])
-            .allow_origin(origins);
+            .allow_origin(AllowOrigin::predicate(
+                move |origin: &HeaderValue, _: &Parts| {
+                    origins.read().unwrap().contains(origin)
+                },
+            ));
 
         let service = Arc::new(RelayService::new(Arc::clone(&state)));
-        let mut app = Router::new()
-            .route("/", get(crate::websocket::upgrade))
-            .route("/public-key", get(public_key));
+        let prefix = prefix.trim_matches('/');
+        let prefix = if prefix.is_empty() {
+            String::new()
+        } else {
+            format!("/{}", prefix)
+        };
+        let enabled = |route: Route| {
+            routes.map(|r| r.contains(&route)).unwrap_or(true)
+        };
+
+        let mut app = Router::new();
+        if enabled(Route::Websocket) {
+            app = app.route(
+                &format!("{}/", prefix),
+                get(crate::websocket::upgrade),
+            );
+            if !prefix.is_empty() {
+                app = app
+                    .route(&prefix, get(crate::websocket::upgrade));
+            }
+        }
+        if enabled(Route::Http) {
+            app = app
+                .route(
+                    &format!("{}/http", prefix),
+                    get(crate::http::connect),
+                )
+                .route(
+                    &format!("{}/http/:id", prefix),
+                    post(crate::http::send)
+                        .delete(crate::http::close),
+                );
+        }
+        if enabled(Route::PublicKey) {
+            app = app.route(
+                &format!("{}/public-key", prefix),
+                get(public_key),
+            );
+        }
         app = app
             .layer(cors)
             .layer(TraceLayer::new_for_http())
@@ -197,6 +822,195 @@ impl RelayServer {
     }
 }
 
+/// Background tasks and listeners for a server.
+///
+/// The tasks and listeners are stopped when this is dropped.
+struct Listeners {
+    tasks: Vec<JoinHandle<()>>,
+    handles: Vec<Handle>,
+    servers: Vec<JoinHandle<Result<()>>>,
+}
+
+impl Listeners {
+    /// Wait until one of the listeners stops.
+    async fn wait(&mut self) -> Result<()> {
+        let (result, _, _) =
+            futures::future::select_all(self.servers.iter_mut())
+                .await;
+        result?
+    }
+}
+
+impl Drop for Listeners {
+    fn drop(&mut self) {
+        for handle in &self.handles {
+            handle.shutdown();
+        }
+        for task in &self.tasks {
+            task.abort();
+        }
+        for task in &self.servers {
+            task.abort();
+        }
+    }
+}
+
+/// Relay server running in a background task.
+///
+/// The server is shut down when this is dropped.
+pub struct SpawnedServer {
+    addr: SocketAddr,
+    quic_addr: Option<SocketAddr>,
+    public_key: Vec<u8>,
+    tls: bool,
+    handle: Handle,
+}
+
+impl SpawnedServer {
+    /// Address the server is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Address of the QUIC endpoint when configured.
+    pub fn quic_addr(&self) -> Option<SocketAddr> {
+        self.quic_addr
+    }
+
+    /// Public key of the server.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Websocket URL for clients to connect to the server.
+    pub fn url(&self) -> String {
+        let scheme = if self.tls { "wss" } else { "ws" };
+        format!("{}://{}", scheme, self.addr)
+    }
+}
+
+impl Drop for SpawnedServer {
+    fn drop(&mut self) {
+        self.handle.shutdown();
+    }
+}
+
+/// Handle for a relay mounted in another application.
+///
+/// Dropping the handle does not stop the background tasks,
+/// call [RelayHandle::shutdown] to stop the tasks and
+/// close the client connections.
+pub struct RelayHandle {
+    state: State,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl RelayHandle {
+    /// Stop the background tasks and close all connections.
+    pub async fn shutdown(self) {
+        for task in self.tasks {
+            task.abort();
+        }
+
+        let connections: Vec<Connection> = {
+            let reader = self.state.read().await;
+            reader
+                .pending
+                .values()
+                .chain(reader.active.values())
+                .map(Arc::clone)
+                .collect()
+        };
+
+        for conn in connections {
+            crate::websocket::close(Arc::clone(&self.state), conn)
+                .await;
+        }
+    }
+}
+
+/// Bind a listener for the Unix domain socket and
+/// set the permissions for the socket file.
+///
+/// When permissions are configured the socket is bound in a
+/// private directory next to the socket path and moved into
+/// place once the permissions are set so it is never
+/// accessible with the permissions from the umask.
+#[cfg(unix)]
+fn bind_unix(
+    unix: &crate::UnixSocketConfig,
+) -> Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{
+        DirBuilderExt, FileTypeExt, PermissionsExt,
+    };
+
+    // Remove a socket left behind by a previous run
+    if let Ok(metadata) = std::fs::symlink_metadata(&unix.path) {
+        if metadata.file_type().is_socket() {
+            std::fs::remove_file(&unix.path)?;
+        }
+    }
+
+    let mode = match unix.mode {
+        Some(mode) => mode,
+        None => {
+            return Ok(tokio::net::UnixListener::bind(&unix.path)?)
+        }
+    };
+
+    let parent = unix
+        .path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| std::path::Path::new("."));
+    let dir = parent.join(format!(".mpc-relay-{}", Uuid::new_v4()));
+    std::fs::DirBuilder::new().mode(0o700).create(&dir)?;
+    let path = dir.join("relay.sock");
+    let result =
+        tokio::net::UnixListener::bind(&path).and_then(|listener| {
+            std::fs::set_permissions(
+                &path,
+                std::fs::Permissions::from_mode(mode),
+            )?;
+            std::fs::rename(&path, &unix.path)?;
+            Ok(listener)
+        });
+    let _ = std::fs::remove_file(&path);
+    std::fs::remove_dir(&dir)?;
+    Ok(result?)
+}
+
+/// Accept connections on a Unix domain socket.
+#[cfg(unix)]
+struct UnixAccept(tokio::net::UnixListener);
+
+#[cfg(unix)]
+impl hyper::server::accept::Accept for UnixAccept {
+    type Conn = tokio::net::UnixStream;
+    type Error = std::io::Error;
+
+    fn poll_accept(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<std::io::Result<Self::Conn>>> {
+        self.0
+            .poll_accept(cx)
+            .map(|result| Some(result.map(|(stream, _)| stream)))
+    }
+}
+
+/// Parse the CORS origins from the server configuration.
+fn cors_origins(config: &ServerConfig) -> Result<Vec<HeaderValue>> {
+    let mut origins = Vec::new();
+    for url in config.cors.origins.iter() {
+        tracing::info!(url = %url, "cors");
+        origins.push(HeaderValue::from_str(
+            url.as_str().trim_end_matches('/'),
+        )?);
+    }
+    Ok(origins)
+}
+
 async fn public_key(
     Extension(state): Extension<State>,
 ) -> std::result::Result<Response, StatusCode> {
@@ -1,4 +1,3 @@
-use axum::http::StatusCode;
 use futures::StreamExt;
 use std::{
     sync::Arc,
@@ -9,12 +8,19 @@ use tokio_stream::wrappers::IntervalStream;
 
 use mpc_protocol::{
     channel::{decrypt_server_channel, encrypt_server_channel},
-    decode, encode, hex, Encoding, HandshakeMessage, MeetingState,
-    OpaqueMessage, ProtocolState, RequestMessage, ResponseMessage,
-    ServerMessage, SessionState, TransparentMessage,
+    decode, encode, hex, Encoding, HandshakeMessage, JoinCode,
+    MeetingInvitation, MeetingSecret, MeetingState, OpaqueMessage,
+    ProtocolState, RequestMessage, ResponseMessage, ServerMessage,
+    Session, SessionId, SessionQuorum, SessionState, SessionStatus,
+    TransparentMessage,
 };
 
-use crate::{server::State, websocket::Connection, Error, Result};
+use crate::{
+    server::{ServerState, State},
+    webhook::{self, WebhookEvent},
+    websocket::{close, Connection},
+    DuplicateConnectionPolicy, Error, RelayPolicy, Result,
+};
 
 pub struct RelayService {
     state: State,
@@ -45,7 +51,13 @@ async fn listen(
     mut read_channel: mpsc::Receiver<Vec<u8>>,
 ) -> Result<()> {
     while let Some(buffer) = read_channel.recv().await {
-        let message: RequestMessage = decode(&buffer).await?;
+        let message: RequestMessage = match decode(&buffer).await {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!(error = %e, "invalid request message");
+                continue;
+            }
+        };
         if let Err(e) = handle_request(
             Arc::clone(&state),
             Arc::clone(&conn),
@@ -124,6 +136,13 @@ async fn handle_request(
 
             let peer = {
                 let reader = state.read().await;
+                if !is_relay_allowed(
+                    &reader,
+                    &from_public_key,
+                    &public_key,
+                ) {
+                    return Err(Error::PermissionDenied);
+                }
                 reader.active.get(&public_key).map(Arc::clone)
             };
 
@@ -156,36 +175,75 @@ async fn handle_request(
             session_id,
             envelope,
         }) => {
+            let from_public_key = {
+                let reader = conn.read().await;
+                reader.public_key.clone()
+            };
+
             // When we have a session identifier check the session
             // is valid and the target peer is a session participant.
             if let Some(id) = session_id {
                 let mut writer = state.write().await;
+                let relay = writer.config.relay;
+                let timeout = writer.config.session.timeout;
                 if let Some(session) =
                     writer.sessions.touch_session(&id)
                 {
-                    let public_keys = session.public_keys();
-                    let is_participant = public_keys
-                        .into_iter()
-                        .any(|k| k == public_key);
+                    if session.is_expired(timeout) {
+                        return Err(Error::SessionNotFound(id));
+                    }
 
-                    if !is_participant {
+                    if let Some(max_message_size) =
+                        session.limits().max_message_size
+                    {
+                        if envelope.len() as u64 > max_message_size {
+                            return Err(Error::MessageTooLarge(
+                                id,
+                                max_message_size,
+                            ));
+                        }
+                    }
+
+                    if !session.is_participant(&public_key) {
                         return Err(Error::NotSessionParticipant(
                             id,
                             hex::encode(public_key),
                         ));
                     }
+
+                    if matches!(relay, RelayPolicy::Participants)
+                        && !session.is_participant(&from_public_key)
+                    {
+                        return Err(Error::NotSessionParticipant(
+                            id,
+                            hex::encode(from_public_key),
+                        ));
+                    }
                 } else {
                     return Err(Error::SessionNotFound(id));
                 }
+            } else {
+                let reader = state.read().await;
+                let max_message_size =
+                    reader.config.session.max_message_size;
+                if envelope.len() as u64 > max_message_size {
+                    return Err(Error::PeerMessageTooLarge(
+                        max_message_size,
+                    ));
+                }
             }
 
-            let from_public_key = {
-                let reader = conn.read().await;
-                reader.public_key.clone()
-            };
-
             let peer = {
                 let reader = state.read().await;
+                if session_id.is_none()
+                    && !is_relay_allowed(
+                        &reader,
+                        &from_public_key,
+                        &public_key,
+                    )
+                {
+                    return Err(Error::PermissionDenied);
+                }
                 reader.active.get(&public_key).map(Arc::clone)
             };
 
@@ -277,13 +335,7 @@ async fn wait_for_meeting_ready(
             if let Some(target) =
                 reader.meetings.get_meeting(&meeting.meeting_id)
             {
-                let ready = target.is_full();
-                let state = MeetingState {
-                    meeting_id: meeting.meeting_id,
-                    registered_participants: target.participants(),
-                    data: target.data().clone(),
-                };
-                (ready, state)
+                (target.is_full(), target.state(meeting.meeting_id))
             } else {
                 break;
             }
@@ -305,25 +357,81 @@ async fn wait_for_session_ready(
     state: State,
     owner: Connection,
     start_time: SystemTime,
-    session: SessionState,
+    mut session: SessionState,
+    quorum: Option<SessionQuorum>,
 ) {
     let interval =
         tokio::time::interval(Duration::from_secs(interval_secs));
     let mut stream = IntervalStream::new(interval);
     while stream.next().await.is_some() {
-        let (ready, wait_timeout) = {
+        let (joined, wait_timeout) = {
             let reader = state.read().await;
+            let target = if let Some(target) =
+                reader.sessions.get_session(&session.session_id)
+            {
+                target
+            } else {
+                break;
+            };
+            // Without a quorum participants are counted as
+            // joined once connected so clients that do not
+            // join explicitly are still supported
             (
-                session.all_participants.iter().all(|public_key| {
-                    reader.active.get(public_key).is_some()
-                }),
+                session
+                    .all_participants
+                    .iter()
+                    .filter(|public_key| {
+                        (quorum.is_none()
+                            || target.is_joined(public_key))
+                            && reader.active.contains_key(*public_key)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>(),
                 Duration::from_secs(
-                    reader.config.session.wait_timeout,
+                    session.limits.wait_timeout.unwrap_or(
+                        reader.config.session.wait_timeout,
+                    ),
                 ),
             )
         };
 
+        let mut ready =
+            joined.len() == session.all_participants.len();
+
+        // Proceed with the connected participants that have
+        // joined once the quorum deadline has passed
+        if let Some(quorum) = &quorum {
+            let duration = start_time.elapsed().unwrap();
+            if !ready
+                && duration >= Duration::from_secs(quorum.deadline)
+                && joined.len() >= quorum.min_participants as usize
+            {
+                session.active_participants = joined;
+                ready = true;
+            }
+        }
+
         if ready {
+            {
+                let mut writer = state.write().await;
+                if let Some(target) = writer
+                    .sessions
+                    .get_session_mut(&session.session_id)
+                {
+                    target.set_active_participants(
+                        session.active_participants.clone(),
+                    );
+                    if quorum.is_none() {
+                        for public_key in &session.all_participants {
+                            target.join(public_key);
+                        }
+                    }
+                    target.set_ready();
+                } else {
+                    break;
+                }
+            }
+
             if let Err(e) = notify_session_ready(
                 Arc::clone(&state),
                 session.clone(),
@@ -361,22 +469,31 @@ async fn notify_meeting_ready(
     let public_keys: Vec<_> = meeting
         .registered_participants
         .iter()
-        .map(|key| key.to_vec())
+        .map(|p| p.public_key.to_vec())
         .collect();
+    webhook::dispatch(
+        &state,
+        WebhookEvent::MeetingReady {
+            meeting_id: meeting.meeting_id,
+            participants: webhook::public_keys(&public_keys),
+        },
+    )
+    .await;
     let message = ServerMessage::MeetingReady(meeting);
     notify_peers(state, public_keys, message).await?;
     Ok(())
 }
 
+/// Notify the participants that a session is ready.
+///
+/// Connected participants that are not taking part in a
+/// session that proceeds with a quorum are also sent the
+/// state so they know the session went ahead without them.
 async fn notify_session_ready(
     state: State,
     session: SessionState,
 ) -> Result<()> {
-    let public_keys: Vec<_> = session
-        .all_participants
-        .iter()
-        .map(|key| key.to_vec())
-        .collect();
+    let public_keys = session.all_participants.clone();
     let message = ServerMessage::SessionReady(session);
     notify_peers(state, public_keys, message).await?;
     Ok(())
@@ -395,13 +512,15 @@ async fn wait_for_session_active(
     while stream.next().await.is_some() {
         let (active, wait_timeout) = {
             let reader = state.read().await;
-            if let Some(session) =
+            if let Some(target) =
                 reader.sessions.get_session(&session.session_id)
             {
                 (
-                    session.is_active(),
+                    target.is_active(),
                     Duration::from_secs(
-                        reader.config.session.wait_timeout,
+                        target.limits().wait_timeout.unwrap_or(
+                            reader.config.session.wait_timeout,
+                        ),
                     ),
                 )
             } else {
@@ -435,10 +554,18 @@ async fn notify_session_active(
     session: SessionState,
 ) -> Result<()> {
     let public_keys: Vec<_> = session
-        .all_participants
+        .active_participants
         .iter()
         .map(|key| key.to_vec())
         .collect();
+    webhook::dispatch(
+        &state,
+        WebhookEvent::SessionActive {
+            session_id: session.session_id,
+            participants: webhook::public_keys(&public_keys),
+        },
+    )
+    .await;
     let message = ServerMessage::SessionActive(session);
     notify_peers(state, public_keys, message).await?;
     Ok(())
@@ -453,6 +580,14 @@ async fn notify_session_timeout(
         .iter()
         .map(|key| key.to_vec())
         .collect();
+    webhook::dispatch(
+        &state,
+        WebhookEvent::SessionTimeout {
+            session_id: session.session_id,
+            participants: webhook::public_keys(&public_keys),
+        },
+    )
+    .await;
     let message = ServerMessage::SessionTimeout(session.session_id);
     notify_peers(state, public_keys, message).await?;
 
@@ -469,22 +604,59 @@ async fn service(
         ServerMessage::NewMeeting {
             owner_id,
             slots,
+            invitations,
+            join_code,
             data,
         } => {
+            if invitations && join_code.is_some() {
+                return Err(Error::MeetingJoinCode);
+            }
+
+            let secret = MeetingSecret {
+                join_code: if invitations {
+                    None
+                } else {
+                    Some(join_code.unwrap_or_else(new_join_code))
+                },
+                invitations: if invitations {
+                    slots
+                        .iter()
+                        .filter(|s| s.user_id != owner_id)
+                        .map(|s| MeetingInvitation {
+                            user_id: s.user_id.clone(),
+                            join_code: new_join_code(),
+                        })
+                        .collect()
+                } else {
+                    vec![]
+                },
+            };
+
             // Meeting initiator is automatically a
             // registered participant
-            let registered_participants =
-                vec![public_key.as_ref().to_vec()];
-
-            let (meeting_id, wait_interval) = {
+            let (meeting_id, registered_participants, wait_interval) = {
                 let mut writer = state.write().await;
-                let meeting_id = writer.meetings.new_meeting(
-                    public_key.as_ref().to_vec(),
-                    owner_id,
-                    slots,
-                    data.clone(),
-                );
-                (meeting_id, writer.config.session.wait_interval)
+                let meeting_id = writer
+                    .meetings
+                    .new_meeting(
+                        public_key.as_ref().to_vec(),
+                        owner_id,
+                        slots,
+                        secret.clone(),
+                        data.clone(),
+                    )
+                    .ok_or(Error::MeetingSlots)?;
+                writer.scheduled.notify_one();
+                let registered_participants = writer
+                    .meetings
+                    .get_meeting(&meeting_id)
+                    .map(|m| m.registered_participants())
+                    .unwrap_or_default();
+                (
+                    meeting_id,
+                    registered_participants,
+                    writer.config.session.wait_interval,
+                )
             };
 
             let response = MeetingState {
@@ -501,9 +673,14 @@ async fn service(
                 response.clone(),
             ));
 
-            Ok(Some(ServerMessage::MeetingCreated(response)))
+            Ok(Some(ServerMessage::MeetingCreated(response, secret)))
         }
-        ServerMessage::JoinMeeting(meeting_id, user_id) => {
+        ServerMessage::JoinMeeting(
+            meeting_id,
+            user_id,
+            join_code,
+            signature,
+        ) => {
             let from_public_key = {
                 let reader = conn.read().await;
                 reader.public_key.clone()
@@ -511,17 +688,109 @@ async fn service(
 
             let mut writer = state.write().await;
             if let Some(meeting) =
-                writer.meetings.get_meeting_mut(&meeting_id)
+                writer.meetings.get_meeting(&meeting_id)
             {
-                if meeting.is_full() {
-                    Err(Error::MeetingFull(meeting_id))
-                } else {
-                    meeting.join(user_id, from_public_key);
-                    Ok(None)
+                // Invitations are bound to the public key
+                // once the participant has joined the slot
+                if !meeting.verify_signature(
+                    &meeting_id,
+                    &user_id,
+                    &from_public_key,
+                    signature.as_ref(),
+                ) {
+                    return Err(Error::InvalidSignature(meeting_id));
+                } else if !meeting.verify_join_code(
+                    &user_id,
+                    &join_code,
+                    &from_public_key,
+                ) {
+                    return Err(Error::InvalidJoinCode(meeting_id));
+                }
+                match meeting
+                    .is_slot_available(&user_id, &from_public_key)
+                {
+                    Some(true) => {}
+                    Some(false) => {
+                        return Err(Error::MeetingSlotFilled(
+                            meeting_id,
+                        ))
+                    }
+                    None => {
+                        return Err(Error::MeetingSlotNotFound(
+                            meeting_id,
+                        ))
+                    }
                 }
             } else {
-                Err(Error::MeetingNotFound(meeting_id))
+                return Err(Error::MeetingNotFound(meeting_id));
+            }
+
+            // Invitation is bound to the public key by the join
+            writer.meetings.join_meeting(
+                &meeting_id,
+                &user_id,
+                from_public_key,
+                signature,
+            );
+            Ok(None)
+        }
+        ServerMessage::LeaveMeeting(meeting_id) => {
+            let mut writer = state.write().await;
+            if let Some(meeting) =
+                writer.meetings.get_meeting(&meeting_id)
+            {
+                // Owner must cancel the meeting
+                if public_key.as_ref() == meeting.owner_key() {
+                    return Err(Error::PermissionDenied);
+                }
+            } else {
+                return Err(Error::MeetingNotFound(meeting_id));
             }
+
+            if writer
+                .meetings
+                .leave_meeting(&meeting_id, public_key.as_ref())
+            {
+                Ok(None)
+            } else {
+                Err(Error::NotMeetingParticipant(
+                    meeting_id,
+                    hex::encode(public_key.as_ref()),
+                ))
+            }
+        }
+        ServerMessage::CancelMeeting(meeting_id) => {
+            let participants = {
+                let mut writer = state.write().await;
+                if let Some(meeting) =
+                    writer.meetings.get_meeting(&meeting_id)
+                {
+                    if public_key.as_ref() != meeting.owner_key() {
+                        return Err(Error::PermissionDenied);
+                    }
+                } else {
+                    return Err(Error::MeetingNotFound(meeting_id));
+                }
+
+                let meeting = writer
+                    .meetings
+                    .remove_meeting(&meeting_id)
+                    .unwrap();
+                meeting
+                    .participants()
+                    .into_iter()
+                    .filter(|k| k != public_key.as_ref())
+                    .collect::<Vec<_>>()
+            };
+
+            notify_peers(
+                Arc::clone(&state),
+                participants,
+                ServerMessage::MeetingCancelled(meeting_id),
+            )
+            .await?;
+
+            Ok(Some(ServerMessage::MeetingCancelled(meeting_id)))
         }
         ServerMessage::NewSession(request) => {
             let mut all_participants =
@@ -529,30 +798,127 @@ async fn service(
             all_participants
                 .append(&mut request.participant_keys.clone());
 
+            let limits = {
+                let reader = state.read().await;
+                reader.config.session.limits(&request.limits)
+            };
+
+            if let Some(quorum) = &request.quorum {
+                let wait_timeout =
+                    limits.wait_timeout.unwrap_or_default();
+                let min_participants =
+                    quorum.min_participants as usize;
+                if min_participants == 0
+                    || min_participants > all_participants.len()
+                    || quorum.deadline > wait_timeout
+                {
+                    return Err(Error::SessionQuorum);
+                }
+            }
+
+            let max_sessions = {
+                let reader = conn.read().await;
+                reader.claims.as_ref().and_then(|c| c.max_sessions)
+            };
+
             let (session_id, wait_interval) = {
                 let mut writer = state.write().await;
+                if let Some(max_sessions) = max_sessions {
+                    let owned = writer
+                        .sessions
+                        .owned_sessions(public_key.as_ref());
+                    if owned >= max_sessions {
+                        return Err(Error::SessionLimit(
+                            max_sessions,
+                        ));
+                    }
+                }
+
                 let session_id = writer.sessions.new_session(
                     public_key.as_ref().to_vec(),
-                    request.participant_keys,
+                    request.participant_keys.clone(),
+                    limits,
                 );
+                writer.scheduled.notify_one();
                 (session_id, writer.config.session.wait_interval)
             };
 
             let response = SessionState {
                 session_id,
+                active_participants: all_participants.clone(),
                 all_participants,
+                limits,
             };
 
+            webhook::dispatch(
+                &state,
+                WebhookEvent::SessionCreated {
+                    session_id,
+                    owner: hex::encode(public_key.as_ref()),
+                    participants: webhook::public_keys(
+                        &response.all_participants,
+                    ),
+                },
+            )
+            .await;
+
+            // Participants that connect later are invited
+            // once they have completed the server handshake
+            notify_peers(
+                Arc::clone(&state),
+                request.participant_keys,
+                ServerMessage::SessionInvitation(response.clone()),
+            )
+            .await?;
+
             tokio::task::spawn(wait_for_session_ready(
                 wait_interval,
                 Arc::clone(&state),
                 Arc::clone(&conn),
                 SystemTime::now(),
                 response.clone(),
+                request.quorum,
             ));
 
             Ok(Some(ServerMessage::SessionCreated(response)))
         }
+        ServerMessage::JoinSession(session_id) => {
+            let mut writer = state.write().await;
+            if let Some(session) =
+                writer.sessions.get_session_mut(&session_id)
+            {
+                // Participants that were counted as joined
+                // have already been sent the session state
+                if session.is_joined(public_key.as_ref()) {
+                    return Ok(None);
+                }
+                if !session.join(public_key.as_ref()) {
+                    return Err(Error::NotSessionParticipant(
+                        session_id,
+                        hex::encode(public_key.as_ref()),
+                    ));
+                }
+
+                // Participants that join late are sent the
+                // current state for the session
+                if session.is_ready() {
+                    let response = session.state(session_id);
+                    if session.is_active() {
+                        Ok(Some(ServerMessage::SessionActive(
+                            response,
+                        )))
+                    } else {
+                        Ok(Some(ServerMessage::SessionReady(
+                            response,
+                        )))
+                    }
+                } else {
+                    Ok(None)
+                }
+            } else {
+                Err(Error::SessionNotFound(session_id))
+            }
+        }
         ServerMessage::SessionConnection {
             session_id,
             peer_key,
@@ -563,9 +929,23 @@ async fn service(
             };
 
             let mut writer = state.write().await;
+            let relay = writer.config.relay;
             if let Some(session) =
                 writer.sessions.get_session_mut(&session_id)
             {
+                if matches!(relay, RelayPolicy::Participants) {
+                    for key in [&from_public_key, &peer_key] {
+                        if !session.is_participant(key) {
+                            return Err(
+                                Error::NotSessionParticipant(
+                                    session_id,
+                                    hex::encode(key),
+                                ),
+                            );
+                        }
+                    }
+                }
+
                 session
                     .register_connection(from_public_key, peer_key);
                 Ok(None)
@@ -587,17 +967,115 @@ async fn service(
                 }
             }
 
-            let mut writer = state.write().await;
-            writer.sessions.remove_session(&session_id);
+            {
+                let mut writer = state.write().await;
+                writer.sessions.remove_session(&session_id);
+            }
+
+            webhook::dispatch(
+                &state,
+                WebhookEvent::SessionFinished { session_id },
+            )
+            .await;
 
             Ok(Some(ServerMessage::SessionFinished(session_id)))
         }
+        ServerMessage::GetSession(session_id) => {
+            let reader = state.read().await;
+            if let Some(session) =
+                reader.sessions.get_session(&session_id)
+            {
+                if !session.is_participant(public_key.as_ref()) {
+                    return Err(Error::NotSessionParticipant(
+                        session_id,
+                        hex::encode(public_key.as_ref()),
+                    ));
+                }
+                let status =
+                    session_status(&reader, session_id, session);
+                Ok(Some(ServerMessage::SessionInfo(status)))
+            } else {
+                Err(Error::SessionNotFound(session_id))
+            }
+        }
+        ServerMessage::GetMeeting(meeting_id) => {
+            let reader = state.read().await;
+            if let Some(meeting) =
+                reader.meetings.get_meeting(&meeting_id)
+            {
+                if !meeting.is_participant(public_key.as_ref()) {
+                    return Err(Error::NotMeetingParticipant(
+                        meeting_id,
+                        hex::encode(public_key.as_ref()),
+                    ));
+                }
+                Ok(Some(ServerMessage::MeetingInfo(
+                    meeting.state(meeting_id),
+                )))
+            } else {
+                Err(Error::MeetingNotFound(meeting_id))
+            }
+        }
+        ServerMessage::ListSessions => {
+            let reader = state.read().await;
+            let sessions = reader
+                .sessions
+                .participant_sessions(public_key.as_ref())
+                .into_iter()
+                .map(|(session_id, session)| {
+                    session_status(&reader, session_id, session)
+                })
+                .collect();
+            Ok(Some(ServerMessage::SessionList(sessions)))
+        }
         _ => Ok(None),
     }
 }
 
+/// Status of a session for a query response.
+fn session_status(
+    state: &ServerState,
+    session_id: SessionId,
+    session: &Session,
+) -> SessionStatus {
+    let session_state = session.state(session_id);
+    let connected = session_state
+        .all_participants
+        .iter()
+        .filter(|key| state.active.contains_key(*key))
+        .cloned()
+        .collect();
+    SessionStatus {
+        state: session_state,
+        connected,
+        connections: session.connections(),
+        expires_in: session.expires_in(state.config.session.timeout),
+    }
+}
+
+/// Determine if a peer may relay a handshake or message
+/// to another peer under the configured relay policy.
+fn is_relay_allowed(
+    state: &ServerState,
+    from: &[u8],
+    to: &[u8],
+) -> bool {
+    match state.config.relay {
+        RelayPolicy::Any => true,
+        RelayPolicy::Participants => {
+            state.sessions.shares_session(from, to)
+                || state.meetings.shares_meeting(from, to)
+        }
+    }
+}
+
+/// Generate a random code used to join a meeting.
+fn new_join_code() -> JoinCode {
+    rand::random::<[u8; 32]>().into()
+}
+
 /// Send a message to a collection of peers.
-async fn notify_peers(
+pub(crate) async fn notify_peers(
     state: State,
     public_keys: Vec<Vec<u8>>,
     message: ServerMessage,
@@ -621,15 +1099,13 @@ async fn handle_error(conn: Connection, error: Error) -> Result<()> {
     // Connection is in transport mode so we can
     // send over the encrypted server channel
     if is_transport {
-        let response = ServerMessage::Error(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            error.to_string(),
-        );
+        let response =
+            ServerMessage::Error(error.code(), error.to_string());
         send_message(Arc::clone(&conn), &response, false).await?;
     } else {
         let response =
             ResponseMessage::Transparent(TransparentMessage::Error(
-                StatusCode::INTERNAL_SERVER_ERROR,
+                error.code(),
                 error.to_string(),
             ));
 
@@ -667,12 +1143,92 @@ async fn send_message(
 /// Promote a connection from pending to active state.
 ///
 /// Called once the server handshake has been initiated.
+///
+/// When another connection is already active for the same
+/// public key the duplicate connection policy determines
+/// whether the new connection is rejected or replaces the
+/// existing connection.
 async fn promote_connection(state: State, conn: Connection) {
     let (id, public_key) = {
         let reader = conn.read().await;
         (reader.id, reader.public_key.clone())
     };
+
     let mut writer = state.write().await;
     writer.pending.remove(&id);
-    writer.active.insert(public_key, conn);
+
+    let existing = writer.active.get(&public_key).map(Arc::clone);
+    match (existing, writer.config.duplicate_connection) {
+        (Some(_), DuplicateConnectionPolicy::Reject) => {
+            drop(writer);
+            let error =
+                Error::DuplicateConnection(hex::encode(&public_key));
+            if let Err(e) =
+                handle_error(Arc::clone(&conn), error).await
+            {
+                tracing::error!("{}", e);
+            }
+            close(state, conn).await;
+            return;
+        }
+        (Some(existing), DuplicateConnectionPolicy::Replace) => {
+            writer
+                .active
+                .insert(public_key.clone(), Arc::clone(&conn));
+            drop(writer);
+            tracing::debug!(
+                public_key = ?hex::encode(&public_key),
+                "replace connection",
+            );
+            if let Err(e) = send_message(
+                Arc::clone(&existing),
+                &ServerMessage::ConnectionReplaced,
+                false,
+            )
+            .await
+            {
+                tracing::error!("{}", e);
+            }
+            close(Arc::clone(&state), existing).await;
+        }
+        (None, _) => {
+            writer
+                .active
+                .insert(public_key.clone(), Arc::clone(&conn));
+            drop(writer);
+        }
+    }
+
+    if let Err(e) = invite_participant(state, conn, &public_key).await
+    {
+        tracing::error!("{}", e);
+    }
+}
+
+/// Invite a participant that has completed the server
+/// handshake to the sessions it has not joined.
+async fn invite_participant(
+    state: State,
+    conn: Connection,
+    public_key: &[u8],
+) -> Result<()> {
+    let invitations: Vec<_> = {
+        let reader = state.read().await;
+        reader
+            .sessions
+            .participant_sessions(public_key)
+            .into_iter()
+            .filter(|(_, session)| !session.is_joined(public_key))
+            .map(|(session_id, session)| session.state(session_id))
+            .collect()
+    };
+    for session in invitations {
+        send_message(
+            Arc::clone(&conn),
+            &ServerMessage::SessionInvitation(session),
+            true,
+        )
+        .await?;
+    }
+    Ok(())
 }
@@ -0,0 +1,268 @@
+//! TLS configuration and client certificate authentication.
+use axum_server::{
+    accept::Accept,
+    tls_rustls::{RustlsAcceptor, RustlsConfig},
+};
+use futures::future::BoxFuture;
+use mpc_protocol::hex;
+use rustls::{
+    server::AllowAnyAuthenticatedClient, Certificate, PrivateKey,
+    RootCertStore, ServerConfig,
+};
+use std::{io, net::SocketAddr, sync::Arc};
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncWrite},
+};
+use tokio_rustls::server::TlsStream;
+use tower_http::add_extension::AddExtension;
+use x509_parser::{certificate::X509Certificate, prelude::*};
+
+use crate::{config::TlsConfig, Error, Result};
+
+/// Load the rustls configuration.
+///
+/// When a client CA bundle is configured clients must present
+/// a certificate signed by one of the certificate authorities.
+pub(crate) async fn rustls_config(
+    tls: &TlsConfig,
+) -> Result<RustlsConfig> {
+    Ok(RustlsConfig::from_config(load(tls).await?))
+}
+
+/// Load the server configuration so it can be swapped into
+/// a running rustls configuration.
+pub(crate) async fn load(
+    tls: &TlsConfig,
+) -> Result<Arc<ServerConfig>> {
+    if tls.client_ca.is_none() && tls.bind_public_key {
+        return Err(Error::ClientCaRequired);
+    }
+    Ok(Arc::new(server_config(tls).await?))
+}
+
+/// Load the rustls configuration for a QUIC endpoint.
+pub(crate) async fn quic_config(
+    tls: &TlsConfig,
+    alpn: &[u8],
+) -> Result<ServerConfig> {
+    if tls.client_ca.is_none() && tls.bind_public_key {
+        return Err(Error::ClientCaRequired);
+    }
+    let mut config = server_config(tls).await?;
+    config.alpn_protocols = vec![alpn.to_vec()];
+    Ok(config)
+}
+
+/// Server configuration that requires client certificates
+/// when a client CA bundle is configured.
+async fn server_config(tls: &TlsConfig) -> Result<ServerConfig> {
+    let certs = rustls_pemfile::certs(
+        &mut fs::read(&tls.cert).await?.as_slice(),
+    )?
+    .into_iter()
+    .map(Certificate)
+    .collect();
+
+    let key = private_key(&fs::read(&tls.key).await?)?
+        .ok_or_else(|| Error::NoPrivateKey(tls.key.clone()))?;
+
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let mut config = if let Some(client_ca) = &tls.client_ca {
+        let mut roots = RootCertStore::empty();
+        for cert in rustls_pemfile::certs(
+            &mut fs::read(client_ca).await?.as_slice(),
+        )? {
+            roots.add(&Certificate(cert))?;
+        }
+        builder
+            .with_client_cert_verifier(
+                AllowAnyAuthenticatedClient::new(roots).boxed(),
+            )
+            .with_single_cert(certs, key)?
+    } else {
+        builder.with_no_client_auth().with_single_cert(certs, key)?
+    };
+    config.alpn_protocols =
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(config)
+}
+
+/// Read the first private key from PEM-encoded data.
+fn private_key(pem: &[u8]) -> io::Result<Option<PrivateKey>> {
+    use rustls_pemfile::Item;
+    for item in rustls_pemfile::read_all(&mut &pem[..])? {
+        match item {
+            Item::RSAKey(key)
+            | Item::PKCS8Key(key)
+            | Item::ECKey(key) => return Ok(Some(PrivateKey(key))),
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+/// TLS configuration that accepted a connection.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum TlsSource {
+    /// TLS configuration for the server address.
+    Server,
+    /// TLS configuration for an additional listener.
+    Listener(SocketAddr),
+    /// TLS configuration for the QUIC endpoint.
+    Quic,
+}
+
+impl TlsSource {
+    /// Find the TLS configuration in the server configuration.
+    fn config<'a>(
+        &self,
+        config: &'a crate::ServerConfig,
+    ) -> Option<&'a TlsConfig> {
+        match self {
+            Self::Server => config.tls.as_ref(),
+            Self::Listener(bind) => config
+                .listeners
+                .iter()
+                .find(|listener| &listener.bind == bind)
+                .and_then(|listener| listener.tls.as_ref()),
+            Self::Quic => config.quic.as_ref().map(|quic| &quic.tls),
+        }
+    }
+}
+
+/// Certificate presented by a client over TLS.
+///
+/// Added to the requests for a connection by the acceptor.
+#[derive(Clone)]
+pub(crate) struct PeerCertificate {
+    /// DER-encoded end entity certificate.
+    certificate: Option<Vec<u8>>,
+    /// TLS configuration that accepted the connection.
+    source: TlsSource,
+}
+
+impl PeerCertificate {
+    /// Create a peer certificate from the DER-encoded
+    /// end entity certificate.
+    pub(crate) fn new(
+        certificate: Option<Vec<u8>>,
+        source: TlsSource,
+    ) -> Self {
+        Self {
+            certificate,
+            source,
+        }
+    }
+
+    /// Determine if a client with a public key is allowed
+    /// to connect using this certificate.
+    ///
+    /// When the certificate must be bound to the public key
+    /// the subject common name or one of the subject
+    /// alternative names must be the hex-encoded public key.
+    ///
+    /// The setting is read from the current configuration so
+    /// it follows a reload; when the TLS configuration was
+    /// removed certificates must still be bound until the
+    /// server is restarted.
+    pub(crate) fn is_allowed(
+        &self,
+        config: &crate::ServerConfig,
+        public_key: &[u8],
+    ) -> bool {
+        let bind_public_key = self
+            .source
+            .config(config)
+            .map(|tls| tls.bind_public_key)
+            .unwrap_or(true);
+        if !bind_public_key {
+            return true;
+        }
+
+        let certificate = match &self.certificate {
+            Some(certificate) => certificate,
+            None => return false,
+        };
+
+        let cert = match X509Certificate::from_der(certificate) {
+            Ok((_, cert)) => cert,
+            Err(_) => return false,
+        };
+
+        let public_key = hex::encode(public_key);
+        let is_bound =
+            |name: &str| name.eq_ignore_ascii_case(&public_key);
+
+        let subject = cert
+            .subject()
+            .iter_common_name()
+            .filter_map(|name| name.as_str().ok())
+            .any(is_bound);
+
+        let alt_names = match cert.subject_alternative_name() {
+            Ok(Some(extension)) => extension
+                .value
+                .general_names
+                .iter()
+                .any(|name| match name {
+                    GeneralName::DNSName(name)
+                    | GeneralName::URI(name)
+                    | GeneralName::RFC822Name(name) => is_bound(name),
+                    _ => false,
+                }),
+            _ => false,
+        };
+
+        subject || alt_names
+    }
+}
+
+/// TLS acceptor that adds the client certificate to
+/// the requests for a connection.
+#[derive(Clone)]
+pub(crate) struct CertificateAcceptor {
+    inner: RustlsAcceptor,
+    source: TlsSource,
+}
+
+impl CertificateAcceptor {
+    /// Create a new certificate acceptor.
+    pub(crate) fn new(
+        config: RustlsConfig,
+        source: TlsSource,
+    ) -> Self {
+        Self {
+            inner: RustlsAcceptor::new(config),
+            source,
+        }
+    }
+}
+
+impl<I, S> Accept<I, S> for CertificateAcceptor
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = TlsStream<I>;
+    type Service = AddExtension<S, PeerCertificate>;
+    type Future =
+        BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let acceptor = self.inner.clone();
+        let source = self.source;
+        Box::pin(async move {
+            let (stream, service) =
+                acceptor.accept(stream, service).await?;
+            let certificate = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certs| certs.first())
+                .map(|cert| cert.0.clone());
+            let peer = PeerCertificate::new(certificate, source);
+            Ok((stream, AddExtension::new(service, peer)))
+        })
+    }
+}
@@ -0,0 +1,270 @@
+//! Webhooks for meeting and session lifecycle events.
+//!
+//! Events are queued without waiting for the webhooks and
+//! signed by a background task that forwards them to a
+//! queue for each URL. Each URL has its own task so a slow
+//! endpoint only delays and drops its own events, failed
+//! requests are retried with an exponential backoff.
+use axum::http::{header::CONTENT_TYPE, Method, Request};
+use hmac::{Hmac, Mac};
+use hyper::{client::HttpConnector, Body, Client};
+use mpc_protocol::{hex, uuid::Uuid, MeetingId, SessionId};
+use serde::Serialize;
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use url::Url;
+
+use crate::{server::State, Error, Result, WebhookConfig};
+
+/// Header for the signature of an event.
+const SIGNATURE_HEADER: &str = "x-relay-signature";
+
+/// Delay before the first retry, doubled for each attempt.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Meeting or session lifecycle event.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub(crate) enum WebhookEvent {
+    /// All participants have joined a meeting.
+    MeetingReady {
+        meeting_id: MeetingId,
+        participants: Vec<String>,
+    },
+    /// Session was created.
+    SessionCreated {
+        session_id: SessionId,
+        owner: String,
+        participants: Vec<String>,
+    },
+    /// Session participants have connected to each other.
+    SessionActive {
+        session_id: SessionId,
+        participants: Vec<String>,
+    },
+    /// Session participants did not connect before
+    /// the wait timeout.
+    SessionTimeout {
+        session_id: SessionId,
+        participants: Vec<String>,
+    },
+    /// Session was closed by the owner.
+    SessionFinished { session_id: SessionId },
+    /// Session was removed after a period of inactivity.
+    SessionExpired {
+        session_id: SessionId,
+        participants: Vec<String>,
+    },
+}
+
+/// Event queued for the webhooks.
+#[derive(Debug, Serialize)]
+pub(crate) struct WebhookMessage {
+    /// Unique identifier so retried events can be detected.
+    id: Uuid,
+    /// Seconds since the UNIX epoch when the event happened.
+    timestamp: u64,
+    #[serde(flatten)]
+    event: WebhookEvent,
+}
+
+/// Signed event queued for a webhook URL.
+struct Delivery {
+    id: Uuid,
+    config: Arc<WebhookConfig>,
+    body: Arc<[u8]>,
+    signature: Arc<str>,
+}
+
+/// Hex-encoded public keys for an event.
+pub(crate) fn public_keys<T: AsRef<[u8]>>(
+    keys: impl IntoIterator<Item = T>,
+) -> Vec<String> {
+    keys.into_iter().map(hex::encode).collect()
+}
+
+/// Queue an event for the webhooks.
+///
+/// Events are dropped when webhooks are not configured
+/// or the queue is full.
+pub(crate) async fn dispatch(state: &State, event: WebhookEvent) {
+    let queue = {
+        let reader = state.read().await;
+        reader.webhooks.clone()
+    };
+
+    if let Some(queue) = queue {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let message = WebhookMessage {
+            id: Uuid::new_v4(),
+            timestamp,
+            event,
+        };
+        if let Err(TrySendError::Full(message)) =
+            queue.try_send(message)
+        {
+            tracing::warn!(
+                id = %message.id,
+                "webhook queue is full, event dropped",
+            );
+        }
+    }
+}
+
+/// Sign queued events and forward them to the queue
+/// for each webhook URL.
+///
+/// The configuration is read for each event so that
+/// changes to the URLs and secret apply after a reload,
+/// the task for a URL stops when it is removed.
+pub(crate) async fn deliver(
+    state: State,
+    mut queue: mpsc::Receiver<WebhookMessage>,
+) {
+    let client = Client::new();
+    let mut queues: HashMap<Url, mpsc::Sender<Delivery>> =
+        HashMap::new();
+    while let Some(message) = queue.recv().await {
+        let config = {
+            let reader = state.read().await;
+            reader.config.webhooks.clone()
+        };
+        let config = match config {
+            Some(config) => Arc::new(config),
+            None => {
+                queues.clear();
+                continue;
+            }
+        };
+        queues.retain(|url, _| config.urls.contains(url));
+
+        let body: Arc<[u8]> = match serde_json::to_vec(&message) {
+            Ok(body) => body.into(),
+            Err(e) => {
+                tracing::error!(error = %e, "webhook event");
+                continue;
+            }
+        };
+        let signature: Arc<str> =
+            sign(config.secret.as_bytes(), &body).into();
+
+        for url in &config.urls {
+            let queue =
+                queues.entry(url.clone()).or_insert_with(|| {
+                    let (tx, rx) =
+                        mpsc::channel(config.queue_size.max(1));
+                    tokio::task::spawn(deliver_url(
+                        client.clone(),
+                        url.clone(),
+                        rx,
+                    ));
+                    tx
+                });
+            let delivery = Delivery {
+                id: message.id,
+                config: Arc::clone(&config),
+                body: Arc::clone(&body),
+                signature: Arc::clone(&signature),
+            };
+            if let Err(TrySendError::Full(delivery)) =
+                queue.try_send(delivery)
+            {
+                tracing::warn!(
+                    url = %url,
+                    id = %delivery.id,
+                    "webhook queue is full, event dropped",
+                );
+            }
+        }
+    }
+}
+
+/// Send queued events to a webhook URL in order.
+async fn deliver_url(
+    client: Client<HttpConnector>,
+    url: Url,
+    mut queue: mpsc::Receiver<Delivery>,
+) {
+    while let Some(delivery) = queue.recv().await {
+        send(
+            &client,
+            &delivery.config,
+            &url,
+            &delivery.body,
+            &delivery.signature,
+        )
+        .await;
+    }
+}
+
+/// Send an event to a URL retrying failed requests.
+async fn send(
+    client: &Client<HttpConnector>,
+    config: &WebhookConfig,
+    url: &Url,
+    body: &[u8],
+    signature: &str,
+) {
+    let timeout = Duration::from_secs(config.timeout);
+    let mut delay = RETRY_DELAY;
+    for attempt in 1..=config.max_attempts {
+        match post(client, url, body, signature, timeout).await {
+            Ok(_) => return,
+            Err(e) => {
+                tracing::warn!(
+                    url = %url,
+                    attempt = attempt,
+                    error = %e,
+                    "webhook request failed",
+                );
+            }
+        }
+
+        if attempt < config.max_attempts {
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+    tracing::error!(url = %url, "webhook event dropped");
+}
+
+/// Post an event to a URL.
+async fn post(
+    client: &Client<HttpConnector>,
+    url: &Url,
+    body: &[u8],
+    signature: &str,
+    timeout: Duration,
+) -> Result<()> {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(url.as_str())
+        .header(CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature)
+        .body(Body::from(body.to_vec()))?;
+    let response =
+        tokio::time::timeout(timeout, client.request(request))
+            .await
+            .map_err(|_| Error::WebhookTimeout)??;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(Error::WebhookStatus(response.status()))
+    }
+}
+
+/// Signature for the body of an event.
+fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+        .expect("HMAC accepts keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
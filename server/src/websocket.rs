@@ -1,9 +1,12 @@
 use axum::{
+    async_trait,
     extract::{
+        rejection::ExtensionRejection,
         ws::{Message, WebSocket, WebSocketUpgrade},
-        Extension, Query,
+        ConnectInfo, Extension, FromRequestParts, Query, TypedHeader,
     },
-    http::StatusCode,
+    headers::{authorization::Bearer, Authorization},
+    http::{request::Parts, HeaderMap, StatusCode},
     response::Response,
 };
 use futures::{
@@ -13,14 +16,20 @@ use futures::{
 
 use serde::Deserialize;
 
-use std::{fmt, sync::Arc};
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::sync::{mpsc, RwLock};
 
 //use axum_macros::debug_handler;
 
 use crate::{
     server::{Service, State},
-    Result,
+    tls::PeerCertificate,
+    Claims, DuplicateConnectionPolicy, Result, ServerConfig,
 };
 use mpc_protocol::{
     hex,
@@ -37,6 +46,45 @@ pub struct WebSocketQuery {
     /// Public key offered by the client socket.
     #[serde(with = "hex::serde")]
     pub public_key: Vec<u8>,
+    /// Bearer token for clients that cannot set
+    /// the authorization header.
+    pub token: Option<String>,
+}
+
+/// Address of the peer for a connection.
+///
+/// Extracted from the extension added to the router for the
+/// Unix domain socket or from the connect info for a network
+/// address.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum PeerAddr {
+    /// Peer connected over the network.
+    Network(SocketAddr),
+    /// Peer connected on the Unix domain socket.
+    Unix,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for PeerAddr
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtensionRejection;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        if let Some(peer) = parts.extensions.get::<PeerAddr>() {
+            return Ok(*peer);
+        }
+        let ConnectInfo(addr) =
+            ConnectInfo::<SocketAddr>::from_request_parts(
+                parts, state,
+            )
+            .await?;
+        Ok(PeerAddr::Network(addr))
+    }
 }
 
 /// State for the websocket  connection for a single
@@ -46,6 +94,9 @@ pub struct WebSocketConnection {
     pub(crate) id: Uuid,
     /// User supplied public key.
     pub(crate) public_key: Vec<u8>,
+    /// Address of the client, clients on the Unix domain
+    /// socket do not have a network address.
+    pub(crate) addr: Option<IpAddr>,
     /// Outoing channel for messages sent to clients.
     pub(crate) outgoing: mpsc::Sender<Message>,
     // Incoming channel for messages received from clients.
@@ -57,6 +108,13 @@ pub struct WebSocketConnection {
     /// so we move out of the option and convert to
     /// transport mode and then put it back.
     pub(crate) state: Option<ProtocolState>,
+    /// Time the last message was received from the client.
+    pub(crate) last_seen: Instant,
+    /// Claims when the client was authenticated with a token.
+    pub(crate) claims: Option<Claims>,
+    /// Secret that authorizes requests for a connection on
+    /// the HTTP transport.
+    pub(crate) secret: Option<String>,
 }
 
 impl fmt::Debug for WebSocketConnection {
@@ -79,16 +137,106 @@ impl WebSocketConnection {
 
 /// Upgrade to a websocket connection.
 //#[debug_handler]
+#[allow(clippy::too_many_arguments)]
 pub async fn upgrade(
     Extension(state): Extension<State>,
     Extension(service): Extension<Service>,
+    peer: PeerAddr,
+    certificate: Option<Extension<PeerCertificate>>,
     Query(query): Query<WebSocketQuery>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+    headers: HeaderMap,
     ws: WebSocketUpgrade,
 ) -> std::result::Result<Response, StatusCode> {
     tracing::debug!("websocket upgrade request");
 
+    let accepted = accept(
+        Arc::clone(&state),
+        peer,
+        certificate,
+        query,
+        bearer,
+        headers,
+    )
+    .await?;
+
+    Ok(ws.on_upgrade(move |socket| {
+        service.listen_socket(
+            Arc::clone(&accepted.conn),
+            accepted.service_reader,
+        );
+        handle_socket(
+            socket,
+            state,
+            accepted.conn,
+            accepted.outgoing_rx,
+            accepted.outgoing_tx,
+        )
+    }))
+}
+
+/// Connection accepted by the server and the channels
+/// for the transport.
+pub(crate) struct Accepted {
+    /// Pending connection.
+    pub(crate) conn: Connection,
+    /// Sender for messages to the client.
+    pub(crate) outgoing_tx: mpsc::Sender<Message>,
+    /// Receiver for messages to the client.
+    pub(crate) outgoing_rx: mpsc::Receiver<Message>,
+    /// Receiver for messages from the client that is
+    /// passed to the relay service.
+    pub(crate) service_reader: mpsc::Receiver<Vec<u8>>,
+}
+
+/// Check a client may connect and register a pending
+/// connection.
+///
+/// Shared by all the transports so that the same network,
+/// certificate, access list and token checks are applied.
+pub(crate) async fn accept(
+    state: State,
+    peer: PeerAddr,
+    certificate: Option<Extension<PeerCertificate>>,
+    query: WebSocketQuery,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+    headers: HeaderMap,
+) -> std::result::Result<Accepted, StatusCode> {
     let mut writer = state.write().await;
 
+    // Check network access lists, access to the Unix domain
+    // socket is controlled by the permissions for the socket
+    // file and forwarded addresses are not trusted
+    let client_addr = match peer {
+        PeerAddr::Network(addr) => {
+            let client_addr =
+                client_addr(&writer.config, addr.ip(), &headers);
+            if !writer.config.is_allowed_network(&client_addr) {
+                tracing::debug!(
+                    addr = %client_addr,
+                    "network denied",
+                );
+                return Err(StatusCode::FORBIDDEN);
+            }
+            Some(client_addr)
+        }
+        PeerAddr::Unix => None,
+    };
+
+    // Check the client certificate is bound to the public key,
+    // connections without TLS cannot be bound so they are
+    // refused when any TLS configuration requires a binding
+    let is_bound = match &certificate {
+        Some(Extension(certificate)) => {
+            certificate.is_allowed(&writer.config, &query.public_key)
+        }
+        None => !writer.config.is_public_key_bound(),
+    };
+    if !is_bound {
+        tracing::debug!("client certificate denied");
+        return Err(StatusCode::FORBIDDEN);
+    }
+
     // Check access lists
     if (writer.config.allow.is_some() || writer.config.deny.is_some())
         && !writer.config.is_allowed_access(&query.public_key)
@@ -96,6 +244,37 @@ pub async fn upgrade(
         return Err(StatusCode::FORBIDDEN);
     }
 
+    // Verify the bearer token
+    let claims = if let Some(auth) = &writer.config.auth {
+        let token = bearer
+            .as_ref()
+            .map(|TypedHeader(Authorization(bearer))| bearer.token())
+            .or(query.token.as_deref())
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+
+        let claims = auth.verify(token).map_err(|e| {
+            tracing::debug!(error = %e, "token verification failed");
+            StatusCode::UNAUTHORIZED
+        })?;
+
+        if claims.public_key != query.public_key {
+            return Err(StatusCode::FORBIDDEN);
+        }
+
+        Some(claims)
+    } else {
+        None
+    };
+
+    // Reject early when the public key is already connected
+    if matches!(
+        writer.config.duplicate_connection,
+        DuplicateConnectionPolicy::Reject
+    ) && writer.active.contains_key(&query.public_key)
+    {
+        return Err(StatusCode::CONFLICT);
+    }
+
     let pattern = if let Some(pattern) = &writer.config.pattern {
         pattern
     } else {
@@ -121,29 +300,67 @@ pub async fn upgrade(
     let conn = Arc::new(RwLock::new(WebSocketConnection {
         id,
         public_key: query.public_key,
+        addr: client_addr,
         outgoing: outgoing_tx.clone(),
         incoming,
         state: Some(protocol_state),
+        last_seen: Instant::now(),
+        claims,
+        secret: None,
     }));
-    let socket_conn = Arc::clone(&conn);
-    writer.pending.insert(id, conn);
-    drop(writer);
+    writer.pending.insert(id, Arc::clone(&conn));
 
-    let socket_state = Arc::clone(&state);
-    Ok(ws.on_upgrade(move |socket| {
-        service
-            .listen_socket(Arc::clone(&socket_conn), service_reader);
-        handle_socket(
-            socket,
-            socket_state,
-            socket_conn,
-            outgoing_rx,
-            outgoing_tx,
-        )
-    }))
+    Ok(Accepted {
+        conn,
+        outgoing_tx,
+        outgoing_rx,
+        service_reader,
+    })
 }
 
-async fn disconnect(state: State, conn: Connection) {
+/// Resolve the client address.
+///
+/// When the peer is a trusted proxy the `X-Forwarded-For`
+/// header is read from right to left and the first address
+/// that is not a trusted proxy is the client address.
+fn client_addr(
+    config: &ServerConfig,
+    peer: IpAddr,
+    headers: &HeaderMap,
+) -> IpAddr {
+    if !config.is_trusted_proxy(&peer) {
+        return peer;
+    }
+
+    let forwarded: Vec<IpAddr> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|value| value.trim().parse().ok())
+        .collect();
+
+    let mut client = peer;
+    for addr in forwarded.into_iter().rev() {
+        client = addr;
+        if !config.is_trusted_proxy(&addr) {
+            break;
+        }
+    }
+    client
+}
+
+/// Close a connection from the server side.
+pub(crate) async fn close(state: State, conn: Connection) {
+    let outgoing = {
+        let reader = conn.read().await;
+        reader.outgoing.clone()
+    };
+    let _ = outgoing.send(Message::Close(None)).await;
+    disconnect(state, conn).await;
+}
+
+pub(crate) async fn disconnect(state: State, conn: Connection) {
     let (id, public_key) = {
         let reader = conn.read().await;
         (reader.id, reader.public_key.clone())
@@ -151,7 +368,15 @@ async fn disconnect(state: State, conn: Connection) {
     tracing::debug!(public_key = ?hex::encode(&public_key), "disconnect");
     let mut writer = state.write().await;
     writer.pending.remove(&id);
-    writer.active.remove(&public_key);
+
+    // Only remove the active connection when it belongs to this
+    // socket, it may have been replaced by a newer connection
+    if matches!(
+        writer.active.get(&public_key),
+        Some(active) if Arc::ptr_eq(active, &conn))
+    {
+        writer.active.remove(&public_key);
+    }
 }
 
 async fn handle_socket(
@@ -175,6 +400,64 @@ async fn handle_socket(
         Arc::clone(&conn),
         outgoing_tx,
     ));
+    tokio::spawn(heartbeat(Arc::clone(&state), Arc::clone(&conn)));
+}
+
+/// Determine if a connection is still registered with the server.
+async fn is_connected(state: State, conn: Connection) -> bool {
+    let (id, public_key) = {
+        let reader = conn.read().await;
+        (reader.id, reader.public_key.clone())
+    };
+    let reader = state.read().await;
+    reader.pending.contains_key(&id)
+        || matches!(
+            reader.active.get(&public_key),
+            Some(active) if Arc::ptr_eq(active, &conn))
+}
+
+/// Send pings to a client and close the connection when no
+/// messages have been received within the idle timeout.
+pub(crate) async fn heartbeat(state: State, conn: Connection) {
+    loop {
+        let (interval, timeout) = {
+            let reader = state.read().await;
+            (
+                Duration::from_secs(reader.config.heartbeat.interval),
+                Duration::from_secs(reader.config.heartbeat.timeout),
+            )
+        };
+
+        tokio::time::sleep(interval).await;
+
+        if !is_connected(Arc::clone(&state), Arc::clone(&conn)).await
+        {
+            break;
+        }
+
+        let (last_seen, outgoing, public_key) = {
+            let reader = conn.read().await;
+            (
+                reader.last_seen,
+                reader.outgoing.clone(),
+                reader.public_key.clone(),
+            )
+        };
+
+        if last_seen.elapsed() > timeout {
+            tracing::debug!(
+                public_key = ?hex::encode(&public_key),
+                "idle timeout",
+            );
+            close(state, conn).await;
+            break;
+        }
+
+        if outgoing.send(Message::Ping(vec![])).await.is_err() {
+            disconnect(state, conn).await;
+            break;
+        }
+    }
 }
 
 async fn read(
@@ -189,9 +472,22 @@ async fn read(
     };
 
     while let Some(msg) = receiver.next().await {
+        if msg.is_ok() {
+            let mut writer = conn.write().await;
+            writer.last_seen = Instant::now();
+        }
+
         match msg {
             Ok(msg) => match msg {
                 Message::Text(_) => {}
+                // Browsers cannot send pings so web clients send
+                // empty frames which are answered with an empty
+                // frame to keep the connection alive
+                Message::Binary(buffer) if buffer.is_empty() => {
+                    let _ = outgoing_tx
+                        .send(Message::Binary(vec![]))
+                        .await;
+                }
                 Message::Binary(buffer) => {
                     if let Ok(inflated) = zlib::inflate(&buffer) {
                         tx.send(inflated).await?;
@@ -206,6 +502,7 @@ async fn read(
                 Message::Close(frame) => {
                     let _ =
                         outgoing_tx.send(Message::Close(frame)).await;
+                    disconnect(state, Arc::clone(&conn)).await;
                     return Ok(());
                 }
             },
@@ -215,6 +512,7 @@ async fn read(
             }
         }
     }
+    disconnect(state, conn).await;
     Ok(())
 }
 
@@ -232,3 +530,78 @@ async fn write(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::client_addr;
+    use crate::ServerConfig;
+    use anyhow::Result;
+    use axum::http::{HeaderMap, HeaderValue};
+    use std::net::IpAddr;
+
+    fn config(trusted_proxies: &[&str]) -> Result<ServerConfig> {
+        let trusted_proxies = trusted_proxies
+            .iter()
+            .map(|n| n.parse())
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(ServerConfig {
+            trusted_proxies: Some(trusted_proxies),
+            ..Default::default()
+        })
+    }
+
+    fn forwarded(values: &[&'static str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(
+                "x-forwarded-for",
+                HeaderValue::from_static(value),
+            );
+        }
+        headers
+    }
+
+    #[test]
+    fn client_addr_untrusted_peer() -> Result<()> {
+        let config = config(&["10.0.0.0/8"])?;
+        let peer: IpAddr = "192.168.1.1".parse()?;
+        let headers = forwarded(&["203.0.113.7"]);
+        assert_eq!(peer, client_addr(&config, peer, &headers));
+        Ok(())
+    }
+
+    #[test]
+    fn client_addr_trusted_hops() -> Result<()> {
+        let config = config(&["10.0.0.0/8"])?;
+        let peer: IpAddr = "10.0.0.1".parse()?;
+
+        // Trusted hops are skipped from right to left
+        let headers =
+            forwarded(&["198.51.100.1, 203.0.113.7", "10.0.0.2"]);
+        assert_eq!(
+            "203.0.113.7".parse::<IpAddr>()?,
+            client_addr(&config, peer, &headers),
+        );
+
+        // Spoofed addresses left of an untrusted hop are ignored
+        let headers = forwarded(&["10.0.0.3, 203.0.113.7, 10.0.0.2"]);
+        assert_eq!(
+            "203.0.113.7".parse::<IpAddr>()?,
+            client_addr(&config, peer, &headers),
+        );
+
+        // All hops trusted uses the left-most address
+        let headers = forwarded(&["10.0.0.3, 10.0.0.2"]);
+        assert_eq!(
+            "10.0.0.3".parse::<IpAddr>()?,
+            client_addr(&config, peer, &headers),
+        );
+
+        // Missing header uses the peer address
+        assert_eq!(
+            peer,
+            client_addr(&config, peer, &HeaderMap::new())
+        );
+        Ok(())
+    }
+}
@@ -1,29 +1,119 @@
 //! Start the websocket relay server.
 use anyhow::Result;
 use axum_server::Handle;
-use mpc_relay_server::{RelayServer, ServerConfig};
-use std::{net::SocketAddr, path::PathBuf, str::FromStr};
+use mpc_relay_server::{RelayServer, ServerConfig, UnixSocketConfig};
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+const BIND: &str = "0.0.0.0:7007";
 
 /// Run a web server.
 pub async fn run(
-    bind: String,
+    bind: Option<String>,
     config: PathBuf,
     interval: Option<u64>,
     session_timeout: Option<u64>,
+    unix: Option<PathBuf>,
+    unix_mode: Option<u32>,
 ) -> Result<()> {
-    let (mut config, keypair) = ServerConfig::load(&config).await?;
+    let overrides = Overrides {
+        interval,
+        session_timeout,
+        unix,
+        unix_mode,
+    };
+    let (server_config, keypair) =
+        load_config(&config, &overrides).await?;
+
+    // Only listen on the configured listeners, socket and
+    // QUIC endpoint unless an address was given
+    let configured_only = bind.is_none()
+        && (!server_config.listeners.is_empty()
+            || server_config.unix.is_some()
+            || server_config.quic.is_some());
+
+    let handle = Handle::new();
+    let addr = SocketAddr::from_str(bind.as_deref().unwrap_or(BIND))?;
+    let server = RelayServer::new(server_config, keypair);
+
+    #[cfg(unix)]
+    tokio::task::spawn(reload_on_hangup(
+        server.clone(),
+        config,
+        overrides,
+    ));
+
+    if configured_only {
+        server.start_listeners().await?;
+        return Ok(());
+    }
 
-    if let Some(interval) = interval {
+    server.start(addr, handle).await?;
+    Ok(())
+}
+
+/// Command line overrides for the server config.
+struct Overrides {
+    interval: Option<u64>,
+    session_timeout: Option<u64>,
+    unix: Option<PathBuf>,
+    unix_mode: Option<u32>,
+}
+
+/// Load the server config and apply command line overrides.
+async fn load_config(
+    config: &Path,
+    overrides: &Overrides,
+) -> Result<(ServerConfig, mpc_protocol::Keypair)> {
+    let (mut config, keypair) = ServerConfig::load(config).await?;
+
+    if let Some(interval) = overrides.interval {
         config.session.interval = interval;
     }
 
-    if let Some(session_timeout) = session_timeout {
+    if let Some(session_timeout) = overrides.session_timeout {
         config.session.timeout = session_timeout;
     }
 
-    let handle = Handle::new();
-    let addr = SocketAddr::from_str(&bind)?;
-    let server = RelayServer::new(config, keypair);
-    server.start(addr, handle).await?;
+    if let Some(path) = &overrides.unix {
+        let mode = config.unix.take().and_then(|unix| unix.mode);
+        config.unix = Some(UnixSocketConfig {
+            path: path.clone(),
+            mode,
+        });
+    }
+
+    if let (Some(unix), Some(mode)) =
+        (config.unix.as_mut(), overrides.unix_mode)
+    {
+        unix.mode = Some(mode);
+    }
+
+    Ok((config, keypair))
+}
+
+/// Reload the server config when the process receives SIGHUP.
+#[cfg(unix)]
+async fn reload_on_hangup(
+    server: RelayServer,
+    config: PathBuf,
+    overrides: Overrides,
+) -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut hangup = signal(SignalKind::hangup())?;
+    while hangup.recv().await.is_some() {
+        tracing::info!(path = ?config, "reload config");
+        match load_config(&config, &overrides).await {
+            Ok((server_config, _)) => {
+                if let Err(e) = server.reload(server_config).await {
+                    tracing::error!("{}", e);
+                }
+            }
+            Err(e) => tracing::error!("{}", e),
+        }
+    }
     Ok(())
 }
@@ -22,8 +22,36 @@
 //!
 //! ```no_run
 //! key = "server.pem"
+//! relay = "participants"
 //! ```
 //!
+//! Setting `relay = "participants"` restricts peer handshakes
+//! and messages to peers that share a session or meeting, when
+//! omitted peers are relayed between any connected peers.
+//!
+//! # Authentication
+//!
+//! To require clients to present a bearer token add an `[auth]`
+//! section with either a shared `secret` for HMAC (HS256) tokens
+//! or the path to a `jwks` file:
+//!
+//! ```no_run
+//! [auth]
+//! jwks = "keys.json"
+//! issuer = "https://auth.example.com"
+//! ```
+//!
+//! Tokens are verified using the `alg` declared by the key in
+//! the `jwks` file or the configured `algorithm` for keys that
+//! do not declare one, tokens whose header names a different
+//! algorithm are rejected.
+//!
+//! The token is read from the `Authorization` header or the
+//! `token` query string parameter and its claims must include
+//! the `public_key` (hex) of the connecting client; the optional
+//! `max_sessions` claim limits the number of sessions a client
+//! may own.
+//!
 //! # Server
 //!
 //! Start the relay websocket service:
@@ -31,6 +59,147 @@
 //! ```no_run
 //! mpc-relay server config.toml
 //! ```
+//!
+//! # Unix Socket
+//!
+//! To serve clients on the same host, for example a local
+//! reverse proxy, listen on a Unix domain socket instead of
+//! or in addition to the TCP address:
+//!
+//! ```no_run
+//! [unix]
+//! path = "relay.sock"
+//! mode = 0o660
+//! ```
+//!
+//! The socket path and permissions can also be set with the
+//! `--unix` and `--unix-mode` options.
+//!
+//! Access to the socket is controlled by its permissions, the
+//! network access lists do not apply to connections on the
+//! socket and their `X-Forwarded-For` headers are ignored.
+//!
+//! # Listeners
+//!
+//! To listen on several addresses declare each listener with
+//! its own TLS settings and optionally the routes it serves,
+//! for example TLS on a public interface and plain HTTP on
+//! localhost for health checks:
+//!
+//! ```no_run
+//! [[listeners]]
+//! bind = "0.0.0.0:443"
+//! tls = { cert = "cert.pem", key = "key.pem" }
+//!
+//! [[listeners]]
+//! bind = "127.0.0.1:7007"
+//! routes = ["public-key"]
+//! ```
+//!
+//! When listeners, a Unix socket or a QUIC endpoint are
+//! configured the server only listens on the `--bind` address
+//! if it is given.
+//!
+//! # HTTP Transport
+//!
+//! Clients on networks that block websockets can use the HTTP
+//! transport, a `GET` request to `/http` with the same query
+//! string as the websocket endpoint opens a server-sent event
+//! stream for response messages and request messages are sent
+//! in `POST` requests to `/http/{id}` using the connection
+//! identifier from the first event on the stream. That event
+//! also carries a secret for the connection which must be
+//! sent as a bearer token in the `Authorization` header of
+//! every `POST` and `DELETE` request. Listeners
+//! that list their routes only serve this transport when the
+//! routes include `http`. Browsers use `EventSource` and
+//! `fetch` for this transport so the page origin must be
+//! included in the CORS origins.
+//!
+//! # QUIC
+//!
+//! For lower latency the server can also accept QUIC
+//! connections on a UDP address, QUIC connections are always
+//! encrypted so the endpoint requires its own TLS settings:
+//!
+//! ```no_run
+//! [quic]
+//! bind = "0.0.0.0:7008"
+//! tls = { cert = "cert.pem", key = "key.pem" }
+//! ```
+//!
+//! Clients connect using a `quic://` server URL.
+//!
+//! # Client Certificates
+//!
+//! To require clients to present a certificate signed by a
+//! CA bundle add `client-ca` to the TLS settings, set
+//! `bind-public-key` to also require the certificate common
+//! name or a subject alternative name to be the hex-encoded
+//! public key of the client:
+//!
+//! ```no_run
+//! [tls]
+//! cert = "cert.pem"
+//! key = "key.pem"
+//! client-ca = "clients.pem"
+//! bind-public-key = true
+//! ```
+//!
+//! While any TLS settings bind public keys connections without
+//! a client certificate are refused, including connections to
+//! listeners without TLS and the Unix domain socket. Reloading
+//! the configuration applies changes to the client CA bundles
+//! and public key bindings.
+//!
+//! # Webhooks
+//!
+//! To let a backend know when meetings are ready and when
+//! sessions are created, become active, time out, finish or
+//! expire configure webhook URLs and a shared secret:
+//!
+//! ```no_run
+//! [webhooks]
+//! urls = ["http://127.0.0.1:8080/relay-events"]
+//! secret = "change-me"
+//! ```
+//!
+//! Each event is a JSON object with an `event` name, an `id`
+//! and a `timestamp` along with the meeting or session
+//! identifier and participant public keys, message payloads
+//! are never sent. The `X-Relay-Signature` header contains
+//! `sha256=` followed by the hex-encoded HMAC-SHA256 of the
+//! request body using the secret. Failed requests are retried
+//! with the same `id` up to `max-attempts` times. Each URL
+//! has its own queue so a slow webhook does not delay the
+//! others, events for a URL are dropped when more than
+//! `queue-size` are waiting.
+//!
+//! # Reload
+//!
+//! Send `SIGHUP` to reload the access lists, CORS origins,
+//! session timeouts, webhook URLs and TLS certificate from the
+//! configuration file without restarting the server:
+//!
+//! ```no_run
+//! kill -HUP $(pidof mpc-relay)
+//! ```
+//!
+//! Set `disconnect_denied = true` to disconnect clients that
+//! are no longer allowed access after a reload.
+//!
+//! # Network Access
+//!
+//! Restrict the networks that may connect using CIDR lists, when
+//! running behind a reverse proxy add the proxy network to
+//! `trusted_proxies` so the client address is read from the
+//! `X-Forwarded-For` header:
+//!
+//! ```no_run
+//! allow_networks = ["10.0.0.0/16", "192.0.2.0/24"]
+//! deny_networks = ["10.0.99.0/24"]
+//! trusted_proxies = ["10.0.0.10/32"]
+//! ```
 
 #[doc(hidden)]
 #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
@@ -71,8 +240,9 @@ mod cli {
 
         /// Start a relay websocket service.
         Server {
-            /// Override the interval to poll for expired sessions in seconds.
-            #[clap(long)]
+            /// Override the maximum interval between checks for
+            /// expired sessions in seconds.
+            #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
             session_interval: Option<u64>,
 
             /// Override the default session timeout in seconds.
@@ -80,14 +250,33 @@ mod cli {
             session_timeout: Option<u64>,
 
             /// Bind to host:port.
-            #[clap(short, long, default_value = "0.0.0.0:7007")]
-            bind: String,
+            ///
+            /// Defaults to 0.0.0.0:7007 unless listeners,
+            /// a Unix domain socket or a QUIC endpoint are
+            /// configured.
+            #[clap(short, long)]
+            bind: Option<String>,
+
+            /// Listen on a Unix domain socket at this path.
+            #[clap(long)]
+            unix: Option<PathBuf>,
+
+            /// Octal permissions for the Unix domain socket.
+            #[clap(long, value_parser = parse_mode)]
+            unix_mode: Option<u32>,
 
             /// Config file to load.
             config: PathBuf,
         },
     }
 
+    /// Parse octal permissions for a file.
+    fn parse_mode(
+        value: &str,
+    ) -> std::result::Result<u32, std::num::ParseIntError> {
+        u32::from_str_radix(value.trim_start_matches("0o"), 8)
+    }
+
     pub(super) async fn run() -> Result<()> {
         let args = RelayServer::parse();
         match args.cmd {
@@ -105,6 +294,8 @@ mod cli {
                 session_interval,
                 session_timeout,
                 bind,
+                unix,
+                unix_mode,
                 config,
             } => {
                 commands::server::run(
@@ -112,6 +303,8 @@ mod cli {
                     config,
                     session_interval,
                     session_timeout,
+                    unix,
+                    unix_mode,
                 )
                 .await?
             }
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use futures::StreamExt;
+use mpc_client::{Error, Event, NetworkTransport};
+use mpc_protocol::{generate_keypair, http::StatusCode};
+use std::time::Duration;
+
+use crate::test_utils::auth_token;
+
+/// Rejects connections without a valid token for the
+/// public key of the client.
+#[tokio::test]
+async fn integration_auth_token_rejected() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server = auth_token::spawn().await?;
+    let keypair = generate_keypair()?;
+
+    // Missing token
+    let result =
+        auth_token::connect(&server, keypair.clone(), None).await;
+    assert!(matches!(
+        result,
+        Err(Error::ConnectError(StatusCode::UNAUTHORIZED, _))
+    ));
+
+    // Invalid token
+    let result = auth_token::connect(
+        &server,
+        keypair.clone(),
+        Some("not-a-token".to_owned()),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ConnectError(StatusCode::UNAUTHORIZED, _))
+    ));
+
+    // Expired token
+    let token = auth_token::token(keypair.public_key(), -3600, None)?;
+    let result =
+        auth_token::connect(&server, keypair.clone(), Some(token))
+            .await;
+    assert!(matches!(
+        result,
+        Err(Error::ConnectError(StatusCode::UNAUTHORIZED, _))
+    ));
+
+    // Token for another public key
+    let other = generate_keypair()?;
+    let token = auth_token::token(other.public_key(), 3600, None)?;
+    let result =
+        auth_token::connect(&server, keypair, Some(token)).await;
+    assert!(matches!(
+        result,
+        Err(Error::ConnectError(StatusCode::FORBIDDEN, _))
+    ));
+
+    Ok(())
+}
+
+/// Limits the number of sessions owned by a client to the
+/// maximum in the token claims.
+#[tokio::test]
+async fn integration_auth_token_max_sessions() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server = auth_token::spawn().await?;
+    let keypair = generate_keypair()?;
+    let participant = generate_keypair()?;
+    let token =
+        auth_token::token(keypair.public_key(), 3600, Some(1))?;
+    let (mut client, event_loop) =
+        auth_token::connect(&server, keypair, Some(token)).await?;
+    client.connect().await?;
+
+    let participants = vec![participant.public_key().to_vec()];
+    let mut s = event_loop.run();
+    let limited =
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while let Some(event) = s.next().await {
+                match event {
+                    Ok(Event::ServerConnected { .. }) => {
+                        client
+                            .new_session(
+                                participants.clone(),
+                                None,
+                                Default::default(),
+                            )
+                            .await?;
+                    }
+                    Ok(Event::SessionCreated(_)) => {
+                        client
+                            .new_session(
+                                participants.clone(),
+                                None,
+                                Default::default(),
+                            )
+                            .await?;
+                    }
+                    Err(Error::RateLimited(_)) => return Ok(true),
+                    Err(e) => return Err(e.into()),
+                    _ => {}
+                }
+            }
+            Ok::<_, anyhow::Error>(false)
+        })
+        .await??;
+    assert!(limited);
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use anyhow::Result;
+use futures::StreamExt;
+use mpc_client::{Error, Event, NetworkTransport};
+use mpc_protocol::http::StatusCode;
+use std::time::Duration;
+
+use crate::test_utils::{
+    config_reload, new_client, new_client_with_keypair,
+};
+
+/// Reloads the access lists and disconnects the client
+/// that is no longer allowed access.
+#[tokio::test]
+async fn integration_config_reload() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let (server, spawned) = config_reload::spawn().await?;
+    let url = spawned.url();
+    let server_public_key = spawned.public_key().to_vec();
+
+    let (mut denied, event_loop_d, denied_keypair) =
+        new_client::<anyhow::Error>(&url, server_public_key.clone())
+            .await?;
+    let (mut allowed, event_loop_a, _) =
+        new_client::<anyhow::Error>(&url, server_public_key.clone())
+            .await?;
+    denied.connect().await?;
+    allowed.connect().await?;
+
+    let mut s_d = event_loop_d.run();
+    let mut s_a = event_loop_a.run();
+    config_reload::wait_connected(&mut s_d).await?;
+    config_reload::wait_connected(&mut s_a).await?;
+
+    let config =
+        config_reload::deny(denied_keypair.public_key()).await?;
+    server.reload(config).await?;
+
+    // Denied client is disconnected
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        config_reload::wait_closed(&mut s_d),
+    )
+    .await??;
+
+    // Denied client cannot connect again
+    let result = new_client_with_keypair::<Error>(
+        &url,
+        server_public_key,
+        denied_keypair,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ConnectError(StatusCode::FORBIDDEN, _))
+    ));
+
+    // Allowed client is still served
+    allowed.list_sessions().await?;
+    let event = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(event) = s_a.next().await {
+            if let Event::SessionList(_) = event? {
+                return Ok::<_, anyhow::Error>(true);
+            }
+        }
+        Ok(false)
+    })
+    .await??;
+    assert!(event);
+
+    Ok(())
+}
+
+/// Reloading a configuration that fails to load the QUIC
+/// certificate leaves the server certificate untouched.
+#[tokio::test]
+async fn integration_config_reload_tls() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let dir = std::env::temp_dir()
+        .join(format!("mpc-relay-reload-tls-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+
+    let (server, spawned, root) =
+        config_reload::spawn_tls(&dir).await?;
+    let config = config_reload::missing_quic_cert(&dir).await?;
+    assert!(server.reload(config).await.is_err());
+
+    // Clients trusting the original certificate still connect
+    let (mut client, event_loop) =
+        config_reload::new_tls_client(&spawned, root).await?;
+    client.connect().await?;
+    let mut s = event_loop.run();
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        config_reload::wait_connected(&mut s),
+    )
+    .await??;
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use crate::test_utils::{
+    duplicate_connection, server_public_key, spawn_server, SERVER,
+};
+use anyhow::Result;
+use serial_test::serial;
+
+/// Creates a client that handshakes with the server and then
+/// connects a second client using the same keypair; the first
+/// client should be notified that the connection was replaced.
+#[tokio::test]
+#[serial]
+async fn integration_duplicate_connection() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    // Wait for the server to start
+    let (rx, _handle) = spawn_server()?;
+    let _ = rx.await?;
+
+    let server_public_key = server_public_key().await?;
+    duplicate_connection::run(SERVER, server_public_key).await?;
+
+    Ok(())
+}
+
+/// Rejects a second connection that uses the same keypair
+/// both when upgrading and when promoting a pending handshake.
+#[tokio::test]
+async fn integration_duplicate_connection_reject() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server = duplicate_connection::spawn_reject().await?;
+    duplicate_connection::run_reject(&server).await?;
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use crate::test_utils::embedded_router;
+use anyhow::Result;
+
+/// Mounts the relay router under a path prefix in an
+/// application and connects a client using the prefixed URL.
+#[tokio::test]
+async fn integration_embedded_router() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    embedded_router::run().await?;
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use crate::test_utils::{ephemeral_server, socket_close};
+use anyhow::Result;
+use std::time::Duration;
+
+/// Spawns isolated servers on ephemeral ports and connects
+/// a client to each of them.
+///
+/// Does not need to run serially as the servers do not
+/// share a port with the other tests.
+#[tokio::test]
+async fn integration_ephemeral_server() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server_1 = ephemeral_server::spawn().await?;
+    let server_2 = ephemeral_server::spawn().await?;
+
+    assert_ne!(0, server_1.addr().port());
+    assert_ne!(server_1.addr(), server_2.addr());
+
+    for server in [&server_1, &server_2] {
+        socket_close::run(
+            &server.url(),
+            server.public_key().to_vec(),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Spawning a server with TLS files that cannot be loaded
+/// returns the error rather than waiting to listen.
+#[tokio::test]
+async fn integration_ephemeral_server_missing_tls() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(5),
+        ephemeral_server::spawn_missing_tls(),
+    )
+    .await?;
+    assert!(matches!(result, Err(mpc_relay_server::Error::Io(_))));
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use crate::test_utils::{
+    error_code, server_public_key, spawn_server, SERVER,
+};
+use anyhow::Result;
+use serial_test::serial;
+
+/// Creates a client that attempts to join a meeting that
+/// does not exist and asserts on the typed error.
+#[tokio::test]
+#[serial]
+async fn integration_error_code() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    // Wait for the server to start
+    let (rx, _handle) = spawn_server()?;
+    let _ = rx.await?;
+
+    let server_public_key = server_public_key().await?;
+    error_code::run(SERVER, server_public_key).await?;
+
+    Ok(())
+}
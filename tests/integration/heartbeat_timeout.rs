@@ -0,0 +1,13 @@
+use crate::test_utils::heartbeat_timeout;
+use anyhow::Result;
+
+/// Checks the server closes connections that stop answering
+/// pings for longer than the heartbeat timeout.
+#[tokio::test]
+async fn integration_heartbeat_timeout() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server = heartbeat_timeout::spawn().await?;
+    heartbeat_timeout::run(&server).await?;
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use crate::test_utils::{http_channel, http_transport};
+use anyhow::Result;
+
+/// Connects a websocket client and a client using the
+/// HTTP transport to the same server and exchanges
+/// messages between them over the peer channel.
+#[tokio::test]
+async fn integration_http_transport() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server = http_transport::spawn().await?;
+    http_transport::run(&server).await?;
+    Ok(())
+}
+
+/// Exchanges messages between a websocket client and a
+/// client using the HTTP transport then closes the HTTP
+/// connection.
+#[tokio::test]
+async fn integration_http_channel() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server = http_transport::spawn().await?;
+    http_channel::run(
+        &server.url(),
+        &format!("http://{}", server.addr()),
+        server.public_key().to_vec(),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Requires the secret from the connect event for requests
+/// to a connection and skips malformed frames.
+#[tokio::test]
+async fn integration_http_secret() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server = http_transport::spawn().await?;
+    http_transport::run_secret(&server).await?;
+    Ok(())
+}
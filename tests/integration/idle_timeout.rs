@@ -0,0 +1,23 @@
+use crate::test_utils::{
+    idle_timeout, server_public_key, spawn_server, SERVER,
+};
+use anyhow::Result;
+use serial_test::serial;
+
+/// Creates a client with an idle timeout shorter than the
+/// server heartbeat interval and waits for the connection
+/// lost event.
+#[tokio::test]
+#[serial]
+async fn integration_idle_timeout() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    // Wait for the server to start
+    let (rx, _handle) = spawn_server()?;
+    let _ = rx.await?;
+
+    let server_public_key = server_public_key().await?;
+    idle_timeout::run(SERVER, server_public_key).await?;
+
+    Ok(())
+}
@@ -1,23 +1,101 @@
 #[cfg(not(target_arch = "wasm32"))]
 mod test_utils;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod auth_token;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod config_reload;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod duplicate_connection;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod embedded_router;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod ephemeral_server;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod error_code;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod gg20;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod heartbeat_timeout;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod http_transport;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod idle_timeout;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod meeting_cancel;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod meeting_encrypt;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod meeting_join_code;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod meeting_point;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod meeting_signature;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod meeting_slots;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod meeting_verify;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod multiple_listeners;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod mutual_tls;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod peer_channel;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod peer_message_size;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod quic_transport;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod relay_policy;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod session_broadcast;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod session_expiring;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod session_handshake;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod session_limits;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod session_query;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod session_quorum;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod session_timeout;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod socket_close;
+
+#[cfg(unix)]
+mod unix_socket;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod webhooks;
@@ -0,0 +1,23 @@
+use anyhow::Result;
+use serial_test::serial;
+
+use crate::test_utils::{
+    meeting_cancel, server_public_key, spawn_server, SERVER,
+};
+
+/// Creates a meeting point, joins a participant and then
+/// the owner cancels the meeting.
+#[tokio::test]
+#[serial]
+async fn integration_meeting_cancel() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    // Wait for the server to start
+    let (rx, _handle) = spawn_server()?;
+    let _ = rx.await?;
+
+    let server_public_key = server_public_key().await?;
+    meeting_cancel::run(SERVER, server_public_key).await?;
+
+    Ok(())
+}
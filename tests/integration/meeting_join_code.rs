@@ -0,0 +1,23 @@
+use anyhow::Result;
+use serial_test::serial;
+
+use crate::test_utils::{
+    meeting_join_code, server_public_key, spawn_server, SERVER,
+};
+
+/// Creates a meeting point with invitations and checks
+/// that a participant must use a valid join code.
+#[tokio::test]
+#[serial]
+async fn integration_meeting_join_code() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    // Wait for the server to start
+    let (rx, _handle) = spawn_server()?;
+    let _ = rx.await?;
+
+    let server_public_key = server_public_key().await?;
+    meeting_join_code::run(SERVER, server_public_key).await?;
+
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use anyhow::Result;
+use serial_test::serial;
+
+use crate::test_utils::{
+    meeting_signature, server_public_key, spawn_server, SERVER,
+};
+
+/// Creates a meeting point with a slot bound to a verifying
+/// key and checks the participant must sign the join request.
+#[tokio::test]
+#[serial]
+async fn integration_meeting_signature() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    // Wait for the server to start
+    let (rx, _handle) = spawn_server()?;
+    let _ = rx.await?;
+
+    let server_public_key = server_public_key().await?;
+    meeting_signature::run(SERVER, server_public_key).await?;
+
+    Ok(())
+}
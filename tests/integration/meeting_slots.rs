@@ -0,0 +1,20 @@
+use anyhow::Result;
+use std::time::Duration;
+
+use crate::test_utils::meeting_slots;
+
+/// Rejects joins to meeting slots that have been filled by
+/// another participant and join codes for meetings that use
+/// invitations, then rejoins an invitation slot after leaving.
+#[tokio::test]
+async fn integration_meeting_slots() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server = meeting_slots::spawn().await?;
+    tokio::time::timeout(
+        Duration::from_secs(10),
+        meeting_slots::run(&server),
+    )
+    .await??;
+    Ok(())
+}
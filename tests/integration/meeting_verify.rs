@@ -0,0 +1,20 @@
+use anyhow::Result;
+use std::time::Duration;
+
+use crate::test_utils::meeting_verify;
+
+/// Joins a meeting slot bound to a verifying key with the
+/// driver which signs the join request and verifies the
+/// signatures of the participants.
+#[tokio::test]
+async fn integration_meeting_verify() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server = meeting_verify::spawn().await?;
+    tokio::time::timeout(
+        Duration::from_secs(10),
+        meeting_verify::run(&server),
+    )
+    .await??;
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use crate::test_utils::{
+    multiple_listeners::{self, INTERNAL, PUBLIC},
+    new_client, socket_close,
+};
+use anyhow::Result;
+use mpc_protocol::{hex, http::StatusCode};
+use std::time::Duration;
+
+/// Serves a listener with all routes alongside a listener
+/// that only serves the public key.
+#[tokio::test]
+async fn integration_multiple_listeners() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server = multiple_listeners::spawn().await?;
+    let server_public_key = server.public_key().to_vec();
+
+    socket_close::run(
+        &format!("ws://{}", PUBLIC),
+        server_public_key.clone(),
+    )
+    .await?;
+
+    let public_key = multiple_listeners::public_key(INTERNAL).await?;
+    assert_eq!(hex::encode(&server_public_key), public_key);
+
+    // Websocket route is not served by the internal listener
+    let result = new_client::<mpc_client::Error>(
+        &format!("ws://{}", INTERNAL),
+        server_public_key,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(mpc_client::Error::ConnectError(
+            StatusCode::NOT_FOUND,
+            _
+        ))
+    ));
+
+    Ok(())
+}
+
+/// Spawning a server with a listener whose TLS files cannot
+/// be loaded returns the error rather than waiting to listen.
+#[tokio::test]
+async fn integration_multiple_listeners_missing_tls() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(5),
+        multiple_listeners::spawn_missing_tls(),
+    )
+    .await?;
+    assert!(matches!(result, Err(mpc_relay_server::Error::Io(_))));
+
+    Ok(())
+}
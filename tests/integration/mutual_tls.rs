@@ -0,0 +1,145 @@
+use crate::test_utils::mutual_tls::{self, Authority};
+use anyhow::Result;
+use futures::StreamExt;
+use mpc_client::{Event, NetworkTransport};
+use mpc_protocol::{generate_keypair, http::StatusCode};
+
+/// Requires clients to present a certificate bound to
+/// their public key.
+#[tokio::test]
+async fn integration_mutual_tls() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let authority = Authority::new()?;
+    let server = authority.spawn().await?;
+
+    // Certificate bound to the public key of the client
+    let keypair = generate_keypair()?;
+    let identity =
+        authority.identity(&mutual_tls::common_name(&keypair))?;
+    let (mut client, event_loop) = mutual_tls::new_client(
+        &server,
+        keypair,
+        authority.root()?,
+        Some(identity),
+    )
+    .await?;
+    client.connect().await?;
+
+    let mut connected = false;
+    let mut s = event_loop.run();
+    while let Some(event) = s.next().await {
+        let event = event?;
+        match &event {
+            Event::ServerConnected { .. } => {
+                connected = true;
+                client.close().await?;
+            }
+            Event::Close => {
+                break;
+            }
+            _ => {}
+        }
+    }
+    assert!(connected);
+
+    // Certificate bound to another public key
+    let other = generate_keypair()?;
+    let identity =
+        authority.identity(&mutual_tls::common_name(&other))?;
+    let result = mutual_tls::new_client(
+        &server,
+        generate_keypair()?,
+        authority.root()?,
+        Some(identity),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(mpc_client::Error::ConnectError(
+            StatusCode::FORBIDDEN,
+            _
+        ))
+    ));
+
+    // No client certificate
+    let result = mutual_tls::new_client(
+        &server,
+        generate_keypair()?,
+        authority.root()?,
+        None,
+    )
+    .await;
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+/// Refuses connections without a certificate while public
+/// keys are bound and applies a change to the binding when
+/// the configuration is reloaded.
+#[cfg(unix)]
+#[tokio::test]
+async fn integration_mutual_tls_bind_reload() -> Result<()> {
+    use crate::test_utils::unix_socket;
+    use mpc_relay_server::{RelayServer, UnixSocketConfig};
+
+    //crate::test_utils::init_tracing();
+
+    let is_forbidden = |e: &mpc_client::Error| {
+        matches!(
+            e,
+            mpc_client::Error::ConnectError(StatusCode::FORBIDDEN, _)
+        )
+    };
+
+    let authority = Authority::new()?;
+    let path = std::env::temp_dir()
+        .join(format!("mpc-relay-bind-{}.sock", std::process::id()));
+    let unix = UnixSocketConfig {
+        path: path.clone(),
+        mode: None,
+    };
+    let (mut config, keypair) = authority.config(true).await?;
+    config.unix = Some(unix.clone());
+    let public_key = keypair.public_key().to_vec();
+    let server = RelayServer::new(config, keypair);
+    let spawned = server.spawn("127.0.0.1:0".parse()?).await?;
+
+    // Unix domain socket connections have no certificate
+    let result =
+        unix_socket::new_client(&path, public_key.clone()).await;
+    let error = result.err().expect("unix client refused");
+    assert!(error
+        .downcast_ref::<mpc_client::Error>()
+        .map(is_forbidden)
+        .unwrap_or(false));
+
+    // Certificate bound to another public key
+    let identity = authority
+        .identity(&mutual_tls::common_name(&generate_keypair()?))?;
+    let result = mutual_tls::new_client(
+        &spawned,
+        generate_keypair()?,
+        authority.root()?,
+        Some(identity.clone()),
+    )
+    .await;
+    assert!(result.err().map(|e| is_forbidden(&e)).unwrap_or(false));
+
+    // Stop binding public keys
+    let (mut config, _) = authority.config(false).await?;
+    config.unix = Some(unix);
+    server.reload(config).await?;
+
+    unix_socket::new_client(&path, public_key).await?;
+    mutual_tls::new_client(
+        &spawned,
+        generate_keypair()?,
+        authority.root()?,
+        Some(identity),
+    )
+    .await?;
+
+    Ok(())
+}
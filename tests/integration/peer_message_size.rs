@@ -0,0 +1,19 @@
+use anyhow::Result;
+use std::time::Duration;
+
+use crate::test_utils::peer_message_size;
+
+/// Rejects messages relayed without a session that exceed
+/// the maximum message size.
+#[tokio::test]
+async fn integration_peer_message_size() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server = peer_message_size::spawn().await?;
+    tokio::time::timeout(
+        Duration::from_secs(10),
+        peer_message_size::run(&server),
+    )
+    .await??;
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use crate::test_utils::quic_transport;
+use anyhow::Result;
+
+/// Connects a websocket client and a client using the
+/// QUIC transport to the same server and exchanges
+/// messages between them over the peer channel.
+///
+/// Then relays messages between two clients using the
+/// QUIC transport on the streams for each peer which are
+/// delivered in order with the messages from the server.
+#[tokio::test]
+async fn integration_quic_transport() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let (server, root) = quic_transport::spawn().await?;
+    quic_transport::run(&server, root.clone()).await?;
+    quic_transport::run_peers(&server, root).await?;
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use anyhow::Result;
+use std::time::Duration;
+
+use crate::test_utils::relay_policy;
+
+/// Only relays handshakes, messages and session connections
+/// between peers that are participants in a session.
+#[tokio::test]
+async fn integration_relay_policy() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let server = relay_policy::spawn().await?;
+    tokio::time::timeout(
+        Duration::from_secs(10),
+        relay_policy::run(&server),
+    )
+    .await??;
+    Ok(())
+}
@@ -30,3 +30,28 @@ async fn integration_session_broadcast() -> Result<()> {
 
     Ok(())
 }
+
+/// Participants that do not join explicitly are counted as
+/// joined once connected when the session has no quorum.
+#[tokio::test]
+#[serial]
+async fn integration_session_broadcast_without_join() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    // Wait for the server to start
+    let (rx, _handle) = spawn_server()?;
+    let _ = rx.await?;
+
+    let server_public_key = server_public_key().await?;
+    let expected_result = vec![1u8, 1u8, 2u8, 2u8, 3u8, 3u8];
+    let session_result = session_broadcast::run_without_join(
+        SERVER,
+        server_public_key,
+    )
+    .await?;
+    let mut result = session_result.lock().await;
+    result.sort();
+    assert_eq!(expected_result, result.clone());
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use crate::test_utils::{
+    server_public_key, session_expiring, spawn_server, SERVER,
+};
+use anyhow::Result;
+use serial_test::serial;
+
+/// Creates a session with a short timeout so the
+/// initiator is warned before the session expires.
+#[tokio::test]
+#[serial]
+async fn integration_session_expiring() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    // Wait for the server to start
+    let (rx, _handle) = spawn_server()?;
+    let _ = rx.await?;
+
+    let server_public_key = server_public_key().await?;
+    let (session_id, expiring_id, expires_in) =
+        session_expiring::run(SERVER, server_public_key).await?;
+
+    assert_eq!(session_id, expiring_id);
+    assert!(expires_in <= 3);
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use crate::test_utils::{
+    server_public_key, session_limits, spawn_server, SERVER,
+};
+use anyhow::Result;
+use serial_test::serial;
+
+/// Creates a session with requested limits and checks
+/// the effective limits are clamped by the server.
+#[tokio::test]
+#[serial]
+async fn integration_session_limits() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    // Wait for the server to start
+    let (rx, _handle) = spawn_server()?;
+    let _ = rx.await?;
+
+    let server_public_key = server_public_key().await?;
+    let session =
+        session_limits::run(SERVER, server_public_key).await?;
+
+    assert_eq!(Some(3600), session.limits.timeout);
+    assert_eq!(Some(1), session.limits.wait_timeout);
+    assert_eq!(Some(1024), session.limits.max_message_size);
+    assert_eq!(Some(86400), session.limits.max_lifetime);
+
+    Ok(())
+}
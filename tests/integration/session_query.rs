@@ -0,0 +1,34 @@
+use crate::test_utils::{
+    server_public_key, session_query, spawn_server, SERVER,
+};
+use anyhow::Result;
+use serial_test::serial;
+
+/// Creates a session then queries the session status
+/// and lists the sessions for the initiator.
+#[tokio::test]
+#[serial]
+async fn integration_session_query() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    // Wait for the server to start
+    let (rx, _handle) = spawn_server()?;
+    let _ = rx.await?;
+
+    let server_public_key = server_public_key().await?;
+    let (initiator_key, status, sessions) =
+        session_query::run(SERVER, server_public_key).await?;
+
+    assert_eq!(2, status.state.all_participants.len());
+    assert_eq!(vec![initiator_key], status.connected);
+    assert!(status.connections.is_empty());
+    assert!(status.expires_in > 0);
+
+    assert_eq!(1, sessions.len());
+    assert_eq!(
+        status.state.session_id,
+        sessions.first().unwrap().state.session_id
+    );
+
+    Ok(())
+}
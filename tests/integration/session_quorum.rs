@@ -0,0 +1,52 @@
+use anyhow::Result;
+use serial_test::serial;
+
+use crate::test_utils::{
+    server_public_key, session_quorum, spawn_server, SERVER,
+};
+
+/// Uses a session quorum so the session becomes active with
+/// the participants that connected before the deadline.
+#[tokio::test]
+#[serial]
+async fn integration_session_quorum() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    // Wait for the server to start
+    let (rx, _handle) = spawn_server()?;
+    let _ = rx.await?;
+
+    let server_public_key = server_public_key().await?;
+    let completed =
+        session_quorum::run(SERVER, server_public_key).await?;
+    assert_eq!(2, completed.len());
+    for session in completed {
+        assert_eq!(3, session.all_participants.len());
+        assert_eq!(2, session.active_participants.len());
+    }
+
+    Ok(())
+}
+
+/// Notifies a participant that joins after the session has
+/// proceeded with a quorum that it was excluded.
+#[tokio::test]
+#[serial]
+async fn integration_session_quorum_excluded() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    // Wait for the server to start
+    let (rx, _handle) = spawn_server()?;
+    let _ = rx.await?;
+
+    let server_public_key = server_public_key().await?;
+    let (session_id, error) =
+        session_quorum::run_excluded(SERVER, server_public_key)
+            .await?;
+    assert!(matches!(
+        error,
+        mpc_driver::Error::ExcludedFromSession(id) if id == session_id
+    ));
+
+    Ok(())
+}
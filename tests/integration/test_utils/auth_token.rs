@@ -0,0 +1,63 @@
+use anyhow::Result;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use mpc_client::{Client, ClientOptions, Error, EventLoop};
+use mpc_protocol::Keypair;
+use mpc_relay_server::{
+    AuthConfig, Claims, RelayServer, ServerConfig, SpawnedServer,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Shared secret used to sign tokens.
+const SECRET: &str = "integration-test-secret";
+
+/// Spawn a relay server on an ephemeral port that requires
+/// tokens signed with the shared secret.
+pub async fn spawn() -> Result<SpawnedServer> {
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    let mut auth = AuthConfig::default();
+    auth.secret = Some(SECRET.to_owned());
+    config.auth = Some(auth);
+    let server = RelayServer::new(config, keypair);
+    Ok(server.spawn("127.0.0.1:0".parse()?).await?)
+}
+
+/// Create a token bound to a public key that expires after
+/// the given number of seconds, negative values create a
+/// token that has already expired.
+pub fn token(
+    public_key: &[u8],
+    expires_in: i64,
+    max_sessions: Option<usize>,
+) -> Result<String> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let claims = Claims {
+        sub: "user".to_owned(),
+        public_key: public_key.to_vec(),
+        exp: now.saturating_add_signed(expires_in),
+        max_sessions,
+    };
+    Ok(encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(SECRET.as_bytes()),
+    )?)
+}
+
+/// Connect a client to the server with an optional token.
+pub async fn connect(
+    server: &SpawnedServer,
+    keypair: Keypair,
+    token: Option<String>,
+) -> std::result::Result<(Client, EventLoop), Error> {
+    let options = ClientOptions {
+        keypair,
+        server_public_key: server.public_key().to_vec(),
+        pattern: None,
+        idle_timeout: None,
+        token,
+        tls: None,
+    };
+    let url = options.url(&server.url());
+    Client::new(&url, options).await
+}
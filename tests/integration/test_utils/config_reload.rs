@@ -0,0 +1,154 @@
+use anyhow::Result;
+use futures::StreamExt;
+use mpc_client::{
+    Client, ClientOptions, Event, EventLoop, EventStream, TlsOptions,
+};
+use mpc_protocol::{generate_keypair, hex};
+use mpc_relay_server::{
+    QuicConfig, RelayServer, ServerConfig, SpawnedServer, TlsConfig,
+};
+use rcgen::{Certificate, CertificateParams};
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+
+/// Spawn a relay server on an ephemeral port and keep the
+/// server so the configuration can be reloaded.
+pub async fn spawn() -> Result<(RelayServer, SpawnedServer)> {
+    let (config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    let server = RelayServer::new(config, keypair);
+    let addr: SocketAddr = "127.0.0.1:0".parse()?;
+    let spawned = server.spawn(addr).await?;
+    Ok((server, spawned))
+}
+
+/// Write and load a configuration that denies access to
+/// a public key and disconnects denied clients.
+pub async fn deny(public_key: &[u8]) -> Result<ServerConfig> {
+    let dir = std::env::temp_dir()
+        .join(format!("mpc-relay-reload-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let key: PathBuf =
+        std::env::current_dir()?.join("tests").join("test.pem");
+    let path = dir.join("config.toml");
+    std::fs::write(
+        &path,
+        format!(
+            r#"key = "{}"
+disconnect_denied = true
+deny = [{{ public_key = "{}" }}]
+"#,
+            key.display(),
+            hex::encode(public_key),
+        ),
+    )?;
+    let result = ServerConfig::load(&path).await;
+    std::fs::remove_dir_all(&dir)?;
+    Ok(result?.0)
+}
+
+/// Wait until the client has completed the server handshake.
+pub async fn wait_connected(s: &mut EventStream) -> Result<()> {
+    while let Some(event) = s.next().await {
+        if let Event::ServerConnected { .. } = event? {
+            return Ok(());
+        }
+    }
+    anyhow::bail!("event stream ended before server connected");
+}
+
+/// Wait until the server closes the connection.
+pub async fn wait_closed(s: &mut EventStream) -> Result<()> {
+    while let Some(event) = s.next().await {
+        if let Event::Close | Event::ConnectionLost = event? {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+/// Write a self-signed certificate for localhost to a
+/// directory and return the PEM-encoded certificate.
+fn write_cert(
+    dir: &Path,
+    name: &str,
+) -> Result<(TlsConfig, Vec<u8>)> {
+    let cert =
+        Certificate::from_params(CertificateParams::new(vec![
+            "localhost".to_owned(),
+        ]))?;
+    let root = cert.serialize_pem()?.into_bytes();
+    let tls = TlsConfig {
+        cert: dir.join(format!("{}-cert.pem", name)),
+        key: dir.join(format!("{}-key.pem", name)),
+        client_ca: None,
+        bind_public_key: false,
+    };
+    std::fs::write(&tls.cert, &root)?;
+    std::fs::write(&tls.key, cert.serialize_private_key_pem())?;
+    Ok((tls, root))
+}
+
+/// Spawn a relay server using TLS and a QUIC endpoint with
+/// the same certificate on ephemeral ports.
+///
+/// Returns the PEM-encoded certificate for the server.
+pub async fn spawn_tls(
+    dir: &Path,
+) -> Result<(RelayServer, SpawnedServer, Vec<u8>)> {
+    let (tls, root) = write_cert(dir, "server")?;
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    config.quic = Some(QuicConfig {
+        bind: "127.0.0.1:0".parse()?,
+        tls: tls.clone(),
+    });
+    config.tls = Some(tls);
+    let server = RelayServer::new(config, keypair);
+    let addr: SocketAddr = "127.0.0.1:0".parse()?;
+    let spawned = server.spawn(addr).await?;
+    Ok((server, spawned, root))
+}
+
+/// Configuration with a new certificate for the server and a
+/// missing certificate for the QUIC endpoint.
+pub async fn missing_quic_cert(dir: &Path) -> Result<ServerConfig> {
+    let (tls, _) = write_cert(dir, "reload")?;
+    let (mut config, _) =
+        ServerConfig::load("tests/config.toml").await?;
+    config.quic = Some(QuicConfig {
+        bind: "127.0.0.1:0".parse()?,
+        tls: TlsConfig {
+            cert: dir.join("missing-cert.pem"),
+            key: dir.join("missing-key.pem"),
+            client_ca: None,
+            bind_public_key: false,
+        },
+    });
+    config.tls = Some(tls);
+    Ok(config)
+}
+
+/// Create a client that trusts a root certificate connected
+/// to the server over TLS.
+pub async fn new_tls_client(
+    server: &SpawnedServer,
+    root: Vec<u8>,
+) -> mpc_client::Result<(Client, EventLoop)> {
+    let options = ClientOptions {
+        keypair: generate_keypair()?,
+        server_public_key: server.public_key().to_vec(),
+        pattern: None,
+        idle_timeout: None,
+        token: None,
+        tls: Some(TlsOptions {
+            roots: Some(root),
+            identity: None,
+        }),
+    };
+    let url = options
+        .url(&format!("wss://localhost:{}", server.addr().port()));
+    Client::new(&url, options).await
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use futures::StreamExt;
+use mpc_client::{Error, Event, NetworkTransport};
+use mpc_protocol::http::StatusCode;
+use mpc_relay_server::{
+    DuplicateConnectionPolicy, RelayServer, ServerConfig,
+    SpawnedServer,
+};
+
+use super::{new_client, new_client_with_keypair};
+
+pub async fn run(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<()> {
+    let (mut initiator, event_loop_i, keypair) =
+        new_client::<anyhow::Error>(
+            server,
+            server_public_key.clone(),
+        )
+        .await?;
+
+    initiator.connect().await?;
+
+    let mut replaced = false;
+    let mut s = event_loop_i.run();
+    while let Some(event) = s.next().await {
+        let event = event?;
+        match &event {
+            Event::ServerConnected { .. } => {
+                // Connect again using the same keypair
+                let (mut duplicate, event_loop_d) =
+                    new_client_with_keypair::<anyhow::Error>(
+                        server,
+                        server_public_key.clone(),
+                        keypair.clone(),
+                    )
+                    .await?;
+                duplicate.connect().await?;
+                tokio::task::spawn(async move {
+                    let mut s = event_loop_d.run();
+                    while let Some(event) = s.next().await {
+                        if let Ok(Event::ServerConnected { .. }) =
+                            event
+                        {
+                            let _ = duplicate.close().await;
+                        }
+                    }
+                });
+            }
+            Event::ConnectionReplaced => {
+                replaced = true;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    assert!(replaced);
+    Ok(())
+}
+
+/// Spawn a relay server on an ephemeral port that rejects
+/// duplicate connections.
+pub async fn spawn_reject() -> Result<SpawnedServer> {
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    config.duplicate_connection = DuplicateConnectionPolicy::Reject;
+    let server = RelayServer::new(config, keypair);
+    Ok(server.spawn("127.0.0.1:0".parse()?).await?)
+}
+
+/// Connects two sockets with the same keypair before either
+/// completes the server handshake so the second handshake is
+/// rejected, then checks that connecting again while the key
+/// is active is refused.
+pub async fn run_reject(server: &SpawnedServer) -> Result<()> {
+    let url = server.url();
+    let server_public_key = server.public_key().to_vec();
+
+    let (mut first, event_loop_f, keypair) =
+        new_client::<anyhow::Error>(&url, server_public_key.clone())
+            .await?;
+    let (mut second, event_loop_s) =
+        new_client_with_keypair::<anyhow::Error>(
+            &url,
+            server_public_key.clone(),
+            keypair.clone(),
+        )
+        .await?;
+
+    first.connect().await?;
+    let mut s_f = event_loop_f.run();
+    while let Some(event) = s_f.next().await {
+        if let Event::ServerConnected { .. } = event? {
+            break;
+        }
+    }
+
+    // Duplicate is rejected and closed once the pending
+    // handshake completes
+    second.connect().await?;
+    let mut rejected = false;
+    let mut s_s = event_loop_s.run();
+    while let Some(event) = s_s.next().await {
+        match event {
+            Err(Error::DuplicateConnection(_)) => rejected = true,
+            Ok(Event::Close | Event::ConnectionLost) => break,
+            _ => {}
+        }
+    }
+    assert!(rejected);
+
+    // Key is active so the upgrade is refused
+    let result = new_client_with_keypair::<Error>(
+        &url,
+        server_public_key,
+        keypair,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ConnectError(StatusCode::CONFLICT, _))
+    ));
+
+    // First connection is still active
+    first.list_sessions().await?;
+    while let Some(event) = s_f.next().await {
+        if let Event::SessionList(_) = event? {
+            break;
+        }
+    }
+
+    Ok(())
+}
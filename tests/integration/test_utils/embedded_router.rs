@@ -0,0 +1,69 @@
+use anyhow::Result;
+use axum_server::Handle;
+use futures::StreamExt;
+use mpc_client::{Event, NetworkTransport};
+use mpc_relay_server::{
+    axum::{routing::get, Router},
+    RelayServer, ServerConfig,
+};
+use std::net::SocketAddr;
+
+use super::new_client;
+
+/// Mount the relay router in an application on an ephemeral
+/// port and connect a client using the prefixed URL.
+pub async fn run() -> Result<()> {
+    let (config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    let server_public_key = keypair.public_key().to_vec();
+    let server = RelayServer::new(config, keypair);
+    let (relay, relay_handle) = server.router("/relay").await?;
+
+    // Mount the relay alongside the routes for the application
+    let app = Router::new()
+        .route("/health", get(|| async { "ok" }))
+        .merge(relay);
+
+    let handle = Handle::new();
+    let listen_handle = handle.clone();
+    let addr: SocketAddr = "127.0.0.1:0".parse()?;
+    tokio::spawn(async move {
+        axum_server::bind(addr)
+            .handle(listen_handle)
+            .serve(
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .expect("failed to serve application");
+    });
+    let addr = handle.listening().await.expect("app listening");
+    let server = format!("ws://{}/relay", addr);
+
+    let (mut client, event_loop, _) =
+        new_client::<anyhow::Error>(&server, server_public_key)
+            .await?;
+
+    client.connect().await?;
+
+    let mut relay_handle = Some(relay_handle);
+    let mut s = event_loop.run();
+    while let Some(event) = s.next().await {
+        let event = event?;
+        match &event {
+            Event::ServerConnected { .. } => {
+                // Shutting down the relay closes the connection
+                if let Some(relay_handle) = relay_handle.take() {
+                    relay_handle.shutdown().await;
+                }
+            }
+            Event::Close | Event::ConnectionLost => {
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    handle.shutdown();
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use mpc_relay_server::{
+    RelayServer, ServerConfig, SpawnedServer, TlsConfig,
+};
+use std::net::SocketAddr;
+
+/// Spawn a relay server listening on an ephemeral port.
+pub async fn spawn() -> Result<SpawnedServer> {
+    let (config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    let server = RelayServer::new(config, keypair);
+    let addr: SocketAddr = "127.0.0.1:0".parse()?;
+    Ok(server.spawn(addr).await?)
+}
+
+/// Spawn a relay server with TLS files that do not exist.
+pub async fn spawn_missing_tls(
+) -> std::result::Result<SpawnedServer, mpc_relay_server::Error> {
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml")
+            .await
+            .expect("load server config");
+    config.tls = Some(TlsConfig {
+        cert: "tests/missing-cert.pem".into(),
+        key: "tests/missing-key.pem".into(),
+        ..Default::default()
+    });
+    let server = RelayServer::new(config, keypair);
+    server.spawn("127.0.0.1:0".parse().unwrap()).await
+}
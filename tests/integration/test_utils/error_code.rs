@@ -0,0 +1,48 @@
+use anyhow::Result;
+use futures::StreamExt;
+use mpc_client::{Error, Event, NetworkTransport};
+use mpc_protocol::{uuid::Uuid, ErrorCode, JoinCode, UserId};
+
+use super::new_client;
+
+pub async fn run(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<()> {
+    let (mut client, event_loop, _) = new_client::<anyhow::Error>(
+        server,
+        server_public_key.clone(),
+    )
+    .await?;
+
+    client.connect().await?;
+
+    let mut s = event_loop.run();
+    while let Some(event) = s.next().await {
+        match event {
+            Ok(Event::ServerConnected { .. }) => {
+                client
+                    .join_meeting(
+                        Uuid::new_v4(),
+                        UserId::from([0; 32]),
+                        JoinCode::from([0; 32]),
+                        None,
+                    )
+                    .await?;
+            }
+            Err(e) => {
+                assert_eq!(
+                    Some(ErrorCode::MeetingNotFound),
+                    e.code()
+                );
+                assert!(matches!(e, Error::MeetingNotFound(_)));
+                client.close().await?;
+            }
+            Ok(Event::Close) => {
+                break;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -128,6 +128,8 @@ async fn gg20_keygen(
     let mut client_i_session = SessionInitiator::new(
         client_i_transport,
         session_participants,
+        None,
+        Default::default(),
     );
     let mut client_p_1_session =
         SessionParticipant::new(client_p_1_transport);
@@ -317,8 +319,12 @@ async fn gg20_sign_offline(
 
     let mut sessions: Vec<SessionState> = Vec::new();
 
-    let mut client_i_session =
-        SessionInitiator::new(client_i_transport, sign_participants);
+    let mut client_i_session = SessionInitiator::new(
+        client_i_transport,
+        sign_participants,
+        None,
+        Default::default(),
+    );
     let mut client_p_2_session =
         SessionParticipant::new(client_p_2_transport);
 
@@ -532,8 +538,12 @@ async fn gg20_sign_online(
 
     let mut sessions: Vec<SessionState> = Vec::new();
 
-    let mut client_i_session =
-        SessionInitiator::new(client_i_transport, sign_participants);
+    let mut client_i_session = SessionInitiator::new(
+        client_i_transport,
+        sign_participants,
+        None,
+        Default::default(),
+    );
     let mut client_p_2_session =
         SessionParticipant::new(client_p_2_transport);
 
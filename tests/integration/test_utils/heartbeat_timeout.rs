@@ -0,0 +1,54 @@
+use anyhow::Result;
+use futures::StreamExt;
+use mpc_client::Event;
+use mpc_relay_server::{RelayServer, ServerConfig, SpawnedServer};
+use std::time::Duration;
+
+use super::new_client;
+
+/// Spawn a relay server on an ephemeral port that sends pings
+/// every second and disconnects idle clients after two seconds.
+pub async fn spawn() -> Result<SpawnedServer> {
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    config.heartbeat.interval = 1;
+    config.heartbeat.timeout = 2;
+    let server = RelayServer::new(config, keypair);
+    Ok(server.spawn("127.0.0.1:0".parse()?).await?)
+}
+
+/// Connects a client that answers pings and a client that does
+/// not read from the socket so pongs are never sent, then checks
+/// the server only closes the connection for the silent client.
+pub async fn run(server: &SpawnedServer) -> Result<()> {
+    let url = server.url();
+    let server_public_key = server.public_key().to_vec();
+    let (_active, active_loop, _) =
+        new_client::<anyhow::Error>(&url, server_public_key.clone())
+            .await?;
+    let (_silent, silent_loop, _) =
+        new_client::<anyhow::Error>(&url, server_public_key).await?;
+
+    let mut active = active_loop.run();
+    let mut silent = silent_loop.run();
+    // Silent client exceeds the timeout while the active
+    // client keeps its connection
+    let waited =
+        tokio::time::timeout(Duration::from_secs(4), active.next())
+            .await;
+    assert!(waited.is_err(), "active client received {:?}", waited);
+
+    // Server closed the silent connection after the timeout
+    let mut lost = false;
+    while let Some(event) =
+        tokio::time::timeout(Duration::from_secs(5), silent.next())
+            .await?
+    {
+        if matches!(event, Ok(Event::ConnectionLost)) {
+            lost = true;
+            break;
+        }
+    }
+    assert!(lost);
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use futures::{select, FutureExt, StreamExt};
+use mpc_client::{
+    ClientOptions, Event, HttpEventLoop, NetworkTransport, Transport,
+};
+use mpc_protocol::generate_keypair;
+use tokio::sync::mpsc;
+
+use super::{new_client, peer_channel};
+
+/// Creates a websocket client and a client using the HTTP
+/// transport that handshake with the server and then each
+/// other and exchange "ping" and "pong" messages over the
+/// noise transport channel.
+///
+/// The HTTP client closes the connection once the messages
+/// have been exchanged.
+pub async fn run(
+    server: &str,
+    http_server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<()> {
+    let (initiator, event_loop_i, initiator_key) =
+        new_client::<anyhow::Error>(
+            server,
+            server_public_key.clone(),
+        )
+        .await?;
+
+    let options = ClientOptions {
+        keypair: generate_keypair()?,
+        server_public_key,
+        pattern: None,
+        idle_timeout: None,
+        token: None,
+        tls: None,
+    };
+    let (participant, event_loop_p) =
+        Transport::new_http(http_server, options).await?;
+
+    let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>(1);
+
+    let ev_i = peer_channel::initiator_client::<anyhow::Error>(
+        initiator,
+        event_loop_i,
+        shutdown_tx,
+    );
+    let ev_p = participant_client(
+        participant,
+        event_loop_p,
+        initiator_key.public_key(),
+        shutdown_rx,
+    );
+
+    // Must drive the event loop futures
+    let (res_i, res_p) = futures::join!(ev_i, ev_p);
+    res_i?;
+    res_p?;
+
+    Ok(())
+}
+
+async fn participant_client(
+    mut client: Transport,
+    event_loop: HttpEventLoop,
+    initiator_public_key: &[u8],
+    mut shutdown_rx: mpsc::Receiver<()>,
+) -> Result<()> {
+    client.connect().await?;
+
+    let mut s = event_loop.run();
+    let mut closed = false;
+    loop {
+        select! {
+            event = s.next().fuse() => {
+                let event = match event {
+                    Some(event) => event?,
+                    None => break,
+                };
+                match &event {
+                    Event::ServerConnected { .. } => {
+                        client.connect_peer(initiator_public_key).await?;
+                    }
+                    Event::JsonMessage { peer_key, message, .. } => {
+                        let message: &str = message.deserialize()?;
+                        if message == "ping" {
+                            client.send_json(peer_key, "pong", None).await?;
+                        }
+                    }
+                    Event::Close => {
+                        closed = true;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            shutdown = shutdown_rx.recv().fuse() => {
+                if shutdown.is_some() {
+                    client.close().await?;
+                }
+            }
+        }
+    }
+    assert!(closed);
+    Ok(())
+}
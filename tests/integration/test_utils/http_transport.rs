@@ -0,0 +1,297 @@
+use anyhow::Result;
+use futures::{
+    future::{self, BoxFuture},
+    select, FutureExt, StreamExt,
+};
+use mpc_client::{
+    ClientOptions, Event, EventStream, NetworkTransport, Transport,
+};
+use mpc_protocol::{
+    encode, generate_keypair, hex, snow::Builder, zlib,
+    HandshakeMessage, RequestMessage, TransparentMessage,
+};
+use mpc_relay_server::{RelayServer, ServerConfig, SpawnedServer};
+use serde_json::Value;
+use std::{net::SocketAddr, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    sync::mpsc,
+};
+
+/// Spawn a relay server on an ephemeral port that sends
+/// pings every second and disconnects idle clients after
+/// two seconds.
+pub async fn spawn() -> Result<SpawnedServer> {
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    config.heartbeat.interval = 1;
+    config.heartbeat.timeout = 2;
+    let server = RelayServer::new(config, keypair);
+    let addr: SocketAddr = "127.0.0.1:0".parse()?;
+    Ok(server.spawn(addr).await?)
+}
+
+fn options(server_public_key: Vec<u8>) -> Result<ClientOptions> {
+    Ok(ClientOptions {
+        keypair: generate_keypair()?,
+        server_public_key,
+        pattern: None,
+        idle_timeout: Some(Duration::from_secs(5)),
+        token: None,
+        tls: None,
+    })
+}
+
+/// Connect a websocket client and a client using the HTTP
+/// transport then exchange messages over the peer channel
+/// once the heartbeat interval has elapsed a few times.
+pub async fn run(server: &SpawnedServer) -> Result<()> {
+    let options_i = options(server.public_key().to_vec())?;
+    let url = options_i.url(&server.url());
+    let (initiator, event_loop_i) =
+        Transport::new_relay(&url, options_i).await?;
+    let initiator_key = initiator.public_key().to_vec();
+
+    let url = format!("http://{}", server.addr());
+    let (participant, event_loop_p) = Transport::new_http(
+        &url,
+        options(server.public_key().to_vec())?,
+    )
+    .await?;
+
+    let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>(1);
+    let (res_i, res_p) = futures::join!(
+        initiator_client(initiator, event_loop_i.run(), shutdown_tx),
+        participant_client(
+            participant,
+            event_loop_p.run(),
+            initiator_key,
+            shutdown_rx,
+        ),
+    );
+    res_i?;
+    res_p?;
+    Ok(())
+}
+
+async fn initiator_client(
+    mut client: Transport,
+    mut s: EventStream,
+    shutdown_tx: mpsc::Sender<()>,
+) -> Result<()> {
+    client.connect().await?;
+    while let Some(event) = s.next().await {
+        match &event? {
+            Event::PeerConnected { peer_key } => {
+                client.send_json(peer_key, "ping", None).await?;
+            }
+            Event::JsonMessage { message, .. } => {
+                let message: &str = message.deserialize()?;
+                if message == "pong" {
+                    let _ = shutdown_tx.send(()).await;
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+async fn participant_client(
+    mut client: Transport,
+    mut s: EventStream,
+    initiator_key: Vec<u8>,
+    mut shutdown_rx: mpsc::Receiver<()>,
+) -> Result<()> {
+    client.connect().await?;
+    let mut closed = false;
+    let mut idle: BoxFuture<'static, ()> =
+        Box::pin(future::pending());
+    loop {
+        select! {
+            event = s.next().fuse() => {
+                let event = match event {
+                    Some(event) => event?,
+                    None => break,
+                };
+                match &event {
+                    Event::ServerConnected { .. } => {
+                        // Stay idle for longer than the server
+                        // timeout so pings must be answered
+                        idle = Box::pin(tokio::time::sleep(
+                            Duration::from_secs(3),
+                        ));
+                    }
+                    Event::JsonMessage { peer_key, message, .. } => {
+                        let message: &str = message.deserialize()?;
+                        if message == "ping" {
+                            client
+                                .send_json(peer_key, "pong", None)
+                                .await?;
+                        }
+                    }
+                    Event::ConnectionLost => {
+                        anyhow::bail!("http connection lost");
+                    }
+                    Event::Close => {
+                        closed = true;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            _ = (&mut idle).fuse() => {
+                idle = Box::pin(future::pending());
+                client.connect_peer(&initiator_key).await?;
+            }
+            shutdown = shutdown_rx.recv().fuse() => {
+                if shutdown.is_some() {
+                    client.close().await?;
+                }
+            }
+        }
+    }
+    assert!(closed);
+    Ok(())
+}
+
+/// Open an event stream then check that requests to the
+/// connection must include the secret from the connect event
+/// and that a malformed frame does not close the connection.
+pub async fn run_secret(server: &SpawnedServer) -> Result<()> {
+    let options = options(server.public_key().to_vec())?;
+    let public_key = hex::encode(options.keypair.public_key());
+
+    let mut events =
+        BufReader::new(TcpStream::connect(server.addr()).await?);
+    events
+        .get_mut()
+        .write_all(
+            format!(
+                "GET /http?public_key={} HTTP/1.1\r\n\
+                Host: {}\r\n\
+                Accept: text/event-stream\r\n\r\n",
+                public_key,
+                server.addr(),
+            )
+            .as_bytes(),
+        )
+        .await?;
+
+    let (name, data) = next_event(&mut events).await?;
+    assert_eq!("connect", name);
+    let connect: Value = serde_json::from_str(&data)?;
+    let id = connect["id"].as_str().unwrap().to_owned();
+    let secret = connect["secret"].as_str().unwrap().to_owned();
+    let path = format!("/http/{}?public_key={}", id, public_key);
+
+    // Identifier and public key are not enough
+    let status = send(server, "POST", &path, None, &[]).await?;
+    assert_eq!(401, status);
+    let status =
+        send(server, "POST", &path, Some("invalid"), &[]).await?;
+    assert_eq!(404, status);
+    let status = send(server, "DELETE", &path, None, &[]).await?;
+    assert_eq!(401, status);
+
+    // Malformed frame is skipped
+    let garbage = zlib::deflate(&[0xff; 16])?;
+    let status =
+        send(server, "POST", &path, Some(&secret), &garbage).await?;
+    assert_eq!(202, status);
+
+    // Server still replies to the handshake
+    let mut handshake = Builder::new(options.params()?)
+        .local_private_key(options.keypair.private_key())
+        .remote_public_key(server.public_key())
+        .build_initiator()?;
+    let mut buf = vec![0u8; 1024];
+    let len = handshake.write_message(&[], &mut buf)?;
+    let message = RequestMessage::Transparent(
+        TransparentMessage::ServerHandshake(
+            HandshakeMessage::Initiator(len, buf),
+        ),
+    );
+    let body = zlib::deflate(&encode(&message).await?)?;
+    let status =
+        send(server, "POST", &path, Some(&secret), &body).await?;
+    assert_eq!(202, status);
+
+    let (name, _) = tokio::time::timeout(
+        Duration::from_secs(5),
+        next_event(&mut events),
+    )
+    .await??;
+    assert_eq!("message", name);
+
+    let status =
+        send(server, "DELETE", &path, Some(&secret), &[]).await?;
+    assert_eq!(204, status);
+
+    Ok(())
+}
+
+/// Read the next event name and data from a raw event stream.
+///
+/// Lines for the response headers and the chunk sizes
+/// are ignored.
+async fn next_event(
+    events: &mut BufReader<TcpStream>,
+) -> Result<(String, String)> {
+    let mut name = String::from("message");
+    loop {
+        let mut line = String::new();
+        if events.read_line(&mut line).await? == 0 {
+            anyhow::bail!("event stream closed");
+        }
+        let (field, value) =
+            line.trim_end().split_once(':').unwrap_or_default();
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => name = value.to_owned(),
+            "data" => return Ok((name, value.to_owned())),
+            _ => {}
+        }
+    }
+}
+
+/// Send a request on a new connection and return the
+/// response status code.
+async fn send(
+    server: &SpawnedServer,
+    method: &str,
+    path: &str,
+    secret: Option<&str>,
+    body: &[u8],
+) -> Result<u16> {
+    let mut socket = TcpStream::connect(server.addr()).await?;
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\n\
+        Host: {}\r\n\
+        Connection: close\r\n\
+        Content-Length: {}\r\n",
+        method,
+        path,
+        server.addr(),
+        body.len(),
+    );
+    if let Some(secret) = secret {
+        request.push_str(&format!(
+            "Authorization: Bearer {}\r\n",
+            secret
+        ));
+    }
+    request.push_str("\r\n");
+    socket.write_all(request.as_bytes()).await?;
+    socket.write_all(body).await?;
+
+    let mut response = String::new();
+    socket.read_to_string(&mut response).await?;
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .ok_or_else(|| anyhow::anyhow!("invalid response"))?;
+    Ok(status.parse()?)
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use futures::StreamExt;
+use mpc_client::{Client, ClientOptions, Event, NetworkTransport};
+use mpc_protocol::generate_keypair;
+use std::time::Duration;
+
+pub async fn run(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<()> {
+    let options = ClientOptions {
+        keypair: generate_keypair()?,
+        server_public_key,
+        pattern: None,
+        idle_timeout: Some(Duration::from_secs(1)),
+        token: None,
+        tls: None,
+    };
+    let url = options.url(server);
+    let (mut client, event_loop) = Client::new(&url, options).await?;
+
+    client.connect().await?;
+
+    let mut connected = false;
+    let mut lost = false;
+    let mut s = event_loop.run();
+    while let Some(event) = s.next().await {
+        let event = event?;
+        match &event {
+            Event::ServerConnected { .. } => {
+                connected = true;
+            }
+            Event::ConnectionLost => {
+                lost = true;
+            }
+            _ => {}
+        }
+    }
+
+    assert!(connected);
+    assert!(lost);
+    Ok(())
+}
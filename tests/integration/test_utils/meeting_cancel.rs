@@ -0,0 +1,113 @@
+use anyhow::Result;
+use futures::{select, FutureExt, StreamExt};
+use mpc_protocol::{uuid::Uuid, MeetingId, UserId};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::new_client;
+use mpc_client::{Error, Event, NetworkTransport, Transport};
+
+pub async fn run(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<()> {
+    let mut completed: Vec<()> = Vec::new();
+
+    // Create new clients
+    let (client_i, event_loop_i, _) = new_client::<anyhow::Error>(
+        server,
+        server_public_key.clone(),
+    )
+    .await?;
+    let (client_p, event_loop_p, _) = new_client::<anyhow::Error>(
+        server,
+        server_public_key.clone(),
+    )
+    .await?;
+
+    let init_id: [u8; 32] =
+        Sha256::digest("initiator".as_bytes()).into();
+    let part_id: [u8; 32] =
+        Sha256::digest("participant".as_bytes()).into();
+    let other_id: [u8; 32] =
+        Sha256::digest("other".as_bytes()).into();
+
+    let init_id: UserId = init_id.into();
+    let part_id: UserId = part_id.into();
+    let other_id: UserId = other_id.into();
+
+    let mut client_i_transport: Transport = client_i.into();
+    let mut client_p_transport: Transport = client_p.into();
+
+    client_i_transport.connect().await?;
+    client_p_transport.connect().await?;
+
+    let mut s_i = event_loop_i.run();
+    let mut s_p = event_loop_p.run();
+
+    let mut meeting_id: Option<MeetingId> = None;
+
+    loop {
+        if completed.len() == 2 {
+            break;
+        }
+
+        select! {
+            event = s_i.next().fuse() => {
+                if let Some(event) = event {
+                    match event? {
+                        Event::ServerConnected { .. } => {
+                            // Extra slot so the meeting is never ready
+                            let slots = vec![
+                                init_id.clone().into(),
+                                part_id.clone().into(),
+                                other_id.clone().into(),
+                            ];
+
+                            client_i_transport.new_meeting(
+                                init_id.clone(), slots, false, None, Value::Null).await?;
+                        }
+                        Event::MeetingCreated(meeting, secret) => {
+                            meeting_id = Some(meeting.meeting_id);
+                            client_p_transport.join_meeting(
+                                meeting.meeting_id,
+                                part_id.clone(),
+                                secret.join_code.unwrap(),
+                                None,
+                            ).await?;
+
+                            // Requests for a connection are handled in
+                            // order so the error for an unknown meeting
+                            // tells us the join has been processed
+                            client_p_transport.leave_meeting(
+                                Uuid::new_v4()).await?;
+                        }
+                        Event::MeetingCancelled(id) => {
+                            assert_eq!(meeting_id, Some(id));
+                            completed.push(());
+                        }
+                        _ => {}
+                    }
+                }
+            },
+            event = s_p.next().fuse() => {
+                if let Some(event) = event {
+                    match event {
+                        Err(Error::MeetingNotFound(_)) => {
+                            client_i_transport.cancel_meeting(
+                                meeting_id.unwrap()).await?;
+                        }
+                        Ok(Event::MeetingCancelled(id)) => {
+                            assert_eq!(meeting_id, Some(id));
+                            completed.push(());
+                        }
+                        Err(e) => return Err(e.into()),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use mpc_driver::{meeting, MeetingOptions, ServerOptions};
+use mpc_protocol::{generate_keypair, Keypair, MeetingSlot, UserId};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+fn options(
+    server: &str,
+    server_public_key: Vec<u8>,
+    keypair: Keypair,
+) -> MeetingOptions {
+    MeetingOptions {
+        keypair,
+        server: ServerOptions {
+            server_url: server.to_string(),
+            server_public_key,
+            pattern: None,
+            token: None,
+        },
+        encrypt_data: true,
+    }
+}
+
+pub async fn run(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<()> {
+    let init_key = generate_keypair()?;
+    let part_key = generate_keypair()?;
+
+    let init_id: [u8; 32] =
+        Sha256::digest("initiator".as_bytes()).into();
+    let part_id: [u8; 32] =
+        Sha256::digest("participant".as_bytes()).into();
+
+    let init_id: UserId = init_id.into();
+    let part_id: UserId = part_id.into();
+
+    let data = json!({"name": "Mock wallet"});
+
+    // Data key is derived from the join code which is
+    // not issued for meetings with invitations
+    let result = meeting::create(
+        options(server, server_public_key.clone(), init_key.clone()),
+        vec![init_id.clone().into(), part_id.clone().into()],
+        init_id.clone(),
+        true,
+        data.clone(),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(mpc_driver::Error::MeetingInvitationsEncrypted)
+    ));
+
+    let slots: Vec<MeetingSlot> =
+        vec![init_id.clone().into(), part_id.clone().into()];
+    let (meeting_id, secret) = meeting::create(
+        options(server, server_public_key.clone(), init_key.clone()),
+        slots.clone(),
+        init_id,
+        false,
+        data.clone(),
+    )
+    .await?;
+
+    // Owner is already registered so only waits
+    // for the meeting to be ready
+    let owner = meeting::join(
+        options(server, server_public_key.clone(), init_key.clone()),
+        meeting_id,
+        None,
+        secret.join_code.clone().unwrap(),
+        None,
+        slots.clone(),
+    );
+    let participant = meeting::join(
+        options(server, server_public_key.clone(), part_key.clone()),
+        meeting_id,
+        Some(part_id),
+        secret.join_code.unwrap(),
+        None,
+        slots,
+    );
+
+    let (owner, participant) =
+        futures::try_join!(owner, participant)?;
+
+    let expected = vec![
+        init_key.public_key().to_vec(),
+        part_key.public_key().to_vec(),
+    ];
+    assert_eq!(expected, owner.0);
+    assert_eq!(expected, participant.0);
+    assert_eq!(data, owner.1);
+    assert_eq!(data, participant.1);
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use futures::{select, FutureExt, StreamExt};
+use mpc_protocol::{JoinCode, MeetingId, UserId};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::new_client;
+use mpc_client::{Error, Event, NetworkTransport, Transport};
+
+pub async fn run(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<()> {
+    let mut completed: Vec<()> = Vec::new();
+
+    // Create new clients
+    let (client_i, event_loop_i, _) = new_client::<anyhow::Error>(
+        server,
+        server_public_key.clone(),
+    )
+    .await?;
+    let (client_p, event_loop_p, _) = new_client::<anyhow::Error>(
+        server,
+        server_public_key.clone(),
+    )
+    .await?;
+
+    let init_id: [u8; 32] =
+        Sha256::digest("initiator".as_bytes()).into();
+    let part_id: [u8; 32] =
+        Sha256::digest("participant".as_bytes()).into();
+
+    let init_id: UserId = init_id.into();
+    let part_id: UserId = part_id.into();
+
+    let mut client_i_transport: Transport = client_i.into();
+    let mut client_p_transport: Transport = client_p.into();
+
+    client_i_transport.connect().await?;
+    client_p_transport.connect().await?;
+
+    let mut s_i = event_loop_i.run();
+    let mut s_p = event_loop_p.run();
+
+    let mut meeting: Option<(MeetingId, JoinCode)> = None;
+
+    loop {
+        if completed.len() == 2 {
+            break;
+        }
+
+        select! {
+                    event = s_i.next().fuse() => {
+                        if let Some(event) = event {
+                            match event? {
+                                Event::ServerConnected { .. } => {
+                                    let slots = vec![
+                                        init_id.clone().into(),
+                                        part_id.clone().into(),
+                                    ];
+                                    client_i_transport.new_meeting(
+                                        init_id.clone(), slots, true, None, Value::Null).await?;
+                                }
+                                Event::MeetingCreated(state, secret) => {
+                                    // Invitations are not created for the owner
+                                    assert_eq!(1, secret.invitations.len());
+                                    let invitation =
+                                        secret.invitations.into_iter().next().unwrap();
+                                    assert_eq!(part_id, invitation.user_id);
+                                    assert!(secret.join_code.is_none());
+
+                                    meeting = Some((
+                                        state.meeting_id,
+                                        invitation.join_code,
+                                    ));
+
+                                    // Knowing the meeting identifier is
+                                    // not enough to join the meeting
+                                    client_p_transport.join_meeting(
+                                        state.meeting_id,
+                                        part_id.clone(),
+                                        JoinCode::from([0; 32]),
+                                        None,
+                                    ).await?;
+                                }
+                                Event::MeetingReady(_) => {
+                                    completed.push(());
+                                }
+                                _ => {}
+                            }
+                        }
+                    },
+                    event = s_p.next().fuse() => {
+                        if let Some(event) = event {
+                            match event {
+                                Err(Error::PermissionDenied(_)) => {
+                                    let (meeting_id, join_code) =
+                                        meeting.clone().unwrap();
+                                    client_p_transport.join_meeting(
+                                        meeting_id,
+                                        part_id.clone(),
+                                        join_code,
+        None,
+                                    ).await?;
+                                }
+                                Ok(Event::MeetingReady(_)) => {
+                                    completed.push(());
+                                }
+                                Err(e) => return Err(e.into()),
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+    }
+
+    Ok(())
+}
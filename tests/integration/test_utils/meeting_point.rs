@@ -1,9 +1,8 @@
 use anyhow::Result;
 use futures::{select, FutureExt, StreamExt};
-use mpc_protocol::{hex, UserId};
+use mpc_protocol::{hex, MeetingState, UserId};
 use serde_json::Value;
 use sha2::{Digest, Sha256};
-use std::collections::HashSet;
 
 use super::new_client;
 use mpc_client::{Event, NetworkTransport, Transport};
@@ -47,12 +46,11 @@ pub async fn run(
 
     // Expected public keys that should be broadcast
     // as the meeting ready event when the meeting point
-    // limit has been reached
-    let mut expected = vec![
+    // limit has been reached, in slot order
+    let expected = vec![
         hex::encode(init_key.public_key()),
         hex::encode(part_key.public_key()),
     ];
-    expected.sort();
 
     let mut s_i = event_loop_i.run();
     let mut s_p = event_loop_p.run();
@@ -72,30 +70,31 @@ pub async fn run(
                             Event::ServerConnected { .. } => {
 
                                 // Prepare enough slots for a 2 of 2
-                                let mut slots = HashSet::new();
-                                slots.insert(init_id.clone());
-                                slots.insert(part_id.clone());
+                                let slots = vec![
+                                    init_id.clone().into(),
+                                    part_id.clone().into(),
+                                ];
 
                                 client_i_transport.new_meeting(
-                                    init_id.clone(), slots, Value::Null).await?;
+                                    init_id.clone(), slots, false, None, Value::Null).await?;
                             }
-                            Event::MeetingCreated(meeting) => {
+                            Event::MeetingCreated(meeting, secret) => {
                                 // In the real world the initiator needs
-                                // to share the meeting/user identifiers with
-                                // all the participants
+                                // to share the meeting/user identifiers
+                                // and join code with all the participants
                                 client_p_transport.join_meeting(
-                                    meeting.meeting_id, part_id.clone()).await?;
+                                    meeting.meeting_id,
+                                    part_id.clone(),
+                                    secret.join_code.unwrap(),
+                                    None,
+                                ).await?;
                             }
                             Event::MeetingReady(meeting) => {
-                                let mut public_keys: Vec<String> =
-                                    meeting
-                                        .registered_participants
-                                        .into_iter()
-                                        .map(hex::encode)
-                                        .collect();
-
-                                public_keys.sort();
-                                assert_eq!(expected, public_keys);
+                                assert_participants(
+                                    &expected,
+                                    &[&init_id, &part_id],
+                                    &meeting,
+                                );
 
                                 completed.push(());
                             }
@@ -110,15 +109,11 @@ pub async fn run(
                     Some(event) => {
                         let event = event?;
                         if let Event::MeetingReady(meeting) = event {
-                            let mut public_keys: Vec<String> =
-                                meeting
-                                    .registered_participants
-                                    .into_iter()
-                                    .map(hex::encode)
-                                    .collect();
-
-                            public_keys.sort();
-                            assert_eq!(expected, public_keys);
+                            assert_participants(
+                                &expected,
+                                &[&init_id, &part_id],
+                                &meeting,
+                            );
 
                             completed.push(());
                         }
@@ -131,3 +126,24 @@ pub async fn run(
 
     Ok(completed.len())
 }
+
+/// Assert the participants are in slot order.
+fn assert_participants(
+    expected: &[String],
+    user_ids: &[&UserId],
+    meeting: &MeetingState,
+) {
+    let public_keys: Vec<String> = meeting
+        .registered_participants
+        .iter()
+        .map(|p| hex::encode(&p.public_key))
+        .collect();
+    assert_eq!(expected, public_keys.as_slice());
+
+    for (index, participant) in
+        meeting.registered_participants.iter().enumerate()
+    {
+        assert_eq!(index as u16, participant.slot);
+        assert_eq!(user_ids[index], &participant.user_id);
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::Result;
+use futures::{select, FutureExt, StreamExt};
+use mpc_protocol::{
+    ed25519_dalek::{Signer, SigningKey},
+    join_meeting_message, JoinCode, MeetingId, MeetingSlot,
+    MeetingState, UserId,
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::new_client;
+use mpc_client::{Error, Event, NetworkTransport, Transport};
+
+pub async fn run(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<()> {
+    let mut completed: Vec<()> = Vec::new();
+
+    // Create new clients
+    let (client_i, event_loop_i, _) = new_client::<anyhow::Error>(
+        server,
+        server_public_key.clone(),
+    )
+    .await?;
+    let (client_p, event_loop_p, part_key) =
+        new_client::<anyhow::Error>(
+            server,
+            server_public_key.clone(),
+        )
+        .await?;
+
+    let init_id: [u8; 32] =
+        Sha256::digest("initiator".as_bytes()).into();
+    let part_id: [u8; 32] =
+        Sha256::digest("participant".as_bytes()).into();
+
+    let init_id: UserId = init_id.into();
+    let part_id: UserId = part_id.into();
+
+    // Identity of the participant
+    let signing_key: [u8; 32] =
+        Sha256::digest("participant-signing-key".as_bytes()).into();
+    let signing_key = SigningKey::from_bytes(&signing_key);
+    let verifying_key = signing_key.verifying_key();
+
+    let mut client_i_transport: Transport = client_i.into();
+    let mut client_p_transport: Transport = client_p.into();
+
+    client_i_transport.connect().await?;
+    client_p_transport.connect().await?;
+
+    let mut s_i = event_loop_i.run();
+    let mut s_p = event_loop_p.run();
+
+    let mut meeting: Option<(MeetingId, JoinCode)> = None;
+
+    let assert_signed = |state: &MeetingState| {
+        let participant = state
+            .registered_participants
+            .iter()
+            .find(|p| p.user_id == part_id)
+            .unwrap();
+        assert_eq!(Some(verifying_key), participant.verifying_key);
+        assert!(participant.verify(&state.meeting_id));
+    };
+
+    loop {
+        if completed.len() == 2 {
+            break;
+        }
+
+        select! {
+            event = s_i.next().fuse() => {
+                if let Some(event) = event {
+                    match event? {
+                        Event::ServerConnected { .. } => {
+                            let slots = vec![
+                                init_id.clone().into(),
+                                MeetingSlot {
+                                    user_id: part_id.clone(),
+                                    role: None,
+                                    verifying_key: Some(verifying_key),
+                                },
+                            ];
+                            client_i_transport.new_meeting(
+                                init_id.clone(), slots, false, None, Value::Null).await?;
+                        }
+                        Event::MeetingCreated(state, secret) => {
+                            meeting = Some((
+                                state.meeting_id,
+                                secret.join_code.clone().unwrap(),
+                            ));
+
+                            // Knowing the join code is not enough
+                            // to join a slot bound to an identity
+                            client_p_transport.join_meeting(
+                                state.meeting_id,
+                                part_id.clone(),
+                                secret.join_code.unwrap(),
+                                None,
+                            ).await?;
+                        }
+                        Event::MeetingReady(state) => {
+                            assert_signed(&state);
+                            completed.push(());
+                        }
+                        _ => {}
+                    }
+                }
+            },
+            event = s_p.next().fuse() => {
+                if let Some(event) = event {
+                    match event {
+                        Err(Error::PermissionDenied(_)) => {
+                            let (meeting_id, join_code) =
+                                meeting.clone().unwrap();
+                            let message = join_meeting_message(
+                                &meeting_id,
+                                part_key.public_key(),
+                            );
+                            let signature = signing_key.sign(&message);
+                            client_p_transport.join_meeting(
+                                meeting_id,
+                                part_id.clone(),
+                                join_code,
+                                Some(signature),
+                            ).await?;
+                        }
+                        Ok(Event::MeetingReady(state)) => {
+                            assert_signed(&state);
+                            completed.push(());
+                        }
+                        Err(e) => return Err(e.into()),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+use anyhow::Result;
+use mpc_client::{Error, Event, NetworkTransport};
+use mpc_protocol::{
+    ErrorCode, JoinCode, MeetingId, MeetingSecret, UserId,
+};
+use mpc_relay_server::{RelayServer, ServerConfig, SpawnedServer};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::{new_client, wait_error, wait_event, Events};
+
+/// Spawn a relay server on an ephemeral port.
+pub async fn spawn() -> Result<SpawnedServer> {
+    let (config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    let server = RelayServer::new(config, keypair);
+    Ok(server.spawn("127.0.0.1:0".parse()?).await?)
+}
+
+fn user_id(name: &str) -> UserId {
+    let id: [u8; 32] = Sha256::digest(name.as_bytes()).into();
+    id.into()
+}
+
+/// Create a meeting and wait for the secrets.
+async fn new_meeting(
+    client: &mut impl NetworkTransport,
+    events: &mut Events,
+    slots: &[&UserId],
+    invitations: bool,
+) -> Result<(MeetingId, MeetingSecret)> {
+    let owner_id = slots[0].clone();
+    let slots = slots.iter().map(|id| (*id).clone().into()).collect();
+    client
+        .new_meeting(owner_id, slots, invitations, None, Value::Null)
+        .await?;
+    match wait_event(events, |event| {
+        matches!(event, Event::MeetingCreated(_, _))
+    })
+    .await?
+    {
+        Event::MeetingCreated(state, secret) => {
+            Ok((state.meeting_id, secret))
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Join a meeting then query the meeting to check the join
+/// was accepted; requests on a connection are handled in order.
+async fn join(
+    client: &mut impl NetworkTransport,
+    events: &mut Events,
+    meeting_id: MeetingId,
+    user_id: &UserId,
+    join_code: JoinCode,
+) -> Result<()> {
+    client
+        .join_meeting(meeting_id, user_id.clone(), join_code, None)
+        .await?;
+    client.get_meeting(meeting_id).await?;
+    wait_event(events, |event| {
+        matches!(event, Event::MeetingInfo(_))
+    })
+    .await?;
+    Ok(())
+}
+
+/// Checks that filled slots cannot be taken by another
+/// public key and that invitations replace the join code.
+pub async fn run(server: &SpawnedServer) -> Result<()> {
+    let url = server.url();
+    let server_public_key = server.public_key().to_vec();
+
+    let (mut owner, event_loop_o, _) =
+        new_client::<anyhow::Error>(&url, server_public_key.clone())
+            .await?;
+    let (mut participant, event_loop_p, _) =
+        new_client::<anyhow::Error>(&url, server_public_key.clone())
+            .await?;
+    let (mut intruder, event_loop_x, _) =
+        new_client::<anyhow::Error>(&url, server_public_key.clone())
+            .await?;
+
+    owner.connect().await?;
+    participant.connect().await?;
+    intruder.connect().await?;
+
+    let mut events_o = super::spawn_events(event_loop_o);
+    let mut events_p = super::spawn_events(event_loop_p);
+    let mut events_x = super::spawn_events(event_loop_x);
+    for events in [&mut events_o, &mut events_p, &mut events_x] {
+        wait_event(events, |event| {
+            matches!(event, Event::ServerConnected { .. })
+        })
+        .await?;
+    }
+
+    let init_id = user_id("initiator");
+    let part_id = user_id("participant");
+    let other_id = user_id("other");
+    let slots = [&init_id, &part_id, &other_id];
+
+    // Filled slot is only available to the same public key
+    let (meeting_id, secret) =
+        new_meeting(&mut owner, &mut events_o, &slots, false).await?;
+    let join_code = secret.join_code.unwrap();
+    join(
+        &mut participant,
+        &mut events_p,
+        meeting_id,
+        &part_id,
+        join_code.clone(),
+    )
+    .await?;
+    join(
+        &mut participant,
+        &mut events_p,
+        meeting_id,
+        &part_id,
+        join_code.clone(),
+    )
+    .await?;
+
+    intruder
+        .join_meeting(meeting_id, part_id.clone(), join_code, None)
+        .await?;
+    let error = wait_error(&mut events_x).await?;
+    assert_eq!(Some(ErrorCode::MeetingFull), error.code());
+
+    // Join code is not accepted with invitations
+    owner
+        .new_meeting(
+            init_id.clone(),
+            vec![init_id.clone().into(), part_id.clone().into()],
+            true,
+            Some(JoinCode::from([1; 32])),
+            Value::Null,
+        )
+        .await?;
+    let error = wait_error(&mut events_o).await?;
+    assert_eq!(Some(ErrorCode::BadRequest), error.code());
+
+    // Invitations are bound to the public key once the slot
+    // is joined so the participant may leave and join again
+    let (meeting_id, secret) =
+        new_meeting(&mut owner, &mut events_o, &slots, true).await?;
+    assert!(secret.join_code.is_none());
+    let invitation = secret
+        .invitations
+        .into_iter()
+        .find(|i| i.user_id == part_id)
+        .unwrap();
+    join(
+        &mut participant,
+        &mut events_p,
+        meeting_id,
+        &part_id,
+        invitation.join_code.clone(),
+    )
+    .await?;
+
+    intruder
+        .join_meeting(
+            meeting_id,
+            part_id.clone(),
+            invitation.join_code.clone(),
+            None,
+        )
+        .await?;
+    let error = wait_error(&mut events_x).await?;
+    assert!(matches!(error, Error::PermissionDenied(_)));
+
+    participant.leave_meeting(meeting_id).await?;
+    intruder
+        .join_meeting(
+            meeting_id,
+            part_id.clone(),
+            invitation.join_code.clone(),
+            None,
+        )
+        .await?;
+    let error = wait_error(&mut events_x).await?;
+    assert!(matches!(error, Error::PermissionDenied(_)));
+
+    join(
+        &mut participant,
+        &mut events_p,
+        meeting_id,
+        &part_id,
+        invitation.join_code,
+    )
+    .await?;
+
+    owner.close().await?;
+    participant.close().await?;
+    intruder.close().await?;
+
+    Ok(())
+}
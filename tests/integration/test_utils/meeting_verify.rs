@@ -0,0 +1,183 @@
+use anyhow::Result;
+use mpc_driver::{meeting, MeetingOptions, ServerOptions};
+use mpc_protocol::{
+    ed25519_dalek::SigningKey, generate_keypair, ErrorCode, Keypair,
+    MeetingSlot, UserId,
+};
+use mpc_relay_server::{RelayServer, ServerConfig, SpawnedServer};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Spawn a relay server on an ephemeral port.
+pub async fn spawn() -> Result<SpawnedServer> {
+    let (config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    let server = RelayServer::new(config, keypair);
+    Ok(server.spawn("127.0.0.1:0".parse()?).await?)
+}
+
+fn options(
+    server: &SpawnedServer,
+    keypair: Keypair,
+) -> MeetingOptions {
+    MeetingOptions {
+        keypair,
+        server: ServerOptions {
+            server_url: server.url(),
+            server_public_key: server.public_key().to_vec(),
+            pattern: None,
+            token: None,
+        },
+        encrypt_data: false,
+    }
+}
+
+/// Rejects a verifying key for the owner slot and joins a slot
+/// bound to a verifying key using the driver.
+pub async fn run(server: &SpawnedServer) -> Result<()> {
+    let owner_key = generate_keypair()?;
+
+    let init_id: [u8; 32] =
+        Sha256::digest("initiator".as_bytes()).into();
+    let part_id: [u8; 32] =
+        Sha256::digest("participant".as_bytes()).into();
+    let init_id: UserId = init_id.into();
+    let part_id: UserId = part_id.into();
+
+    let signing_key: [u8; 32] =
+        Sha256::digest("participant-signing-key".as_bytes()).into();
+    let signing_key = SigningKey::from_bytes(&signing_key);
+    let verifying_key = signing_key.verifying_key();
+
+    // Owner joins without a signature
+    let result = meeting::create(
+        options(server, owner_key.clone()),
+        vec![
+            MeetingSlot {
+                user_id: init_id.clone(),
+                role: None,
+                verifying_key: Some(verifying_key),
+            },
+            part_id.clone().into(),
+        ],
+        init_id.clone(),
+        false,
+        Value::Null,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(mpc_driver::Error::Client(ref e))
+            if e.code() == Some(ErrorCode::BadRequest)
+    ));
+
+    let slots = vec![
+        init_id.clone().into(),
+        MeetingSlot {
+            user_id: part_id.clone(),
+            role: None,
+            verifying_key: Some(verifying_key),
+        },
+    ];
+    let (meeting_id, secret) = meeting::create(
+        options(server, owner_key.clone()),
+        slots.clone(),
+        init_id.clone(),
+        false,
+        Value::Null,
+    )
+    .await?;
+    let join_code = secret.join_code.unwrap();
+
+    // Joining without the signing key is rejected
+    let result = meeting::join(
+        options(server, generate_keypair()?),
+        meeting_id,
+        Some(part_id.clone()),
+        join_code.clone(),
+        None,
+        slots.clone(),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(mpc_driver::Error::Client(
+            mpc_client::Error::PermissionDenied(_)
+        ))
+    ));
+
+    // Owner is already registered so only waits
+    // for the meeting to be ready
+    let part_key = generate_keypair()?;
+    let owner = meeting::join(
+        options(server, owner_key.clone()),
+        meeting_id,
+        None,
+        join_code.clone(),
+        None,
+        slots.clone(),
+    );
+    let participant = meeting::join(
+        options(server, part_key.clone()),
+        meeting_id,
+        Some(part_id.clone()),
+        join_code.clone(),
+        Some(signing_key.clone()),
+        slots.clone(),
+    );
+    let (owner, participant) =
+        futures::try_join!(owner, participant)?;
+
+    let expected = vec![
+        owner_key.public_key().to_vec(),
+        part_key.public_key().to_vec(),
+    ];
+    assert_eq!(expected, owner.0);
+    assert_eq!(expected, participant.0);
+
+    // Slots that do not match the meeting are rejected even
+    // though the relay returns valid signatures
+    let (meeting_id, secret) = meeting::create(
+        options(server, owner_key.clone()),
+        slots.clone(),
+        init_id.clone(),
+        false,
+        Value::Null,
+    )
+    .await?;
+    let join_code = secret.join_code.unwrap();
+
+    let other_key: [u8; 32] =
+        Sha256::digest("other-signing-key".as_bytes()).into();
+    let other_key = SigningKey::from_bytes(&other_key);
+    let owner = meeting::join(
+        options(server, owner_key),
+        meeting_id,
+        None,
+        join_code.clone(),
+        None,
+        vec![
+            init_id.into(),
+            MeetingSlot {
+                user_id: part_id.clone(),
+                role: None,
+                verifying_key: Some(other_key.verifying_key()),
+            },
+        ],
+    );
+    let participant = meeting::join(
+        options(server, generate_keypair()?),
+        meeting_id,
+        Some(part_id),
+        join_code,
+        Some(signing_key),
+        slots,
+    );
+    let (owner, participant) = futures::join!(owner, participant);
+    assert!(matches!(
+        owner,
+        Err(mpc_driver::Error::MeetingSignature(id)) if id == meeting_id
+    ));
+    assert!(participant.is_ok());
+    Ok(())
+}
@@ -1,11 +1,53 @@
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod auth_token;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod config_reload;
+pub(crate) mod duplicate_connection;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod embedded_router;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod ephemeral_server;
+pub(crate) mod error_code;
 pub(crate) mod gg20;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod heartbeat_timeout;
+pub(crate) mod http_channel;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod http_transport;
+pub(crate) mod idle_timeout;
 
+pub(crate) mod meeting_cancel;
+pub(crate) mod meeting_encrypt;
+pub(crate) mod meeting_join_code;
 pub(crate) mod meeting_point;
+pub(crate) mod meeting_signature;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod meeting_slots;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod meeting_verify;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod multiple_listeners;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod mutual_tls;
 pub(crate) mod peer_channel;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod peer_message_size;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod quic_transport;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod relay_policy;
 pub(crate) mod session_broadcast;
+pub(crate) mod session_expiring;
 pub(crate) mod session_handshake;
+pub(crate) mod session_limits;
+pub(crate) mod session_query;
+pub(crate) mod session_quorum;
 pub(crate) mod session_timeout;
 pub(crate) mod socket_close;
+#[cfg(unix)]
+pub(crate) mod unix_socket;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) mod webhooks;
 
 #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 pub(crate) mod native;
@@ -48,6 +90,9 @@ pub async fn new_client_with_keypair<E: From<mpc_client::Error>>(
         keypair,
         server_public_key,
         pattern: None,
+        idle_timeout: None,
+        token: None,
+        tls: None,
     };
     let url = options.url(server);
     let (client, event_loop) = Client::new(&url, options).await?;
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use mpc_relay_server::{
+    ListenerConfig, RelayServer, Route, ServerConfig, SpawnedServer,
+    TlsConfig,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+pub(crate) const PUBLIC: &str = "127.0.0.1:7339";
+pub(crate) const INTERNAL: &str = "127.0.0.1:7340";
+
+/// Spawn a relay server with a listener serving all routes
+/// and an internal listener that only serves the public key.
+pub async fn spawn() -> Result<SpawnedServer> {
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    config.listeners = vec![
+        ListenerConfig {
+            bind: PUBLIC.parse()?,
+            tls: None,
+            routes: None,
+        },
+        ListenerConfig {
+            bind: INTERNAL.parse()?,
+            tls: None,
+            routes: Some(vec![Route::PublicKey]),
+        },
+    ];
+    let server = RelayServer::new(config, keypair);
+    Ok(server.spawn("127.0.0.1:0".parse()?).await?)
+}
+
+/// Spawn a relay server with a listener on an ephemeral port
+/// whose TLS files do not exist.
+pub async fn spawn_missing_tls(
+) -> std::result::Result<SpawnedServer, mpc_relay_server::Error> {
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml")
+            .await
+            .expect("load server config");
+    config.listeners = vec![ListenerConfig {
+        bind: "127.0.0.1:0".parse().unwrap(),
+        tls: Some(TlsConfig {
+            cert: "tests/missing-cert.pem".into(),
+            key: "tests/missing-key.pem".into(),
+            ..Default::default()
+        }),
+        routes: None,
+    }];
+    let server = RelayServer::new(config, keypair);
+    server.spawn("127.0.0.1:0".parse().unwrap()).await
+}
+
+/// Fetch the server public key over plain HTTP.
+pub async fn public_key(addr: &str) -> Result<String> {
+    let mut stream = TcpStream::connect(addr).await?;
+    stream
+        .write_all(
+            b"GET /public-key HTTP/1.1\r\n\
+            Host: localhost\r\n\
+            Connection: close\r\n\r\n",
+        )
+        .await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    let (_, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| anyhow::anyhow!("invalid response"))?;
+    Ok(body.to_owned())
+}
@@ -0,0 +1,136 @@
+use anyhow::Result;
+use mpc_client::{
+    Client, ClientOptions, EventLoop, TlsIdentity, TlsOptions,
+};
+use mpc_protocol::{hex, Keypair};
+use mpc_relay_server::{
+    RelayServer, ServerConfig, SpawnedServer, TlsConfig,
+};
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams,
+    DistinguishedName, DnType, IsCa,
+};
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Counter so each authority writes to its own directory.
+static AUTHORITIES: AtomicUsize = AtomicUsize::new(0);
+
+/// Certificate authority for servers and clients.
+pub struct Authority {
+    ca: Certificate,
+    dir: PathBuf,
+}
+
+impl Authority {
+    /// Create a certificate authority that writes certificates
+    /// to a temporary directory.
+    pub fn new() -> Result<Self> {
+        let mut params = CertificateParams::new(Vec::new());
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let dir = std::env::temp_dir().join(format!(
+            "mpc-relay-tls-{}-{}",
+            std::process::id(),
+            AUTHORITIES.fetch_add(1, Ordering::SeqCst),
+        ));
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            ca: Certificate::from_params(params)?,
+            dir,
+        })
+    }
+
+    /// PEM-encoded certificate for the authority.
+    pub fn root(&self) -> Result<Vec<u8>> {
+        Ok(self.ca.serialize_pem()?.into_bytes())
+    }
+
+    /// Issue a client certificate with a common name.
+    pub fn identity(&self, common_name: &str) -> Result<TlsIdentity> {
+        let mut params = CertificateParams::new(Vec::new());
+        params.distinguished_name = DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, common_name);
+        let cert = Certificate::from_params(params)?;
+        Ok(TlsIdentity {
+            cert: cert
+                .serialize_pem_with_signer(&self.ca)?
+                .into_bytes(),
+            key: cert.serialize_private_key_pem().into_bytes(),
+        })
+    }
+
+    /// Write a server certificate and load a configuration
+    /// that requires client certificates.
+    pub async fn config(
+        &self,
+        bind_public_key: bool,
+    ) -> Result<(ServerConfig, Keypair)> {
+        let cert =
+            Certificate::from_params(CertificateParams::new(vec![
+                "localhost".to_owned(),
+            ]))?;
+        let tls = TlsConfig {
+            cert: self.dir.join("cert.pem"),
+            key: self.dir.join("key.pem"),
+            client_ca: Some(self.dir.join("ca.pem")),
+            bind_public_key,
+        };
+        std::fs::write(
+            &tls.cert,
+            cert.serialize_pem_with_signer(&self.ca)?,
+        )?;
+        std::fs::write(&tls.key, cert.serialize_private_key_pem())?;
+        std::fs::write(self.dir.join("ca.pem"), self.root()?)?;
+
+        let (mut config, keypair) =
+            ServerConfig::load("tests/config.toml").await?;
+        config.tls = Some(tls);
+        Ok((config, keypair))
+    }
+
+    /// Spawn a server that requires client certificates
+    /// bound to public keys.
+    pub async fn spawn(&self) -> Result<SpawnedServer> {
+        let (config, keypair) = self.config(true).await?;
+        let server = RelayServer::new(config, keypair);
+        Ok(server.spawn("127.0.0.1:0".parse()?).await?)
+    }
+}
+
+impl Drop for Authority {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Create a client connected to the server over TLS.
+pub async fn new_client(
+    server: &SpawnedServer,
+    keypair: Keypair,
+    roots: Vec<u8>,
+    identity: Option<TlsIdentity>,
+) -> mpc_client::Result<(Client, EventLoop)> {
+    let options = ClientOptions {
+        keypair,
+        server_public_key: server.public_key().to_vec(),
+        pattern: None,
+        idle_timeout: None,
+        token: None,
+        tls: Some(TlsOptions {
+            roots: Some(roots),
+            identity,
+        }),
+    };
+    let url = options
+        .url(&format!("wss://localhost:{}", server.addr().port()));
+    Client::new(&url, options).await
+}
+
+/// Common name that binds a certificate to a public key.
+pub fn common_name(keypair: &Keypair) -> String {
+    hex::encode(keypair.public_key())
+}
@@ -1,9 +1,14 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use axum_server::Handle;
+use futures::StreamExt;
 
 use std::{net::SocketAddr, thread};
-use tokio::{fs, sync::oneshot};
+use tokio::{
+    fs,
+    sync::{mpsc, oneshot},
+};
 
+use mpc_client::{Error, Event, EventLoop};
 use mpc_protocol::decode_keypair;
 
 use mpc_relay_server::{RelayServer, ServerConfig};
@@ -11,6 +16,9 @@ use mpc_relay_server::{RelayServer, ServerConfig};
 const ADDR: &str = "127.0.0.1:7337";
 pub(crate) const SERVER: &str = "ws://localhost:7337";
 
+/// Events from an event loop driven by a task.
+pub type Events = mpsc::UnboundedReceiver<Result<Event, Error>>;
+
 /// Get the public key for the test server.
 pub async fn server_public_key() -> Result<Vec<u8>> {
     let contents = fs::read_to_string("tests/test.pem").await?;
@@ -108,3 +116,46 @@ pub fn spawn_server(
     let handle = MockServer::spawn(tx)?;
     Ok((rx, handle))
 }
+
+/// Drive an event loop in a task so peer handshakes are
+/// answered while the test waits on another client.
+pub fn spawn_events(event_loop: EventLoop) -> Events {
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::task::spawn(async move {
+        let mut s = event_loop.run();
+        while let Some(event) = s.next().await {
+            if tx.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// Wait for an event that matches the predicate.
+pub async fn wait_event(
+    events: &mut Events,
+    predicate: impl Fn(&Event) -> bool,
+) -> Result<Event> {
+    while let Some(event) = events.recv().await {
+        match event {
+            Ok(event) if predicate(&event) => return Ok(event),
+            Ok(Event::Close | Event::ConnectionLost) => break,
+            Ok(_) => {}
+            Err(e) => bail!("unexpected error {}", e),
+        }
+    }
+    bail!("connection closed waiting for event")
+}
+
+/// Wait for an error returned by the server.
+pub async fn wait_error(events: &mut Events) -> Result<Error> {
+    while let Some(event) = events.recv().await {
+        match event {
+            Err(e) => return Ok(e),
+            Ok(Event::Close | Event::ConnectionLost) => break,
+            Ok(_) => {}
+        }
+    }
+    bail!("connection closed waiting for error")
+}
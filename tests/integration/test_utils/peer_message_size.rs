@@ -0,0 +1,67 @@
+use anyhow::Result;
+use mpc_client::{Event, NetworkTransport};
+use mpc_protocol::ErrorCode;
+use mpc_relay_server::{RelayServer, ServerConfig, SpawnedServer};
+
+use super::{new_client, spawn_events, wait_error, wait_event};
+
+/// Spawn a relay server on an ephemeral port with a small
+/// maximum message size.
+pub async fn spawn() -> Result<SpawnedServer> {
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    config.session.max_message_size = 1024;
+    let server = RelayServer::new(config, keypair);
+    Ok(server.spawn("127.0.0.1:0".parse()?).await?)
+}
+
+/// Handshakes between two clients and sends messages without
+/// a session either side of the maximum message size.
+pub async fn run(server: &SpawnedServer) -> Result<()> {
+    let url = server.url();
+    let server_public_key = server.public_key().to_vec();
+
+    let (mut sender, event_loop_s, _) =
+        new_client::<anyhow::Error>(&url, server_public_key.clone())
+            .await?;
+    let (mut receiver, event_loop_r, receiver_key) =
+        new_client::<anyhow::Error>(&url, server_public_key.clone())
+            .await?;
+    let receiver_key = receiver_key.public_key().to_vec();
+
+    sender.connect().await?;
+    receiver.connect().await?;
+
+    let mut events_s = spawn_events(event_loop_s);
+    let mut events_r = spawn_events(event_loop_r);
+    for events in [&mut events_s, &mut events_r] {
+        wait_event(events, |event| {
+            matches!(event, Event::ServerConnected { .. })
+        })
+        .await?;
+    }
+
+    sender.connect_peer(&receiver_key).await?;
+    for events in [&mut events_s, &mut events_r] {
+        wait_event(events, |event| {
+            matches!(event, Event::PeerConnected { .. })
+        })
+        .await?;
+    }
+
+    sender.send_json(&receiver_key, &"hello", None).await?;
+    wait_event(&mut events_r, |event| {
+        matches!(event, Event::JsonMessage { .. })
+    })
+    .await?;
+
+    let message = "x".repeat(2048);
+    sender.send_json(&receiver_key, &message, None).await?;
+    let error = wait_error(&mut events_s).await?;
+    assert_eq!(Some(ErrorCode::BadRequest), error.code());
+
+    sender.close().await?;
+    receiver.close().await?;
+
+    Ok(())
+}
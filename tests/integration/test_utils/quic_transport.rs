@@ -0,0 +1,287 @@
+use anyhow::Result;
+use futures::{
+    future::{self, BoxFuture},
+    select, FutureExt, StreamExt,
+};
+use mpc_client::{
+    ClientOptions, Event, EventStream, NetworkTransport,
+    QuicEventLoop, TlsOptions, Transport,
+};
+use mpc_protocol::generate_keypair;
+use mpc_relay_server::{
+    QuicConfig, RelayServer, ServerConfig, SpawnedServer, TlsConfig,
+};
+use rcgen::{Certificate, CertificateParams};
+use std::{net::SocketAddr, time::Duration};
+use tokio::sync::mpsc;
+
+/// Spawn a relay server with a QUIC endpoint on ephemeral
+/// ports that sends pings every second and disconnects idle
+/// clients after two seconds.
+///
+/// Returns the server and the PEM-encoded self-signed
+/// certificate for the QUIC endpoint.
+pub async fn spawn() -> Result<(SpawnedServer, Vec<u8>)> {
+    let cert =
+        Certificate::from_params(CertificateParams::new(vec![
+            "localhost".to_owned(),
+        ]))?;
+    let root = cert.serialize_pem()?.into_bytes();
+
+    let dir = std::env::temp_dir()
+        .join(format!("mpc-relay-quic-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let tls = TlsConfig {
+        cert: dir.join("cert.pem"),
+        key: dir.join("key.pem"),
+        client_ca: None,
+        bind_public_key: false,
+    };
+    std::fs::write(&tls.cert, &root)?;
+    std::fs::write(&tls.key, cert.serialize_private_key_pem())?;
+
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    config.heartbeat.interval = 1;
+    config.heartbeat.timeout = 2;
+    config.quic = Some(QuicConfig {
+        bind: "127.0.0.1:0".parse()?,
+        tls,
+    });
+    let server = RelayServer::new(config, keypair);
+    let addr: SocketAddr = "127.0.0.1:0".parse()?;
+    let server = server.spawn(addr).await;
+
+    // Certificates are loaded when the server starts
+    std::fs::remove_dir_all(&dir)?;
+    Ok((server?, root))
+}
+
+fn options(
+    server_public_key: Vec<u8>,
+    tls: Option<TlsOptions>,
+) -> Result<ClientOptions> {
+    Ok(ClientOptions {
+        keypair: generate_keypair()?,
+        server_public_key,
+        pattern: None,
+        idle_timeout: Some(Duration::from_secs(5)),
+        token: None,
+        tls,
+    })
+}
+
+/// Connect a client using the QUIC transport.
+async fn new_quic(
+    server: &SpawnedServer,
+    root: Vec<u8>,
+) -> Result<(Transport, QuicEventLoop)> {
+    let quic_addr = server.quic_addr().expect("quic endpoint");
+    let url = format!("quic://localhost:{}", quic_addr.port());
+    let tls = TlsOptions {
+        roots: Some(root),
+        identity: None,
+    };
+    Ok(Transport::new_quic(
+        &url,
+        options(server.public_key().to_vec(), Some(tls))?,
+    )
+    .await?)
+}
+
+/// Connect a websocket client and a client using the QUIC
+/// transport then exchange messages over the peer channel
+/// once the heartbeat interval has elapsed a few times.
+pub async fn run(
+    server: &SpawnedServer,
+    root: Vec<u8>,
+) -> Result<()> {
+    let options_i = options(server.public_key().to_vec(), None)?;
+    let url = options_i.url(&server.url());
+    let (initiator, event_loop_i) =
+        Transport::new_relay(&url, options_i).await?;
+    let initiator_key = initiator.public_key().to_vec();
+
+    let (participant, event_loop_p) = new_quic(server, root).await?;
+
+    let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>(1);
+    let (res_i, res_p) = futures::join!(
+        initiator_client(initiator, event_loop_i.run(), shutdown_tx),
+        participant_client(
+            participant,
+            event_loop_p.run(),
+            initiator_key,
+            shutdown_rx,
+        ),
+    );
+    res_i?;
+    res_p?;
+    Ok(())
+}
+
+async fn initiator_client(
+    mut client: Transport,
+    mut s: EventStream,
+    shutdown_tx: mpsc::Sender<()>,
+) -> Result<()> {
+    client.connect().await?;
+    while let Some(event) = s.next().await {
+        match &event? {
+            Event::PeerConnected { peer_key } => {
+                client.send_json(peer_key, "ping", None).await?;
+            }
+            Event::JsonMessage { message, .. } => {
+                let message: &str = message.deserialize()?;
+                if message == "pong" {
+                    let _ = shutdown_tx.send(()).await;
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+async fn participant_client(
+    mut client: Transport,
+    mut s: EventStream,
+    initiator_key: Vec<u8>,
+    mut shutdown_rx: mpsc::Receiver<()>,
+) -> Result<()> {
+    client.connect().await?;
+    let mut closed = false;
+    let mut idle: BoxFuture<'static, ()> =
+        Box::pin(future::pending());
+    loop {
+        select! {
+            event = s.next().fuse() => {
+                let event = match event {
+                    Some(event) => event?,
+                    None => break,
+                };
+                match &event {
+                    Event::ServerConnected { .. } => {
+                        // Stay idle for longer than the server
+                        // timeout so pings must be answered
+                        idle = Box::pin(tokio::time::sleep(
+                            Duration::from_secs(3),
+                        ));
+                    }
+                    Event::JsonMessage { peer_key, message, .. } => {
+                        let message: &str = message.deserialize()?;
+                        if message == "ping" {
+                            client
+                                .send_json(peer_key, "pong", None)
+                                .await?;
+                        }
+                    }
+                    Event::ConnectionLost => {
+                        anyhow::bail!("quic connection lost");
+                    }
+                    Event::Close => {
+                        closed = true;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            _ = (&mut idle).fuse() => {
+                idle = Box::pin(future::pending());
+                client.connect_peer(&initiator_key).await?;
+            }
+            shutdown = shutdown_rx.recv().fuse() => {
+                if shutdown.is_some() {
+                    client.close().await?;
+                }
+            }
+        }
+    }
+    assert!(closed);
+    Ok(())
+}
+
+/// Number of messages relayed between the QUIC clients, less
+/// than the capacity of the outbound channel as the messages
+/// are sent before the event loop is polled again.
+const MESSAGES: u32 = 16;
+
+/// Connect two clients using the QUIC transport then relay
+/// numbered messages between them and check the messages
+/// are received in order.
+pub async fn run_peers(
+    server: &SpawnedServer,
+    root: Vec<u8>,
+) -> Result<()> {
+    let (initiator, event_loop_i) =
+        new_quic(server, root.clone()).await?;
+    let (participant, event_loop_p) = new_quic(server, root).await?;
+    let initiator_key = initiator.public_key().to_vec();
+
+    let (res_i, res_p) = futures::join!(
+        sender_client(initiator, event_loop_i.run()),
+        receiver_client(
+            participant,
+            event_loop_p.run(),
+            initiator_key
+        ),
+    );
+    res_i?;
+    res_p?;
+    Ok(())
+}
+
+async fn sender_client(
+    mut client: Transport,
+    mut s: EventStream,
+) -> Result<()> {
+    client.connect().await?;
+    while let Some(event) = s.next().await {
+        match &event? {
+            Event::PeerConnected { peer_key } => {
+                for number in 0..MESSAGES {
+                    client.send_json(peer_key, &number, None).await?;
+                }
+            }
+            Event::JsonMessage { message, .. } => {
+                let message: &str = message.deserialize()?;
+                assert_eq!("done", message);
+                break;
+            }
+            _ => {}
+        }
+    }
+    client.close().await?;
+    Ok(())
+}
+
+async fn receiver_client(
+    mut client: Transport,
+    mut s: EventStream,
+    initiator_key: Vec<u8>,
+) -> Result<()> {
+    client.connect().await?;
+    let mut expected = 0;
+    while let Some(event) = s.next().await {
+        match &event? {
+            Event::ServerConnected { .. } => {
+                client.connect_peer(&initiator_key).await?;
+            }
+            Event::JsonMessage {
+                peer_key, message, ..
+            } => {
+                let number: u32 = message.deserialize()?;
+                assert_eq!(expected, number);
+                expected += 1;
+                if expected == MESSAGES {
+                    client.send_json(peer_key, "done", None).await?;
+                    client.close().await?;
+                }
+            }
+            Event::Close => break,
+            _ => {}
+        }
+    }
+    assert_eq!(MESSAGES, expected);
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use anyhow::Result;
+use mpc_client::{Error, Event, NetworkTransport};
+use mpc_protocol::{SessionId, SessionLimits};
+use mpc_relay_server::{
+    RelayPolicy, RelayServer, ServerConfig, SpawnedServer,
+};
+use serde_json::Value;
+
+use super::{new_client, spawn_events, wait_error, wait_event};
+
+/// Spawn a relay server on an ephemeral port that only relays
+/// between participants.
+pub async fn spawn() -> Result<SpawnedServer> {
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    config.relay = RelayPolicy::Participants;
+    let server = RelayServer::new(config, keypair);
+    Ok(server.spawn("127.0.0.1:0".parse()?).await?)
+}
+
+/// Creates a session between two clients and checks that
+/// handshakes, messages and session connections involving a
+/// third client that is not a participant are rejected.
+pub async fn run(server: &SpawnedServer) -> Result<()> {
+    let url = server.url();
+    let server_public_key = server.public_key().to_vec();
+
+    let (mut owner, event_loop_o, _) =
+        new_client::<anyhow::Error>(&url, server_public_key.clone())
+            .await?;
+    let (mut participant, event_loop_p, participant_key) =
+        new_client::<anyhow::Error>(&url, server_public_key.clone())
+            .await?;
+    let (mut outsider, event_loop_x, outsider_key) =
+        new_client::<anyhow::Error>(&url, server_public_key.clone())
+            .await?;
+
+    let participant_key = participant_key.public_key().to_vec();
+    let outsider_key = outsider_key.public_key().to_vec();
+
+    owner.connect().await?;
+    participant.connect().await?;
+    outsider.connect().await?;
+
+    let mut events_o = spawn_events(event_loop_o);
+    let mut events_p = spawn_events(event_loop_p);
+    let mut events_x = spawn_events(event_loop_x);
+    for events in [&mut events_o, &mut events_p, &mut events_x] {
+        wait_event(events, |event| {
+            matches!(event, Event::ServerConnected { .. })
+        })
+        .await?;
+    }
+
+    // Handshake with a peer that does not share a session
+    owner.connect_peer(&outsider_key).await?;
+    let error = wait_error(&mut events_o).await?;
+    assert!(matches!(error, Error::PermissionDenied(_)));
+
+    owner
+        .new_session(
+            vec![participant_key.clone()],
+            None,
+            SessionLimits::default(),
+        )
+        .await?;
+    let session_id: SessionId =
+        match wait_event(&mut events_o, |event| {
+            matches!(event, Event::SessionCreated(_))
+        })
+        .await?
+        {
+            Event::SessionCreated(session) => session.session_id,
+            _ => unreachable!(),
+        };
+
+    // Co-participants may handshake and relay messages
+    owner.connect_peer(&participant_key).await?;
+    wait_event(&mut events_o, |event| {
+        matches!(event, Event::PeerConnected { .. })
+    })
+    .await?;
+    wait_event(&mut events_p, |event| {
+        matches!(event, Event::PeerConnected { .. })
+    })
+    .await?;
+
+    owner
+        .send_json(&participant_key, &"hello", Some(session_id))
+        .await?;
+    wait_event(&mut events_p, |event| {
+        matches!(event, Event::JsonMessage { .. })
+    })
+    .await?;
+
+    owner
+        .register_connection(&session_id, &participant_key)
+        .await?;
+    participant
+        .register_connection(&session_id, owner.public_key())
+        .await?;
+    wait_event(&mut events_o, |event| {
+        matches!(event, Event::SessionActive(_))
+    })
+    .await?;
+
+    // Session connection from a peer that is not a participant
+    outsider
+        .register_connection(&session_id, &participant_key)
+        .await?;
+    let error = wait_error(&mut events_x).await?;
+    assert!(matches!(error, Error::NotSessionParticipant(_)));
+
+    // Once the session is closed the peers no longer share
+    // a session so messages without a session are rejected
+    owner.close_session(session_id).await?;
+    wait_event(&mut events_o, |event| {
+        matches!(event, Event::SessionFinished(_))
+    })
+    .await?;
+
+    owner
+        .send_json(&participant_key, &Value::Null, None)
+        .await?;
+    let error = wait_error(&mut events_o).await?;
+    assert!(matches!(error, Error::PermissionDenied(_)));
+
+    owner.close().await?;
+    participant.close().await?;
+    outsider.close().await?;
+
+    Ok(())
+}
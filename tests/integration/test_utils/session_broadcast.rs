@@ -19,6 +19,23 @@ pub struct ClientState {
 pub async fn run(
     server: &str,
     server_public_key: Vec<u8>,
+) -> Result<SessionResult> {
+    broadcast(server, server_public_key, true).await
+}
+
+/// Participants do not join the session explicitly and are
+/// counted as joined once connected as no quorum is requested.
+pub async fn run_without_join(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<SessionResult> {
+    broadcast(server, server_public_key, false).await
+}
+
+async fn broadcast(
+    server: &str,
+    server_public_key: Vec<u8>,
+    join: bool,
 ) -> Result<SessionResult> {
     let mut completed: Vec<u8> = Vec::new();
     let session_result = Arc::new(Mutex::new(vec![]));
@@ -100,6 +117,7 @@ pub async fn run(
                                 2u8,
                                 &mut client_p_1,
                                 event,
+                                join,
                                 Arc::clone(&state_2),
                                 Arc::clone(&session_result),
                             ).await?;
@@ -121,6 +139,7 @@ pub async fn run(
                                 3u8,
                                 &mut client_p_2,
                                 event,
+                                join,
                                 Arc::clone(&state_3),
                                 Arc::clone(&session_result),
                             ).await?;
@@ -152,7 +171,13 @@ async fn initiator(
         Event::ServerConnected { .. } => {
             tracing::info!("initiator connected to server");
             // Initiate a session context for broadcasting
-            client.new_session(session_participants).await?;
+            client
+                .new_session(
+                    session_participants,
+                    None,
+                    Default::default(),
+                )
+                .await?;
         }
         Event::SessionCreated(session) => {
             tracing::info!(
@@ -239,10 +264,20 @@ async fn participant(
     number: u8,
     client: &mut Client,
     event: Event,
+    join: bool,
     session_state: Arc<Mutex<ClientState>>,
     session_result: SessionResult,
 ) -> Result<bool> {
     match event {
+        Event::SessionInvitation(session) => {
+            tracing::info!(
+                id = ?session.session_id.to_string(),
+                "participant session invitation");
+
+            if join {
+                client.join_session(session.session_id).await?;
+            }
+        }
         Event::SessionReady(session) => {
             tracing::info!(
                 id = ?session.session_id.to_string(),
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use futures::StreamExt;
+use mpc_client::{Event, NetworkTransport};
+use mpc_protocol::{SessionId, SessionLimits};
+
+use super::new_client;
+
+pub async fn run(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<(SessionId, SessionId, u64)> {
+    // Create new clients
+    let (mut initiator, event_loop_i, _initiator_key) =
+        new_client::<anyhow::Error>(
+            server,
+            server_public_key.clone(),
+        )
+        .await?;
+    let (_participant, _event_loop_p, participant_key) =
+        new_client::<anyhow::Error>(
+            server,
+            server_public_key.clone(),
+        )
+        .await?;
+
+    let session_participants =
+        vec![participant_key.public_key().to_vec()];
+
+    // Timeout within the server expiry warning period
+    let limits = SessionLimits {
+        timeout: Some(3),
+        ..Default::default()
+    };
+
+    initiator.connect().await?;
+
+    let mut session_id: Option<SessionId> = None;
+    let mut s = event_loop_i.run();
+    while let Some(event) = s.next().await {
+        let event = event?;
+        match event {
+            Event::ServerConnected { .. } => {
+                initiator
+                    .new_session(
+                        session_participants.clone(),
+                        None,
+                        limits,
+                    )
+                    .await?;
+            }
+            Event::SessionCreated(session) => {
+                session_id = Some(session.session_id);
+            }
+            Event::SessionExpiring(id, expires_in) => {
+                return Ok((session_id.unwrap(), id, expires_in));
+            }
+            _ => {}
+        }
+    }
+
+    unreachable!()
+}
@@ -41,6 +41,8 @@ pub async fn run(
     let mut client_i_session = SessionInitiator::new(
         client_i_transport,
         session_participants,
+        None,
+        Default::default(),
     );
     let mut client_p_session =
         SessionParticipant::new(client_p_transport);
@@ -0,0 +1,61 @@
+use anyhow::Result;
+use futures::StreamExt;
+use mpc_client::{Event, NetworkTransport};
+use mpc_protocol::{SessionLimits, SessionState};
+
+use super::new_client;
+
+pub async fn run(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<SessionState> {
+    // Create new clients
+    let (mut initiator, event_loop_i, _initiator_key) =
+        new_client::<anyhow::Error>(
+            server,
+            server_public_key.clone(),
+        )
+        .await?;
+    let (_participant, _event_loop_p, participant_key) =
+        new_client::<anyhow::Error>(
+            server,
+            server_public_key.clone(),
+        )
+        .await?;
+
+    let session_participants =
+        vec![participant_key.public_key().to_vec()];
+
+    // Request a timeout beyond the server maximum
+    // and a short wait timeout
+    let limits = SessionLimits {
+        timeout: Some(86400),
+        wait_timeout: Some(1),
+        max_message_size: Some(1024),
+        max_lifetime: None,
+    };
+
+    initiator.connect().await?;
+
+    let mut s = event_loop_i.run();
+    while let Some(event) = s.next().await {
+        let event = event?;
+        match event {
+            Event::ServerConnected { .. } => {
+                initiator
+                    .new_session(
+                        session_participants.clone(),
+                        None,
+                        limits,
+                    )
+                    .await?;
+            }
+            Event::SessionCreated(session) => {
+                return Ok(session);
+            }
+            _ => {}
+        }
+    }
+
+    unreachable!()
+}
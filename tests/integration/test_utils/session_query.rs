@@ -0,0 +1,77 @@
+use anyhow::Result;
+use futures::StreamExt;
+use mpc_client::{Error, Event, NetworkTransport};
+use mpc_protocol::{uuid::Uuid, SessionId, SessionStatus};
+
+use super::new_client;
+
+pub async fn run(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<(Vec<u8>, SessionStatus, Vec<SessionStatus>)> {
+    // Create new clients
+    let (mut initiator, event_loop_i, initiator_key) =
+        new_client::<anyhow::Error>(
+            server,
+            server_public_key.clone(),
+        )
+        .await?;
+    let (_participant, _event_loop_p, participant_key) =
+        new_client::<anyhow::Error>(
+            server,
+            server_public_key.clone(),
+        )
+        .await?;
+
+    // Participant never completes the server handshake
+    let session_participants =
+        vec![participant_key.public_key().to_vec()];
+
+    initiator.connect().await?;
+
+    let mut session_id: Option<SessionId> = None;
+    let mut status: Option<SessionStatus> = None;
+    let mut sessions: Option<Vec<SessionStatus>> = None;
+
+    let mut s = event_loop_i.run();
+    while let Some(event) = s.next().await {
+        match event {
+            Ok(Event::ServerConnected { .. }) => {
+                initiator
+                    .new_session(
+                        session_participants.clone(),
+                        None,
+                        Default::default(),
+                    )
+                    .await?;
+            }
+            Ok(Event::SessionCreated(session)) => {
+                session_id = Some(session.session_id);
+                initiator.get_session(session.session_id).await?;
+            }
+            Ok(Event::SessionInfo(info)) => {
+                status = Some(info);
+                initiator.list_sessions().await?;
+            }
+            Ok(Event::SessionList(list)) => {
+                sessions = Some(list);
+                // Query a session that does not exist
+                initiator.get_session(Uuid::new_v4()).await?;
+            }
+            Err(Error::SessionNotFound(_)) => {
+                break;
+            }
+            Err(e) => return Err(e.into()),
+            _ => {}
+        }
+    }
+
+    let status = status.unwrap();
+    assert_eq!(session_id, Some(status.state.session_id));
+
+    Ok((
+        initiator_key.public_key().to_vec(),
+        status,
+        sessions.unwrap(),
+    ))
+}
@@ -0,0 +1,187 @@
+use anyhow::Result;
+use futures::{select, FutureExt, StreamExt};
+
+use mpc_client::{NetworkTransport, Transport};
+use mpc_driver::{
+    Error, SessionEventHandler, SessionInitiator, SessionParticipant,
+};
+use mpc_protocol::{
+    generate_keypair, SessionId, SessionQuorum, SessionState,
+};
+
+use super::new_client;
+
+pub async fn run(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<Vec<SessionState>> {
+    let mut completed: Vec<SessionState> = Vec::new();
+
+    // Create new clients
+    let (client_i, event_loop_i, _) = new_client::<anyhow::Error>(
+        server,
+        server_public_key.clone(),
+    )
+    .await?;
+    let (client_p, event_loop_p, participant_key) =
+        new_client::<anyhow::Error>(
+            server,
+            server_public_key.clone(),
+        )
+        .await?;
+
+    let mut client_i_transport: Transport = client_i.into();
+    let mut client_p_transport: Transport = client_p.into();
+
+    // Invite a participant that never connects
+    let absent_key = generate_keypair()?;
+    let session_participants = vec![
+        participant_key.public_key().to_vec(),
+        absent_key.public_key().to_vec(),
+    ];
+
+    // Each client handshakes with the server
+    client_i_transport.connect().await?;
+    client_p_transport.connect().await?;
+
+    let mut client_i_session = SessionInitiator::new(
+        client_i_transport,
+        session_participants,
+        Some(SessionQuorum {
+            min_participants: 2,
+            deadline: 1,
+        }),
+        Default::default(),
+    );
+    let mut client_p_session =
+        SessionParticipant::new(client_p_transport);
+
+    let mut s_i = event_loop_i.run();
+    let mut s_p = event_loop_p.run();
+
+    loop {
+        if completed.len() == 2 {
+            break;
+        }
+
+        select! {
+            event = s_i.next().fuse() => {
+                match event {
+                    Some(event) => {
+                        let event = event?;
+
+                        if let Some(session) =
+                            client_i_session.handle_event(event).await? {
+                            completed.push(session);
+                        }
+                    }
+                    _ => {}
+                }
+            },
+            event = s_p.next().fuse() => {
+                match event {
+                    Some(event) => {
+                        let event = event?;
+                        if let Some(session) =
+                            client_p_session.handle_event(event).await? {
+                            completed.push(session);
+                        }
+                    }
+                    _ => {}
+                }
+            },
+        }
+    }
+
+    Ok(completed)
+}
+
+/// Connects a participant that only handles events once the
+/// session has proceeded with a quorum without it and returns
+/// the error for the excluded participant.
+pub async fn run_excluded(
+    server: &str,
+    server_public_key: Vec<u8>,
+) -> Result<(SessionId, Error)> {
+    let mut completed: Vec<SessionState> = Vec::new();
+
+    // Create new clients
+    let (client_i, event_loop_i, _) = new_client::<anyhow::Error>(
+        server,
+        server_public_key.clone(),
+    )
+    .await?;
+    let (client_p, event_loop_p, participant_key) =
+        new_client::<anyhow::Error>(
+            server,
+            server_public_key.clone(),
+        )
+        .await?;
+    let (client_l, event_loop_l, late_key) =
+        new_client::<anyhow::Error>(
+            server,
+            server_public_key.clone(),
+        )
+        .await?;
+
+    let mut client_i_transport: Transport = client_i.into();
+    let mut client_p_transport: Transport = client_p.into();
+    let mut client_l_transport: Transport = client_l.into();
+
+    let session_participants = vec![
+        participant_key.public_key().to_vec(),
+        late_key.public_key().to_vec(),
+    ];
+
+    // Each client handshakes with the server
+    client_i_transport.connect().await?;
+    client_p_transport.connect().await?;
+    client_l_transport.connect().await?;
+
+    let mut client_i_session = SessionInitiator::new(
+        client_i_transport,
+        session_participants,
+        Some(SessionQuorum {
+            min_participants: 2,
+            deadline: 1,
+        }),
+        Default::default(),
+    );
+    let mut client_p_session =
+        SessionParticipant::new(client_p_transport);
+    let mut client_l_session =
+        SessionParticipant::new(client_l_transport);
+
+    let mut s_i = event_loop_i.run();
+    let mut s_p = event_loop_p.run();
+
+    // Late participant does not join before the deadline
+    while completed.len() < 2 {
+        select! {
+            event = s_i.next().fuse() => {
+                if let Some(event) = event {
+                    if let Some(session) =
+                        client_i_session.handle_event(event?).await? {
+                        completed.push(session);
+                    }
+                }
+            },
+            event = s_p.next().fuse() => {
+                if let Some(event) = event {
+                    if let Some(session) =
+                        client_p_session.handle_event(event?).await? {
+                        completed.push(session);
+                    }
+                }
+            },
+        }
+    }
+
+    let mut s_l = event_loop_l.run();
+    while let Some(event) = s_l.next().await {
+        if let Err(e) = client_l_session.handle_event(event?).await {
+            return Ok((completed[0].session_id, e));
+        }
+    }
+    anyhow::bail!("late participant was not excluded")
+}
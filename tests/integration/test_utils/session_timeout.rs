@@ -39,7 +39,11 @@ pub async fn run(
         match &event {
             Event::ServerConnected { .. } => {
                 initiator
-                    .new_session(session_participants.clone())
+                    .new_session(
+                        session_participants.clone(),
+                        None,
+                        Default::default(),
+                    )
                     .await?;
             }
             Event::SessionTimeout(_) => {
@@ -0,0 +1,52 @@
+use anyhow::Result;
+use mpc_client::{Client, ClientOptions, EventLoop};
+use mpc_protocol::generate_keypair;
+use mpc_relay_server::{RelayServer, ServerConfig, UnixSocketConfig};
+use std::path::{Path, PathBuf};
+use tokio::task::JoinHandle;
+
+/// Spawn a relay server listening only on a Unix domain socket.
+pub async fn spawn(
+    path: PathBuf,
+    mode: u32,
+) -> Result<(JoinHandle<()>, Vec<u8>)> {
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    config.unix = Some(UnixSocketConfig {
+        path: path.clone(),
+        mode: Some(mode),
+    });
+    let public_key = keypair.public_key().to_vec();
+    let server = RelayServer::new(config, keypair);
+    let task = tokio::task::spawn(async move {
+        server
+            .start_listeners()
+            .await
+            .expect("failed to start server");
+    });
+
+    // Wait for the server to bind the socket
+    while !path.exists() {
+        tokio::time::sleep(std::time::Duration::from_millis(10))
+            .await;
+    }
+
+    Ok((task, public_key))
+}
+
+/// Create a client connected over a Unix domain socket.
+pub async fn new_client(
+    path: &Path,
+    server_public_key: Vec<u8>,
+) -> Result<(Client, EventLoop)> {
+    let options = ClientOptions {
+        keypair: generate_keypair()?,
+        server_public_key,
+        pattern: None,
+        idle_timeout: None,
+        token: None,
+        tls: None,
+    };
+    let url = options.url("ws://localhost");
+    Ok(Client::new_unix(path, &url, options).await?)
+}
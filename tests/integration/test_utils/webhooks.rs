@@ -0,0 +1,145 @@
+use anyhow::Result;
+use axum_server::Handle;
+use futures::future;
+use hmac::{Hmac, Mac};
+use mpc_protocol::hex;
+use mpc_relay_server::{
+    axum::{
+        body::Bytes, extract::State, http::HeaderMap,
+        http::StatusCode, routing::post, Router,
+    },
+    RelayServer, ServerConfig, SpawnedServer, WebhookConfig,
+};
+use serde_json::Value;
+use sha2::Sha256;
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::mpsc;
+
+/// Secret used to sign events.
+pub const SECRET: &str = "webhook-secret";
+
+/// Request received by the webhook.
+pub struct Received {
+    /// Status code returned for the request.
+    pub status: StatusCode,
+    /// Value of the signature header.
+    pub signature: String,
+    /// Request body.
+    pub body: Vec<u8>,
+}
+
+impl Received {
+    /// Parse the body as JSON.
+    pub fn event(&self) -> Result<Value> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    /// Determine if the signature is valid for the body.
+    pub fn is_signed(&self) -> bool {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(SECRET.as_bytes())
+                .unwrap();
+        mac.update(&self.body);
+        let expected = format!(
+            "sha256={}",
+            hex::encode(mac.finalize().into_bytes())
+        );
+        self.signature == expected
+    }
+}
+
+#[derive(Clone)]
+struct Receiver {
+    tx: mpsc::UnboundedSender<Received>,
+    failed: Arc<AtomicBool>,
+}
+
+/// Spawn a webhook that responds to the first request with
+/// a service unavailable status code so it is retried.
+pub async fn receiver(
+) -> Result<(SocketAddr, Handle, mpsc::UnboundedReceiver<Received>)> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let app = Router::new()
+        .route("/events", post(receive))
+        .with_state(Receiver {
+            tx,
+            failed: Arc::new(AtomicBool::new(false)),
+        });
+
+    let handle = Handle::new();
+    let listen_handle = handle.clone();
+    let addr: SocketAddr = "127.0.0.1:0".parse()?;
+    tokio::spawn(async move {
+        axum_server::bind(addr)
+            .handle(listen_handle)
+            .serve(app.into_make_service())
+            .await
+            .expect("failed to serve webhook");
+    });
+    let addr = handle.listening().await.expect("webhook listening");
+    Ok((addr, handle, rx))
+}
+
+async fn receive(
+    State(receiver): State<Receiver>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let status = if receiver.failed.swap(true, Ordering::SeqCst) {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let signature = headers
+        .get("x-relay-signature")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_owned();
+    let _ = receiver.tx.send(Received {
+        status,
+        signature,
+        body: body.to_vec(),
+    });
+    status
+}
+
+/// Spawn a webhook that never responds to requests.
+pub async fn stalled() -> Result<(SocketAddr, Handle)> {
+    let app = Router::new()
+        .route("/events", post(future::pending::<StatusCode>));
+
+    let handle = Handle::new();
+    let listen_handle = handle.clone();
+    let addr: SocketAddr = "127.0.0.1:0".parse()?;
+    tokio::spawn(async move {
+        axum_server::bind(addr)
+            .handle(listen_handle)
+            .serve(app.into_make_service())
+            .await
+            .expect("failed to serve webhook");
+    });
+    let addr = handle.listening().await.expect("webhook listening");
+    Ok((addr, handle))
+}
+
+/// Spawn a relay server that sends events to the webhooks.
+pub async fn spawn(webhooks: &[SocketAddr]) -> Result<SpawnedServer> {
+    let (mut config, keypair) =
+        ServerConfig::load("tests/config.toml").await?;
+    config.webhooks = Some(WebhookConfig {
+        urls: webhooks
+            .iter()
+            .map(|addr| format!("http://{}/events", addr).parse())
+            .collect::<Result<_, _>>()?,
+        secret: SECRET.to_owned(),
+        ..Default::default()
+    });
+    let server = RelayServer::new(config, keypair);
+    Ok(server.spawn("127.0.0.1:0".parse()?).await?)
+}
@@ -0,0 +1,59 @@
+use crate::test_utils::unix_socket;
+use anyhow::Result;
+use futures::StreamExt;
+use mpc_client::{Event, NetworkTransport};
+use std::os::unix::fs::PermissionsExt;
+
+/// Connects a client to a server listening only on a
+/// Unix domain socket.
+#[tokio::test]
+async fn integration_unix_socket() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let path = std::env::temp_dir()
+        .join(format!("mpc-relay-{}.sock", std::process::id()));
+    let (server, server_public_key) =
+        unix_socket::spawn(path.clone(), 0o600).await?;
+
+    let mode = std::fs::metadata(&path)?.permissions().mode();
+    assert_eq!(0o600, mode & 0o777);
+
+    // Directory used to set the permissions is removed
+    let parent = path.parent().expect("socket directory");
+    assert!(!std::fs::read_dir(parent)?.any(|entry| {
+        entry
+            .map(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(".mpc-relay-")
+            })
+            .unwrap_or(false)
+    }));
+
+    let (mut client, event_loop) =
+        unix_socket::new_client(&path, server_public_key).await?;
+    client.connect().await?;
+
+    let mut connected = false;
+    let mut s = event_loop.run();
+    while let Some(event) = s.next().await {
+        let event = event?;
+        match &event {
+            Event::ServerConnected { .. } => {
+                connected = true;
+                client.close().await?;
+            }
+            Event::Close => {
+                break;
+            }
+            _ => {}
+        }
+    }
+    assert!(connected);
+
+    server.abort();
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}
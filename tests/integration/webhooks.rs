@@ -0,0 +1,101 @@
+use anyhow::Result;
+use mpc_relay_server::axum::http::StatusCode;
+use std::time::Duration;
+
+use crate::test_utils::{session_handshake, webhooks};
+
+/// Sends signed session lifecycle events to a webhook
+/// and retries events the webhook fails to accept.
+#[tokio::test]
+async fn integration_webhooks() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let (addr, _handle, mut rx) = webhooks::receiver().await?;
+    let server = webhooks::spawn(&[addr]).await?;
+
+    let connected_participants = session_handshake::run(
+        &server.url(),
+        server.public_key().to_vec(),
+    )
+    .await?;
+    assert_eq!(2, connected_participants);
+
+    let mut received = Vec::new();
+    loop {
+        let request =
+            tokio::time::timeout(Duration::from_secs(10), rx.recv())
+                .await?
+                .expect("webhook request");
+        assert!(request.is_signed());
+        let event = request.event()?;
+        let name = event["event"].as_str().unwrap_or_default();
+        let done = name == "session-active"
+            && request.status == StatusCode::OK;
+        received.push((request.status, event));
+        if done {
+            break;
+        }
+    }
+
+    // First attempt is rejected and retried with the same event
+    let (status, created) = &received[0];
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, *status);
+    assert_eq!("session-created", created["event"]);
+    assert_eq!(2, created["participants"].as_array().unwrap().len());
+    let (status, retried) = &received[1];
+    assert_eq!(StatusCode::OK, *status);
+    assert_eq!(created["id"], retried["id"]);
+    assert_eq!(created["timestamp"], retried["timestamp"]);
+
+    let (_, active) = received.last().unwrap();
+    assert_eq!(created["session_id"], active["session_id"]);
+
+    // Only metadata is sent
+    for (_, event) in &received {
+        assert!(event.get("data").is_none());
+        assert!(event.get("envelope").is_none());
+    }
+
+    Ok(())
+}
+
+/// Sends events to a webhook without waiting for another
+/// webhook that never responds.
+#[tokio::test]
+async fn integration_webhooks_stalled() -> Result<()> {
+    //crate::test_utils::init_tracing();
+
+    let (stalled, _stalled_handle) = webhooks::stalled().await?;
+    let (addr, _handle, mut rx) = webhooks::receiver().await?;
+    let server = webhooks::spawn(&[stalled, addr]).await?;
+
+    let connected_participants = session_handshake::run(
+        &server.url(),
+        server.public_key().to_vec(),
+    )
+    .await?;
+    assert_eq!(2, connected_participants);
+
+    // Requests to the stalled webhook time out after ten
+    // seconds so events must not wait for them
+    let mut names = Vec::new();
+    loop {
+        let request =
+            tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                .await?
+                .expect("webhook request");
+        assert!(request.is_signed());
+        if request.status != StatusCode::OK {
+            continue;
+        }
+        let event = request.event()?;
+        let name = event["event"].as_str().unwrap_or_default();
+        names.push(name.to_owned());
+        if name == "session-active" {
+            break;
+        }
+    }
+    assert_eq!(vec!["session-created", "session-active"], names);
+
+    Ok(())
+}
@@ -8,12 +8,14 @@ mod wasm_tests {
     use wasm_bindgen_test::*;
 
     use super::integration::test_utils::{
-        gg20, meeting_point, peer_channel, session_broadcast,
-        session_handshake, session_timeout, socket_close,
+        gg20, http_channel, meeting_point, peer_channel,
+        session_broadcast, session_handshake, session_timeout,
+        socket_close,
     };
     use mpc_protocol::hex;
 
     const SERVER: &str = "ws://127.0.0.1:8008";
+    const HTTP_SERVER: &str = "http://127.0.0.1:8008";
     const SERVER_PUBLIC_KEY: &str =
         include_str!("./server_public_key.txt");
 
@@ -30,6 +32,20 @@ mod wasm_tests {
         Ok(())
     }
 
+    /// Creates a websocket client and a client using the HTTP
+    /// transport that exchange "ping" and "pong" messages over
+    /// the noise transport channel.
+    #[wasm_bindgen_test]
+    async fn http_channel() -> Result<(), JsValue> {
+        let _ = wasm_log::try_init(wasm_log::Config::default());
+        let server_public_key =
+            hex::decode(SERVER_PUBLIC_KEY.trim()).unwrap();
+        http_channel::run(SERVER, HTTP_SERVER, server_public_key)
+            .await
+            .unwrap();
+        Ok(())
+    }
+
     /// Creates three clients that handshake with the server
     /// and then each other.
     ///